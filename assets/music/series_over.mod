@@ -0,0 +1,3 @@
+version https://git-lfs.github.com/spec/v1
+oid sha256:ac31d831917dd84122731ee9745e0c59c6d6433e37de5185de819d4029e73d1b
+size 4396
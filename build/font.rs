@@ -0,0 +1,269 @@
+use std::path::Path;
+
+use crate::palette::Palette;
+
+pub mod codegen;
+
+const GLYPH_SIZE: usize = 8;
+
+/// Logical colour indices used in the glyph tiles, before they are mapped to a palette.
+pub const INK: u8 = 1;
+pub const SHADOW: u8 = 2;
+/// Never used in the tiles, the ink is swapped for it when drawing greyed out text.
+pub const DIM: u8 = 3;
+/// Also never used in the tiles, these replace the ink for text in the players' colours.
+pub const RED: u8 = 4;
+pub const YELLOW: u8 = 5;
+/// The first of the glow colours, which are cycled through to make text shine.
+pub const GLOW: u8 = 6;
+
+#[derive(Debug)]
+pub struct FontError(String);
+
+pub struct Glyph {
+    character: char,
+    /// One value per pixel, row by row: 0 for empty, INK or SHADOW.
+    pixels: Vec<u8>,
+    /// How far along to move before drawing the next glyph.
+    width: u8,
+}
+
+pub struct Font {
+    ink: u16,
+    shadow: u16,
+    dim: u16,
+    red: u16,
+    yellow: u16,
+    glow: Vec<u16>,
+    /// Sorted by character, so they can be binary searched at runtime.
+    glyphs: Vec<Glyph>,
+}
+
+impl Font {
+    /// The font's colours, in the order of their logical indices.
+    pub fn palette(&self) -> Palette {
+        let mut colors = vec![self.ink, self.shadow, self.dim, self.red, self.yellow];
+        colors.extend(&self.glow);
+
+        Palette::new(colors)
+    }
+
+    /// How many glow colours there are to cycle through.
+    pub fn glow_len(&self) -> usize {
+        self.glow.len()
+    }
+
+    pub fn has_glyph(&self, character: char) -> bool {
+        self.glyphs
+            .binary_search_by_key(&character, |glyph| glyph.character)
+            .is_ok()
+    }
+}
+
+/// Read a font sheet.
+///
+/// Font sheets are text files made up of `key: value` headers, followed by each glyph.
+/// A glyph starts with a `glyph: <character>` line, followed by up to 8 rows of up to 8 pixels:
+/// `X` for ink and `.` for empty. The drop shadow is added automatically.
+/// Lines starting with `#` are comments.
+///
+/// ```text
+/// ink: f8f8f8
+/// shadow: 283048
+/// dim: 787c90
+/// red: e83828
+/// yellow: f8c820
+/// glow: f8f0a8 f8d860 f8b838 f8d070
+/// space_width: 4
+///
+/// glyph: !
+/// X
+/// X
+/// .
+/// X
+/// ```
+pub fn find_font(path: &Path) -> Result<Font, FontError> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|_e| FontError("Failed to read font file.".to_string()))?;
+
+    parse_font(&text)
+}
+
+fn parse_font(text: &str) -> Result<Font, FontError> {
+    let mut ink = None;
+    let mut shadow = None;
+    let mut dim = None;
+    let mut red = None;
+    let mut yellow = None;
+    let mut glow = None;
+    let mut space_width = None;
+
+    // Each glyph's character, along with its rows.
+    let mut glyph_rows: Vec<(char, Vec<&str>)> = Vec::new();
+
+    for line in text.lines().map(|line| line.trim()) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        if let Some((key, value)) = line.split_once(':') {
+            let value = value.trim();
+
+            match key.trim() {
+                "ink" => ink = Some(parse_color(value)?),
+                "shadow" => shadow = Some(parse_color(value)?),
+                "dim" => dim = Some(parse_color(value)?),
+                "red" => red = Some(parse_color(value)?),
+                "yellow" => yellow = Some(parse_color(value)?),
+                "glow" => {
+                    let colors: Result<Vec<u16>, FontError> =
+                        value.split_whitespace().map(parse_color).collect();
+                    glow = Some(colors?);
+                }
+                "space_width" => {
+                    let width = value.parse::<u8>().map_err(|_e| {
+                        FontError(format!("'{}' is not a valid space width.", value))
+                    })?;
+                    space_width = Some(width);
+                }
+                "glyph" => {
+                    let mut chars = value.chars();
+
+                    let character = match (chars.next(), chars.next()) {
+                        (Some(character), None) => character,
+                        _ => {
+                            return Err(FontError(format!(
+                                "'{}' is not a single character.",
+                                value
+                            )))
+                        }
+                    };
+
+                    glyph_rows.push((character, Vec::new()));
+                }
+                key => return Err(FontError(format!("Unknown key '{}'.", key))),
+            }
+        } else {
+            match glyph_rows.last_mut() {
+                Some((_, rows)) => rows.push(line),
+                None => {
+                    return Err(FontError(
+                        "Found glyph pixels before the first glyph.".to_string(),
+                    ))
+                }
+            }
+        }
+    }
+
+    let ink = ink.ok_or_else(|| FontError("Missing 'ink' colour.".to_string()))?;
+    let shadow = shadow.ok_or_else(|| FontError("Missing 'shadow' colour.".to_string()))?;
+    let dim = dim.ok_or_else(|| FontError("Missing 'dim' colour.".to_string()))?;
+    let red = red.ok_or_else(|| FontError("Missing 'red' colour.".to_string()))?;
+    let yellow = yellow.ok_or_else(|| FontError("Missing 'yellow' colour.".to_string()))?;
+    let glow = glow
+        .filter(|glow| !glow.is_empty())
+        .ok_or_else(|| FontError("Missing 'glow' colours.".to_string()))?;
+    let space_width = space_width.ok_or_else(|| FontError("Missing 'space_width'.".to_string()))?;
+
+    let mut glyphs = glyph_rows
+        .iter()
+        .map(|(character, rows)| {
+            parse_glyph(*character, rows)
+                .map_err(|e| FontError(format!("Glyph '{}': {}", character, e.0)))
+        })
+        .collect::<Result<Vec<Glyph>, FontError>>()?;
+
+    glyphs.push(Glyph {
+        character: ' ',
+        pixels: vec![0; GLYPH_SIZE * GLYPH_SIZE],
+        width: space_width,
+    });
+
+    glyphs.sort_by_key(|glyph| glyph.character);
+
+    if let Some(pair) = glyphs
+        .windows(2)
+        .find(|pair| pair[0].character == pair[1].character)
+    {
+        return Err(FontError(format!(
+            "Glyph '{}' is defined more than once.",
+            pair[0].character
+        )));
+    }
+
+    Ok(Font {
+        ink,
+        shadow,
+        dim,
+        red,
+        yellow,
+        glow,
+        glyphs,
+    })
+}
+
+fn parse_glyph(character: char, rows: &[&str]) -> Result<Glyph, FontError> {
+    if rows.is_empty() || rows.len() > GLYPH_SIZE {
+        return Err(FontError(format!(
+            "Expected 1 to {} rows, found {}.",
+            GLYPH_SIZE,
+            rows.len()
+        )));
+    }
+
+    let mut pixels = vec![0; GLYPH_SIZE * GLYPH_SIZE];
+    let mut ink_width = 0;
+
+    for (y, row) in rows.iter().enumerate() {
+        if row.chars().count() > GLYPH_SIZE {
+            return Err(FontError(format!(
+                "Row '{}' is wider than {} pixels.",
+                row, GLYPH_SIZE
+            )));
+        }
+
+        for (x, pixel) in row.chars().enumerate() {
+            match pixel {
+                'X' => {
+                    pixels[y * GLYPH_SIZE + x] = INK;
+                    ink_width = ink_width.max(x + 1);
+                }
+                '.' => {}
+                c => return Err(FontError(format!("Invalid pixel '{}'.", c))),
+            }
+        }
+    }
+
+    // Drop the shadow one pixel down and to the right, wherever it doesn't cover the ink.
+    for y in 0..GLYPH_SIZE - 1 {
+        for x in 0..GLYPH_SIZE - 1 {
+            let shadow_index = (y + 1) * GLYPH_SIZE + x + 1;
+
+            if pixels[y * GLYPH_SIZE + x] == INK && pixels[shadow_index] == 0 {
+                pixels[shadow_index] = SHADOW;
+            }
+        }
+    }
+
+    // The shadow column doubles as the gap before the next glyph.
+    let width = (ink_width + 1).min(GLYPH_SIZE);
+
+    Ok(Glyph {
+        character,
+        pixels,
+        width: width.try_into().unwrap(),
+    })
+}
+
+/// Convert a 24-bit `rrggbb` hex colour into a 15-bit GBA colour.
+fn parse_color(value: &str) -> Result<u16, FontError> {
+    let rgb = u32::from_str_radix(value, 16)
+        .ok()
+        .filter(|_| value.len() == 6)
+        .ok_or_else(|| FontError(format!("'{}' is not a valid rrggbb colour.", value)))?;
+
+    // Keep the top 5 bits of each 8-bit channel.
+    let channel = |shift: u32| -> u16 { (((rgb >> shift) & 0xff) >> 3).try_into().unwrap() };
+
+    Ok(channel(16) | channel(8) << 5 | channel(0) << 10)
+}
@@ -0,0 +1,65 @@
+use quote::quote;
+
+use crate::{
+    font::{Font, DIM, GLOW, INK, RED, SHADOW, YELLOW},
+    palette::MappedPalette,
+    tiles::Tile4,
+};
+
+/// The glyphs keep their logical colour indices, so that they can be drawn with any palette.
+/// `obj_palette` is where the font's colours ended up in the object palette.
+pub fn generate_font_src(font: &Font, obj_palette: &MappedPalette) -> String {
+    let glyphs: Vec<String> = font
+        .glyphs
+        .iter()
+        .map(|glyph| {
+            let character = glyph.character;
+            let width = glyph.width;
+            let tile = Tile4::from(glyph.pixels.clone());
+
+            quote! { (#character, Glyph { tile: #tile, width: #width }) }.to_string()
+        })
+        .collect();
+
+    let glyphs = format!("&[ {} ]", glyphs.join(","));
+    let glyphs: syn::Expr = syn::parse_str(&glyphs).expect("Error producing font glyphs.");
+
+    let ink = font.ink;
+    let shadow = font.shadow;
+    let obj_palette_bank: u16 = obj_palette.palette_bank().into();
+
+    // The font's palette lists the colours in the order of their logical indices.
+    let obj_ink_index: u32 = obj_palette.map_index(INK - 1).into();
+    let obj_shadow_index: u32 = obj_palette.map_index(SHADOW - 1).into();
+    let obj_dim_index: u32 = obj_palette.map_index(DIM - 1).into();
+    let obj_red_index: u32 = obj_palette.map_index(RED - 1).into();
+    let obj_yellow_index: u32 = obj_palette.map_index(YELLOW - 1).into();
+
+    // The glow is cycled as a single run, so its colours have to stay side by side.
+    let glow_indices: Vec<u8> = (0..font.glow_len())
+        .map(|i| obj_palette.map_index(GLOW - 1 + u8::try_from(i).unwrap()))
+        .collect();
+    let obj_glow_index: u32 = glow_indices[0].into();
+    let glow_len = font.glow_len();
+
+    if glow_indices.windows(2).any(|pair| pair[1] != pair[0] + 1) {
+        panic!("The font's glow colours were not mapped next to each other. Make sure none of them are used by a sprite.");
+    }
+
+    quote! {
+        pub static FONT: Font = Font {
+            glyphs: #glyphs,
+            ink: gba::video::Color(#ink),
+            shadow: gba::video::Color(#shadow),
+            obj_palette_bank: #obj_palette_bank,
+            obj_ink_index: #obj_ink_index,
+            obj_shadow_index: #obj_shadow_index,
+            obj_dim_index: #obj_dim_index,
+            obj_red_index: #obj_red_index,
+            obj_yellow_index: #obj_yellow_index,
+            obj_glow_index: #obj_glow_index,
+            glow_len: #glow_len,
+        };
+    }
+    .to_string()
+}
@@ -3,14 +3,20 @@ use std::{env, path::Path};
 use id_tree::{Node, TreeBuilder};
 use palette::{add_palette, resolve_palette};
 
-use crate::sprites::find_sprites;
+use crate::sprites::{
+    add_marked_variants, add_tinted_variants, colour_blind_palette_entries, find_sprites,
+};
 
 mod backgrounds;
 mod binpack;
+mod font;
 mod grid;
 mod math;
+mod music;
 mod palette;
+mod puzzles;
 mod sprites;
+mod strings;
 mod tiles;
 
 fn main() {
@@ -27,11 +33,30 @@ fn main() {
     // Generate sprite source code.
     let sprite_dir = Path::new(&"assets/sprites");
     let sprite_dir = base_dir.join(sprite_dir);
-    let sprite_source = get_sprite_source(&sprite_dir);
+
+    // The font is drawn into sprites as well as backgrounds, so its colours share the object palette.
+    let font_path = Path::new(&"assets/font/font.txt");
+    let font = font::find_font(&base_dir.join(font_path)).expect("Error building font.");
+
+    let (sprite_source, font_source) = get_sprite_source(&sprite_dir, &font);
 
     let sprite_output_file = output_dir.join(Path::new("sprite_data.rs"));
     write_source(&sprite_source, &sprite_output_file);
 
+    let font_output_file = output_dir.join(Path::new("font_data.rs"));
+    write_source(&font_source, &font_output_file);
+
+    // Generate string table source code.
+    let strings_dir = Path::new(&"assets/strings");
+    let string_table = strings::find_string_table(&base_dir.join(strings_dir))
+        .expect("Error building string table.");
+    string_table
+        .check_font(&font)
+        .expect("Error building string table.");
+    let strings_source = strings::codegen::generate_string_table_src(&string_table);
+    let strings_output_file = output_dir.join(Path::new("string_data.rs"));
+    write_source(&strings_source, &strings_output_file);
+
     // Generate background source code.
     let background_dir = Path::new(&"assets/backgrounds");
     let background_source = get_background_source(background_dir);
@@ -43,24 +68,69 @@ fn main() {
     let lut_output_file = output_dir.join(Path::new("lut_data.rs"));
 
     write_source(&lut_src, &lut_output_file);
+
+    // Generate puzzle source code.
+    let puzzle_dir = Path::new(&"assets/puzzles");
+    let puzzles =
+        puzzles::find_puzzles(&base_dir.join(puzzle_dir)).expect("Error building puzzles.");
+    let puzzle_source = puzzles::codegen::generate_puzzle_array_src(&puzzles);
+    let puzzle_output_file = output_dir.join(Path::new("puzzle_data.rs"));
+    write_source(&puzzle_source, &puzzle_output_file);
+
+    // Generate sound source code.
+    let sound_dir = Path::new(&"assets/audio");
+    let sounds = music::find_music(&base_dir.join(sound_dir)).expect("Error building sounds.");
+    let sound_source: Vec<String> = sounds
+        .iter()
+        .map(|sound| {
+            let samples_path = sound.write_samples(output_dir);
+            music::codegen::generate_sound_src(sound, &samples_path)
+        })
+        .collect();
+    let sound_output_file = output_dir.join(Path::new("sound_data.rs"));
+    write_source(&sound_source.join("\n"), &sound_output_file);
+
+    // Generate song source code.
+    let song_dir = Path::new(&"assets/music");
+    let songs =
+        music::tracker::find_songs(&base_dir.join(song_dir)).expect("Error building songs.");
+    let song_source: Vec<String> = songs
+        .iter()
+        .map(|song| {
+            let sample_paths = song.write_samples(output_dir);
+            music::codegen::generate_song_src(song, &sample_paths)
+        })
+        .collect();
+    let song_output_file = output_dir.join(Path::new("song_data.rs"));
+    write_source(&song_source.join("\n"), &song_output_file);
 }
 
-fn get_sprite_source(sprite_dir: &Path) -> String {
+/// Returns the source for the sprites, and for the font.
+fn get_sprite_source(sprite_dir: &Path, font: &font::Font) -> (String, String) {
     let mut palette_tree = TreeBuilder::new()
         .with_root(Node::new(palette::Palette::new(vec![0])))
         .build();
 
-    let sprites = find_sprites(sprite_dir).unwrap();
+    let mut sprites = find_sprites(sprite_dir).unwrap();
+    add_tinted_variants(&mut sprites).expect("Error generating tinted sprite variants.");
+    add_marked_variants(&mut sprites).expect("Error generating marked sprite variants.");
 
     for sprite in &sprites {
         add_palette(&mut palette_tree, sprite.palette.clone());
     }
 
+    add_palette(&mut palette_tree, font.palette());
+
     let palette_mapper = resolve_palette(palette_tree);
 
     let palette_source: String =
         sprites::codegen::generate_palette_array_src(&palette_mapper.full_palette(), "OBJ");
 
+    let colour_blind_entries = colour_blind_palette_entries(&sprites, font, &palette_mapper)
+        .expect("Error generating colour-blind palette.");
+    let colour_blind_source =
+        sprites::codegen::generate_palette_entries_src(&colour_blind_entries, "OBJ_COLOUR_BLIND");
+
     let struct_definitions: Vec<String> = sprites
         .iter()
         .map(|s| sprites::codegen::generate_sprite_struct_src(s, &palette_mapper))
@@ -68,7 +138,17 @@ fn get_sprite_source(sprite_dir: &Path) -> String {
 
     let struct_definitions: String = struct_definitions.join("\n");
 
-    format!("{}\n{}", palette_source, struct_definitions)
+    let font_palette = palette_mapper
+        .map_palette(&font.palette(), None)
+        .expect("Failed to map font palette.");
+    let font_source = font::codegen::generate_font_src(font, &font_palette);
+
+    let sprite_source = format!(
+        "{}\n{}\n{}",
+        palette_source, colour_blind_source, struct_definitions
+    );
+
+    (sprite_source, font_source)
 }
 
 fn get_background_source(background_dir: &Path) -> String {
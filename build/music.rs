@@ -1,40 +1,320 @@
-use std::{path::Path, fs::{read_dir, File}, io::Read};
+use std::{
+    fs::{read_dir, read_to_string, File},
+    io::Read,
+    path::{Path, PathBuf},
+};
 
+mod adpcm;
 pub mod codegen;
+pub mod tracker;
+mod wav;
+
+/// Raw sounds are signed 8-bit mono samples, already at the mixer's sample rate.
+const RAW_EXTENSION: &str = "raw";
+/// WAV files are converted to the raw format at build time.
+const WAV_EXTENSION: &str = "wav";
+/// Optional per-sound settings, in a file named after the sound.
+const SIDECAR_EXTENSION: &str = "cfg";
+
+/// The mixer plays samples at this rate.
+pub const MIXER_SAMPLE_RATE: u32 = mixer_core::mixer::SAMPLE_RATE;
+
+const MAX_VOLUME: u8 = 63;
+
+// Files checked out without Git LFS contain this pointer text instead of the sound.
+const LFS_POINTER_PREFIX: &[u8] = b"version https://git-lfs";
+
+#[derive(Debug)]
+pub struct SoundError(String);
 
 #[derive(Debug)]
 pub struct Sound {
-    filename: String,
-    bytes: Vec<u8>
+    name: String,
+    samples: Vec<u8>,
+    loop_start: Option<usize>,
+    volume: u8,
+    format: SoundFormat,
 }
 
 #[derive(Debug)]
-pub struct SoundError;
+pub enum SoundFormat {
+    Pcm,
+    /// The decoder's state at the loop start is stored, so that it can jump back there.
+    /// An odd number of samples leaves a spare code at the end, so the real count is kept.
+    Adpcm {
+        num_samples: usize,
+        loop_predictor: i16,
+        loop_step_index: u8,
+    },
+}
+
+/// Settings read from a sound's sidecar file.
+///
+/// ```text
+/// # Loop points are sample frames in the source file.
+/// loop: true
+/// loop_start: 1024
+/// loop_end: 88200
+/// volume: 45
+/// normalise: false
+/// # Store as 4-bit ADPCM, at a quarter of the size.
+/// compress: true
+/// ```
+struct SoundOptions {
+    is_loop: bool,
+    loop_start: usize,
+    loop_end: Option<usize>,
+    volume: u8,
+    normalise: bool,
+    compress: bool,
+}
 
+/// Find every sound in the directory, in filename order.
+/// Each sound is named after its file, so `bounce_noise.wav` becomes `BOUNCE_NOISE`.
 pub fn find_music(directory: &Path) -> Result<Vec<Sound>, SoundError> {
-    let entries = read_dir(directory).map_err(|_e| SoundError)?;
-    let mut sounds: Vec<Sound> = Vec::new();
+    let entries =
+        read_dir(directory).map_err(|_e| SoundError("Failed to read sound dir.".to_string()))?;
+
+    let mut paths = Vec::new();
 
     for entry in entries {
-        let entry = entry.map_err(|_e| SoundError)?;
+        let entry = entry.map_err(|_e| SoundError("Failed to read sound file.".to_string()))?;
         let path = entry.path();
-        let file = File::open(path);
 
-        let filename = entry
-            .file_name()
-            .to_ascii_uppercase()
-            .into_string()
-            .map_err(|_e| SoundError)?
-            .replace(".RAW", "");
+        assert!(
+            path.is_file(),
+            "/audio dir cannot contain nested directories."
+        );
+
+        paths.push(path);
+    }
+
+    paths.sort();
+
+    let mut sounds: Vec<Sound> = Vec::new();
+
+    for path in paths.iter() {
+        let filename = get_filename(path);
+
+        if get_extension(path) == SIDECAR_EXTENSION {
+            let has_sound = [RAW_EXTENSION, WAV_EXTENSION]
+                .iter()
+                .any(|extension| paths.contains(&path.with_extension(extension)));
 
+            if !has_sound {
+                return Err(SoundError(format!(
+                    "{}: There is no sound file with the same name.",
+                    filename
+                )));
+            }
 
-        let mut file = file.map_err(|_e| SoundError)?;
-        let mut sound_bytes: Vec<u8> = Vec::new();
+            continue;
+        }
+
+        let sound = read_sound(path).map_err(|e| SoundError(format!("{}: {}", filename, e.0)))?;
+
+        if sounds.iter().any(|s| s.name == sound.name) {
+            return Err(SoundError(format!(
+                "{}: Another sound is already named {}.",
+                filename, sound.name
+            )));
+        }
 
-        let _ = file.read_to_end(&mut sound_bytes).map_err(|_e| SoundError);
-        let sound = Sound { filename, bytes: sound_bytes  };
         sounds.push(sound);
     }
 
     Ok(sounds)
-}
\ No newline at end of file
+}
+
+impl Sound {
+    /// Write the converted samples next to the generated source, so that they can be included.
+    pub fn write_samples(&self, output_dir: &Path) -> PathBuf {
+        let path = output_dir.join(format!("{}.raw", self.name.to_ascii_lowercase()));
+        std::fs::write(&path, &self.samples).expect("Error writing sound samples.");
+        path
+    }
+}
+
+fn read_sound(path: &Path) -> Result<Sound, SoundError> {
+    let extension = get_extension(path);
+
+    if extension != RAW_EXTENSION && extension != WAV_EXTENSION {
+        return Err(SoundError(format!(
+            "Unsupported file type, sounds must be .{} or .{} files.",
+            RAW_EXTENSION, WAV_EXTENSION
+        )));
+    }
+
+    let stem = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .ok_or_else(|| SoundError("Filename is not valid UTF-8.".to_string()))?;
+
+    let is_valid_name = stem.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && stem.starts_with(|c: char| c.is_ascii_alphabetic());
+
+    if !is_valid_name {
+        return Err(SoundError(
+            "Filename must start with a letter and only contain letters, digits and underscores."
+                .to_string(),
+        ));
+    }
+
+    let mut file = File::open(path).map_err(|_e| SoundError("Failed to open file.".to_string()))?;
+
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes)
+        .map_err(|_e| SoundError("Failed to read file.".to_string()))?;
+
+    if bytes.is_empty() {
+        return Err(SoundError("File is empty.".to_string()));
+    }
+
+    if bytes.starts_with(LFS_POINTER_PREFIX) {
+        return Err(SoundError(
+            "File is a Git LFS pointer, run `git lfs pull` to fetch the sound.".to_string(),
+        ));
+    }
+
+    let options = read_options(&path.with_extension(SIDECAR_EXTENSION))?;
+
+    // Loop points are given in the source file's samples, so they need to be scaled along with it.
+    let (samples, loop_scale) = if extension == WAV_EXTENSION {
+        let wav = wav::parse_wav(&bytes)?;
+        let loop_scale = f64::from(MIXER_SAMPLE_RATE) / f64::from(wav.sample_rate);
+        (
+            wav::convert_to_mixer_format(&wav, options.normalise),
+            loop_scale,
+        )
+    } else {
+        (bytes, 1.0)
+    };
+
+    let scale_position = |position: usize| (position as f64 * loop_scale).round() as usize;
+
+    let loop_end = match options.loop_end {
+        Some(loop_end) => scale_position(loop_end).min(samples.len()),
+        None => samples.len(),
+    };
+
+    let loop_start = scale_position(options.loop_start);
+
+    if options.is_loop && loop_start >= loop_end {
+        return Err(SoundError(
+            "The loop must start before it ends.".to_string(),
+        ));
+    }
+
+    // Nothing after the end of a loop can ever be heard.
+    let mut samples = samples;
+    if options.is_loop {
+        samples.truncate(loop_end);
+    }
+
+    let loop_start = options.is_loop.then_some(loop_start);
+
+    let (samples, format) = if options.compress {
+        let encoded = adpcm::encode(&samples, loop_start);
+        let format = SoundFormat::Adpcm {
+            num_samples: samples.len(),
+            loop_predictor: encoded.loop_predictor,
+            loop_step_index: encoded.loop_step_index,
+        };
+
+        (encoded.data, format)
+    } else {
+        (samples, SoundFormat::Pcm)
+    };
+
+    Ok(Sound {
+        name: stem.to_ascii_uppercase(),
+        samples,
+        loop_start,
+        volume: options.volume,
+        format,
+    })
+}
+
+fn read_options(path: &Path) -> Result<SoundOptions, SoundError> {
+    let mut options = SoundOptions {
+        is_loop: false,
+        loop_start: 0,
+        loop_end: None,
+        volume: MAX_VOLUME,
+        normalise: true,
+        compress: false,
+    };
+
+    if !path.exists() {
+        return Ok(options);
+    }
+
+    let text = read_to_string(path).map_err(|_e| {
+        SoundError(format!(
+            "Failed to read sidecar file {}.",
+            get_filename(path)
+        ))
+    })?;
+
+    for line in text.lines() {
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (key, value) = line.split_once(':').ok_or_else(|| {
+            SoundError(format!(
+                "Expected `key: value` in sidecar file, found `{}`.",
+                line
+            ))
+        })?;
+
+        let (key, value) = (key.trim(), value.trim());
+
+        match key {
+            "loop" => options.is_loop = parse_option(key, value)?,
+            "loop_start" => options.loop_start = parse_option(key, value)?,
+            "loop_end" => options.loop_end = Some(parse_option(key, value)?),
+            "normalise" => options.normalise = parse_option(key, value)?,
+            "compress" => options.compress = parse_option(key, value)?,
+            "volume" => {
+                options.volume = parse_option(key, value)?;
+
+                if options.volume > MAX_VOLUME {
+                    return Err(SoundError(format!(
+                        "Volume must be at most {}.",
+                        MAX_VOLUME
+                    )));
+                }
+            }
+            _ => {
+                return Err(SoundError(format!(
+                    "Unknown option `{}` in sidecar file.",
+                    key
+                )))
+            }
+        }
+    }
+
+    Ok(options)
+}
+
+fn parse_option<T: std::str::FromStr>(key: &str, value: &str) -> Result<T, SoundError> {
+    value
+        .parse()
+        .map_err(|_e| SoundError(format!("Invalid value `{}` for option `{}`.", value, key)))
+}
+
+fn get_filename(path: &Path) -> String {
+    path.file_name()
+        .and_then(|f| f.to_str())
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn get_extension(path: &Path) -> &str {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default()
+}
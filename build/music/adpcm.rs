@@ -0,0 +1,81 @@
+// The encoder shares the decoder's tables, so the two can't drift apart.
+use mixer_core::adpcm::{INDEX_TABLE, STEP_TABLE};
+
+/// Samples compressed to 4 bits each, two to a byte with the first sample in the low bits.
+pub struct AdpcmSamples {
+    pub data: Vec<u8>,
+    /// The decoder's state just before the loop start, so that it can be restored when looping.
+    pub loop_predictor: i16,
+    pub loop_step_index: u8,
+}
+
+/// Encode signed 8-bit samples as IMA-ADPCM.
+pub fn encode(samples: &[u8], loop_start: Option<usize>) -> AdpcmSamples {
+    let mut predictor: i32 = 0;
+    let mut step_index: usize = 0;
+    let mut loop_state = (0, 0);
+    let mut codes = Vec::with_capacity(samples.len());
+
+    for (i, sample) in samples.iter().enumerate() {
+        if loop_start == Some(i) {
+            loop_state = (predictor, step_index);
+        }
+
+        // Work at 16-bit precision, which the decoder scales back down.
+        let target = i32::from(i8::from_ne_bytes([*sample])) << 8;
+        let step = STEP_TABLE[step_index];
+
+        let mut difference = target - predictor;
+        let mut code: u8 = 0;
+
+        if difference < 0 {
+            code = 8;
+            difference = -difference;
+        }
+
+        // Pick the code that gets closest, tracking exactly what the decoder will reconstruct.
+        let mut delta = step >> 3;
+
+        if difference >= step {
+            code |= 4;
+            difference -= step;
+            delta += step;
+        }
+
+        if difference >= step >> 1 {
+            code |= 2;
+            difference -= step >> 1;
+            delta += step >> 1;
+        }
+
+        if difference >= step >> 2 {
+            code |= 1;
+            delta += step >> 2;
+        }
+
+        predictor = if code & 8 != 0 {
+            predictor - delta
+        } else {
+            predictor + delta
+        };
+        predictor = predictor.clamp(i16::MIN.into(), i16::MAX.into());
+
+        let next_index = step_index as i32 + INDEX_TABLE[usize::from(code & 7)];
+        step_index = next_index.clamp(0, STEP_TABLE.len() as i32 - 1) as usize;
+
+        codes.push(code);
+    }
+
+    // An odd number of samples leaves the top half of the last byte unused. The decoder is given
+    // the real sample count, so it never plays it.
+    let data = codes
+        .chunks(2)
+        .map(|pair| pair[0] | (pair.get(1).copied().unwrap_or(0) << 4))
+        .collect();
+
+    AdpcmSamples {
+        data,
+        loop_predictor: loop_state.0.try_into().unwrap(),
+        loop_step_index: loop_state.1.try_into().unwrap(),
+    }
+}
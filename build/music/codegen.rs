@@ -0,0 +1,116 @@
+use std::path::PathBuf;
+
+use quote::{format_ident, quote};
+
+use crate::music::{tracker::Song, Sound, SoundFormat};
+
+/// One static per sound. The samples are pulled in with `include_bytes!`, rather than being
+/// written out as a literal, to keep the generated source small.
+pub fn generate_sound_src(sound: &Sound, samples_path: &std::path::Path) -> String {
+    let ident = format_ident!("{}", sound.name);
+
+    let path = samples_path
+        .to_str()
+        .expect("Sound path is not valid UTF-8.");
+
+    let loop_start = match sound.loop_start {
+        Some(loop_start) => quote! { Some(#loop_start) },
+        None => quote! { None },
+    };
+
+    let volume = sound.volume;
+
+    let format = match sound.format {
+        SoundFormat::Pcm => quote! { SampleFormat::Pcm },
+        SoundFormat::Adpcm {
+            num_samples,
+            loop_predictor,
+            loop_step_index,
+        } => quote! {
+            SampleFormat::Adpcm {
+                num_samples: #num_samples,
+                loop_predictor: #loop_predictor,
+                loop_step_index: #loop_step_index,
+            }
+        },
+    };
+
+    quote! {
+        pub static #ident: SoundAsset = SoundAsset {
+            samples: include_bytes!(#path),
+            loop_start: #loop_start,
+            volume: #volume,
+            format: #format,
+        };
+    }
+    .to_string()
+}
+
+/// A song's instruments, order table and pattern rows. Like sounds, the instrument samples are
+/// pulled in with `include_bytes!`.
+pub fn generate_song_src(song: &Song, sample_paths: &[PathBuf]) -> String {
+    let ident = format_ident!("{}", song.name);
+
+    let instruments: Vec<String> = song
+        .instruments
+        .iter()
+        .zip(sample_paths)
+        .map(|(instrument, path)| {
+            let path = path.to_str().expect("Song sample path is not valid UTF-8.");
+
+            let loop_start = match instrument.loop_start {
+                Some(loop_start) => quote! { Some(#loop_start) },
+                None => quote! { None },
+            };
+
+            let volume = instrument.volume;
+            let finetune = instrument.finetune;
+
+            quote! {
+                Instrument {
+                    samples: include_bytes!(#path),
+                    loop_start: #loop_start,
+                    volume: #volume,
+                    finetune: #finetune,
+                }
+            }
+            .to_string()
+        })
+        .collect();
+
+    let instruments = format!("&[ {} ]", instruments.join(", "));
+    let instruments: syn::Expr =
+        syn::parse_str(&instruments).expect("Error producing instrument array.");
+
+    let orders = &song.orders;
+
+    let rows: Vec<String> = song
+        .rows
+        .iter()
+        .map(|row| {
+            let cells: Vec<String> = row
+                .iter()
+                .map(|cell| {
+                    format!(
+                        "Cell::new({}, {}, {}, {})",
+                        cell.note, cell.instrument, cell.effect, cell.param
+                    )
+                })
+                .collect();
+
+            format!("[ {} ]", cells.join(", "))
+        })
+        .collect();
+
+    let rows = format!("&[ {} ]", rows.join(", "));
+    let rows: syn::Expr = syn::parse_str(&rows).expect("Error producing pattern rows.");
+
+    quote! {
+        pub static #ident: Song = Song {
+            instruments: #instruments,
+            orders: &[ #(#orders),* ],
+            rows: #rows,
+        };
+    }
+    .to_string()
+}
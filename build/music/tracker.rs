@@ -0,0 +1,286 @@
+use std::{
+    fs::{read, read_dir},
+    path::{Path, PathBuf},
+};
+
+use super::{SoundError, LFS_POINTER_PREFIX};
+
+pub const NUM_CHANNELS: usize = 4;
+pub const ROWS_PER_PATTERN: usize = 64;
+
+const NUM_INSTRUMENTS: usize = 31;
+const INSTRUMENT_HEADER_SIZE: usize = 30;
+const INSTRUMENTS_OFFSET: usize = 20;
+const SONG_LENGTH_OFFSET: usize = 950;
+const ORDERS_OFFSET: usize = 952;
+const MAX_ORDERS: usize = 128;
+const TAG_OFFSET: usize = 1080;
+const PATTERNS_OFFSET: usize = 1084;
+const CELL_SIZE: usize = 4;
+const PATTERN_SIZE: usize = ROWS_PER_PATTERN * NUM_CHANNELS * CELL_SIZE;
+
+// Tags used by 4 channel ProTracker compatible modules.
+const FOUR_CHANNEL_TAGS: [&[u8; 4]; 4] = [b"M.K.", b"M!K!", b"4CHN", b"FLT4"];
+
+/// Amiga periods for the three octaves a module can use, with no finetune.
+/// Notes are stored as an index into this table, plus one so that zero can mean "no note".
+const PERIODS: [u16; 36] = [
+    856, 808, 762, 720, 678, 640, 604, 570, 538, 508, 480, 453, // Octave 1
+    428, 404, 381, 360, 339, 320, 302, 285, 269, 254, 240, 226, // Octave 2
+    214, 202, 190, 180, 170, 160, 151, 143, 135, 127, 120, 113, // Octave 3
+];
+
+pub struct Instrument {
+    pub samples: Vec<u8>,
+    pub loop_start: Option<usize>,
+    pub volume: u8,
+    pub finetune: i8,
+}
+
+#[derive(Clone, Copy)]
+pub struct Cell {
+    pub note: u8,
+    pub instrument: u8,
+    pub effect: u8,
+    pub param: u8,
+}
+
+pub struct Song {
+    pub name: String,
+    pub instruments: Vec<Instrument>,
+    pub orders: Vec<u8>,
+    /// Every row of every pattern, one pattern after the other.
+    pub rows: Vec<[Cell; NUM_CHANNELS]>,
+}
+
+impl Song {
+    /// Write each instrument's samples next to the generated source, so that they can be included.
+    pub fn write_samples(&self, output_dir: &Path) -> Vec<PathBuf> {
+        self.instruments
+            .iter()
+            .enumerate()
+            .map(|(i, instrument)| {
+                let filename = format!("{}_{}.raw", self.name.to_ascii_lowercase(), i + 1);
+                let path = output_dir.join(filename);
+                std::fs::write(&path, &instrument.samples).expect("Error writing song samples.");
+                path
+            })
+            .collect()
+    }
+}
+
+/// Find every song in the directory, in filename order.
+/// Each song is named after its file, so `title.mod` becomes `TITLE_SONG`.
+pub fn find_songs(directory: &Path) -> Result<Vec<Song>, SoundError> {
+    let entries =
+        read_dir(directory).map_err(|_e| SoundError("Failed to read music dir.".to_string()))?;
+
+    let mut paths: Vec<PathBuf> = Vec::new();
+
+    for entry in entries {
+        let entry = entry.map_err(|_e| SoundError("Failed to read music file.".to_string()))?;
+        let path = entry.path();
+
+        assert!(
+            path.is_file(),
+            "/music dir cannot contain nested directories."
+        );
+
+        paths.push(path);
+    }
+
+    paths.sort();
+
+    paths
+        .iter()
+        .map(|path| {
+            let filename = path
+                .file_name()
+                .and_then(|f| f.to_str())
+                .unwrap_or_default();
+
+            read_song(path).map_err(|e| SoundError(format!("{}: {}", filename, e.0)))
+        })
+        .collect()
+}
+
+fn read_song(path: &Path) -> Result<Song, SoundError> {
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+
+    match extension.as_str() {
+        "mod" => {}
+        "xm" => {
+            return Err(SoundError(
+                "XM modules are not supported yet, export the song as a 4 channel MOD.".to_string(),
+            ))
+        }
+        _ => {
+            return Err(SoundError(
+                "Unsupported file type, songs must be .mod files.".to_string(),
+            ))
+        }
+    }
+
+    let stem = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .filter(|s| s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'))
+        .ok_or_else(|| {
+            SoundError("Filename must only contain letters, digits and underscores.".to_string())
+        })?;
+
+    let bytes = read(path).map_err(|_e| SoundError("Failed to read file.".to_string()))?;
+
+    if bytes.starts_with(LFS_POINTER_PREFIX) {
+        return Err(SoundError(
+            "File is a Git LFS pointer, run `git lfs pull` to fetch the song.".to_string(),
+        ));
+    }
+
+    parse_mod(&bytes, format!("{}_SONG", stem.to_ascii_uppercase()))
+}
+
+fn parse_mod(bytes: &[u8], name: String) -> Result<Song, SoundError> {
+    if bytes.len() < PATTERNS_OFFSET {
+        return Err(SoundError("File is too short to be a module.".to_string()));
+    }
+
+    let tag = &bytes[TAG_OFFSET..TAG_OFFSET + 4];
+    if !FOUR_CHANNEL_TAGS.iter().any(|t| t.as_slice() == tag) {
+        return Err(SoundError(format!(
+            "Unrecognised module tag {:?}, only 4 channel ProTracker modules are supported.",
+            String::from_utf8_lossy(tag)
+        )));
+    }
+
+    let song_length = usize::from(bytes[SONG_LENGTH_OFFSET]);
+    if song_length == 0 || song_length > MAX_ORDERS {
+        return Err(SoundError(format!("Invalid song length {}.", song_length)));
+    }
+
+    let orders = bytes[ORDERS_OFFSET..ORDERS_OFFSET + song_length].to_vec();
+
+    // Every pattern in the order table is stored, even ones past the end of the song.
+    let num_patterns = usize::from(
+        *bytes[ORDERS_OFFSET..ORDERS_OFFSET + MAX_ORDERS]
+            .iter()
+            .max()
+            .unwrap(),
+    ) + 1;
+
+    let samples_offset = PATTERNS_OFFSET + num_patterns * PATTERN_SIZE;
+    if bytes.len() < samples_offset {
+        return Err(SoundError("Pattern data is truncated.".to_string()));
+    }
+
+    let rows = bytes[PATTERNS_OFFSET..samples_offset]
+        .chunks_exact(NUM_CHANNELS * CELL_SIZE)
+        .map(|row| {
+            let mut cells = [Cell {
+                note: 0,
+                instrument: 0,
+                effect: 0,
+                param: 0,
+            }; NUM_CHANNELS];
+
+            for (cell, data) in cells.iter_mut().zip(row.chunks_exact(CELL_SIZE)) {
+                *cell = parse_cell(data);
+            }
+
+            cells
+        })
+        .collect();
+
+    let mut instruments = Vec::new();
+    let mut sample_offset = samples_offset;
+
+    for i in 0..NUM_INSTRUMENTS {
+        let header = &bytes[INSTRUMENTS_OFFSET + i * INSTRUMENT_HEADER_SIZE..];
+        let (instrument, length) =
+            parse_instrument(header, &bytes[sample_offset.min(bytes.len())..])
+                .map_err(|e| SoundError(format!("Instrument {}: {}", i + 1, e.0)))?;
+
+        instruments.push(instrument);
+        sample_offset += length;
+    }
+
+    // Drop the unused instruments from the end, which most modules have plenty of.
+    while instruments.last().is_some_and(|i| i.samples.is_empty()) {
+        instruments.pop();
+    }
+
+    Ok(Song {
+        name,
+        instruments,
+        orders,
+        rows,
+    })
+}
+
+fn parse_cell(data: &[u8]) -> Cell {
+    let period = (u16::from(data[0] & 0x0F) << 8) | u16::from(data[1]);
+    let instrument = (data[0] & 0xF0) | (data[2] >> 4);
+
+    let note = if period == 0 {
+        0
+    } else {
+        // Trackers write the exact table value, but allow for periods that are slightly off.
+        let index = PERIODS
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, p)| p.abs_diff(period))
+            .map(|(i, _)| i)
+            .unwrap();
+
+        u8::try_from(index + 1).unwrap()
+    };
+
+    Cell {
+        note,
+        instrument,
+        effect: data[2] & 0x0F,
+        param: data[3],
+    }
+}
+
+/// Returns the instrument, along with the number of bytes of sample data it used.
+fn parse_instrument(header: &[u8], sample_data: &[u8]) -> Result<(Instrument, usize), SoundError> {
+    // Lengths and loop points are stored in 16-bit words.
+    let read_words =
+        |offset: usize| usize::from(u16::from_be_bytes([header[offset], header[offset + 1]])) * 2;
+
+    let length = read_words(22);
+    // The finetune is a signed 4-bit value.
+    let finetune = ((header[24] & 0x0F) << 4) as i8 >> 4;
+    let volume = header[25].min(64);
+    let loop_start = read_words(26);
+    let loop_length = read_words(28);
+
+    if sample_data.len() < length {
+        return Err(SoundError("Sample data is truncated.".to_string()));
+    }
+
+    let mut samples = sample_data[..length].to_vec();
+
+    // A loop length of one word means the sample doesn't loop.
+    let loop_start = if loop_length > 2 && loop_start < length {
+        // Nothing after the end of the loop can be heard.
+        samples.truncate((loop_start + loop_length).min(length));
+        Some(loop_start)
+    } else {
+        None
+    };
+
+    let instrument = Instrument {
+        samples,
+        loop_start,
+        volume,
+        finetune,
+    };
+
+    Ok((instrument, length))
+}
@@ -0,0 +1,197 @@
+use super::{SoundError, MIXER_SAMPLE_RATE};
+
+const WAVE_FORMAT_PCM: u16 = 1;
+const WAVE_FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+// Normalised sounds peak just below full scale, to leave room for the dither.
+const NORMALISED_PEAK: f64 = 0.95;
+
+/// Decoded WAV audio, with each channel's samples scaled to -1.0..1.0.
+pub struct Wav {
+    pub sample_rate: u32,
+    channels: Vec<Vec<f64>>,
+}
+
+/// Read an 8 or 16-bit PCM WAV file, with any number of channels.
+pub fn parse_wav(bytes: &[u8]) -> Result<Wav, SoundError> {
+    if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+        return Err(SoundError("Not a RIFF WAVE file.".to_string()));
+    }
+
+    let mut format: Option<(u16, u32, u16)> = None;
+    let mut data: Option<&[u8]> = None;
+    let mut offset = 12;
+
+    while offset + 8 <= bytes.len() {
+        let chunk_id = &bytes[offset..offset + 4];
+        let chunk_size = read_u32(bytes, offset + 4) as usize;
+        let chunk_start = offset + 8;
+        let chunk_end = (chunk_start + chunk_size).min(bytes.len());
+        let chunk = &bytes[chunk_start..chunk_end];
+
+        match chunk_id {
+            b"fmt " => {
+                if chunk.len() < 16 {
+                    return Err(SoundError("WAV format chunk is too short.".to_string()));
+                }
+
+                let audio_format = read_u16(chunk, 0);
+                let num_channels = read_u16(chunk, 2);
+                let sample_rate = read_u32(chunk, 4);
+                let bits_per_sample = read_u16(chunk, 14);
+
+                if audio_format != WAVE_FORMAT_PCM && audio_format != WAVE_FORMAT_EXTENSIBLE {
+                    return Err(SoundError(
+                        "Only uncompressed PCM WAV files are supported.".to_string(),
+                    ));
+                }
+
+                format = Some((num_channels, sample_rate, bits_per_sample));
+            }
+            b"data" => data = Some(chunk),
+            _ => {}
+        }
+
+        // Chunks are padded to an even number of bytes.
+        offset = chunk_start + chunk_size + chunk_size % 2;
+    }
+
+    let (num_channels, sample_rate, bits_per_sample) =
+        format.ok_or_else(|| SoundError("WAV file has no format chunk.".to_string()))?;
+    let data = data.ok_or_else(|| SoundError("WAV file has no data chunk.".to_string()))?;
+
+    if num_channels == 0 || sample_rate == 0 {
+        return Err(SoundError("WAV file has no channels.".to_string()));
+    }
+
+    let bytes_per_sample = match bits_per_sample {
+        8 => 1,
+        16 => 2,
+        _ => {
+            return Err(SoundError(format!(
+                "{}-bit WAV files are not supported, use 8 or 16-bit.",
+                bits_per_sample
+            )))
+        }
+    };
+
+    let frame_size = bytes_per_sample * usize::from(num_channels);
+    let mut channels = vec![Vec::new(); num_channels.into()];
+
+    for frame in data.chunks_exact(frame_size) {
+        for (channel, sample) in channels
+            .iter_mut()
+            .zip(frame.chunks_exact(bytes_per_sample))
+        {
+            // 8-bit WAV samples are unsigned, 16-bit samples are signed.
+            let sample = match bytes_per_sample {
+                1 => (f64::from(sample[0]) - 128.0) / 128.0,
+                _ => f64::from(i16::from_le_bytes([sample[0], sample[1]])) / 32768.0,
+            };
+
+            channel.push(sample);
+        }
+    }
+
+    if channels[0].is_empty() {
+        return Err(SoundError("WAV file contains no samples.".to_string()));
+    }
+
+    Ok(Wav {
+        sample_rate,
+        channels,
+    })
+}
+
+/// Downmix to mono, resample to the mixer rate and quantise to signed 8-bit samples.
+pub fn convert_to_mixer_format(wav: &Wav, normalise: bool) -> Vec<u8> {
+    let mono = downmix(&wav.channels);
+    let mut resampled = resample(&mono, wav.sample_rate, MIXER_SAMPLE_RATE);
+
+    if normalise {
+        let peak = resampled.iter().fold(0.0_f64, |peak, s| peak.max(s.abs()));
+
+        if peak > 0.0 {
+            let gain = NORMALISED_PEAK / peak;
+            resampled.iter_mut().for_each(|s| *s *= gain);
+        }
+    }
+
+    quantise(&resampled)
+}
+
+fn downmix(channels: &[Vec<f64>]) -> Vec<f64> {
+    let num_channels = channels.len() as f64;
+
+    (0..channels[0].len())
+        .map(|i| channels.iter().map(|c| c[i]).sum::<f64>() / num_channels)
+        .collect()
+}
+
+/// Linear interpolation between source samples. When reducing the sample rate, the source is
+/// first smoothed with a moving average, to cut down on aliasing.
+fn resample(samples: &[f64], source_rate: u32, target_rate: u32) -> Vec<f64> {
+    let step = f64::from(source_rate) / f64::from(target_rate);
+
+    let filtered: Vec<f64> = if step > 1.0 {
+        let width = step.round() as usize;
+
+        (0..samples.len())
+            .map(|i| {
+                let window = &samples[i..(i + width).min(samples.len())];
+                window.iter().sum::<f64>() / window.len() as f64
+            })
+            .collect()
+    } else {
+        samples.to_vec()
+    };
+
+    let output_len = (samples.len() as f64 / step).floor() as usize;
+
+    (0..output_len)
+        .map(|i| {
+            let position = i as f64 * step;
+            let index = position.floor() as usize;
+            let fraction = position - index as f64;
+
+            let current = filtered[index];
+            let next = filtered.get(index + 1).copied().unwrap_or(current);
+
+            current + (next - current) * fraction
+        })
+        .collect()
+}
+
+/// Round to 8 bits, with triangular dither to spread the rounding error out as noise.
+fn quantise(samples: &[f64]) -> Vec<u8> {
+    // A fixed seed keeps the output the same from one build to the next.
+    let mut rng_state: u32 = 0x1234_5678;
+    let mut next_random = || {
+        rng_state = rng_state
+            .wrapping_mul(1_664_525)
+            .wrapping_add(1_013_904_223);
+        f64::from(rng_state >> 8) / f64::from(1_u32 << 24)
+    };
+
+    samples
+        .iter()
+        .map(|sample| {
+            let dither = next_random() - next_random();
+            let value = (sample * 127.0 + dither).round().clamp(-128.0, 127.0) as i8;
+            value.to_ne_bytes()[0]
+        })
+        .collect()
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([bytes[offset], bytes[offset + 1]])
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([
+        bytes[offset],
+        bytes[offset + 1],
+        bytes[offset + 2],
+        bytes[offset + 3],
+    ])
+}
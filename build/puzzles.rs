@@ -0,0 +1,427 @@
+use std::{fs::read_dir, path::Path};
+
+use solver_core::{Position, SearchBudget, MAX_SEARCH_NODES};
+
+pub mod codegen;
+
+const BOARD_COLUMNS: usize = 7;
+const BOARD_ROWS: usize = 6;
+
+/// Searching deeper than this is too slow for the GBA to validate moves in a single frame.
+const MAX_PUZZLE_MOVES: u8 = 3;
+
+/// Progress is saved as one bit per puzzle, in a 64-bit field.
+const MAX_PUZZLES: usize = 64;
+
+#[derive(Debug)]
+pub struct PuzzleError(String);
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Token {
+    Red,
+    Yellow,
+}
+
+pub struct Puzzle {
+    to_move: Token,
+    moves: u8,
+    /// The tokens in each column, from the bottom up.
+    columns: Vec<Vec<Token>>,
+}
+
+/// Read every puzzle in the directory, in filename order.
+///
+/// Puzzles are text files made up of `key: value` headers followed by the board, drawn top row
+/// first with one character per slot: `.` for empty, `R` for red and `Y` for yellow.
+/// Lines starting with `#` are comments.
+///
+/// ```text
+/// to_move: red
+/// moves: 2
+///
+/// .......
+/// .......
+/// .......
+/// .......
+/// .RY....
+/// .RR..YY
+/// ```
+pub fn find_puzzles(directory: &Path) -> Result<Vec<Puzzle>, PuzzleError> {
+    let entries =
+        read_dir(directory).map_err(|_e| PuzzleError("Failed to read puzzle dir.".to_string()))?;
+
+    let mut paths = Vec::new();
+
+    for entry in entries {
+        let entry = entry.map_err(|_e| PuzzleError("Failed to read puzzle file.".to_string()))?;
+        let path = entry.path();
+
+        assert!(
+            path.is_file(),
+            "/puzzles dir cannot contain nested directories."
+        );
+
+        paths.push(path);
+    }
+
+    paths.sort();
+
+    if paths.is_empty() {
+        return Err(PuzzleError(
+            "Puzzle dir does not contain any puzzles.".to_string(),
+        ));
+    }
+
+    if paths.len() > MAX_PUZZLES {
+        return Err(PuzzleError(format!(
+            "Found {} puzzles, but at most {} are supported.",
+            paths.len(),
+            MAX_PUZZLES
+        )));
+    }
+
+    paths
+        .iter()
+        .map(|path| {
+            let filename = path.file_name().unwrap().to_string_lossy();
+
+            let text = std::fs::read_to_string(path)
+                .map_err(|_e| PuzzleError(format!("{}: Failed to read file.", filename)))?;
+
+            parse_puzzle(&text).map_err(|e| PuzzleError(format!("{}: {}", filename, e.0)))
+        })
+        .collect()
+}
+
+fn parse_puzzle(text: &str) -> Result<Puzzle, PuzzleError> {
+    let mut to_move = None;
+    let mut moves = None;
+    let mut rows: Vec<&str> = Vec::new();
+
+    for line in text.lines().map(|line| line.trim()) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        if let Some((key, value)) = line.split_once(':') {
+            match key.trim() {
+                "to_move" => to_move = Some(parse_token_name(value.trim())?),
+                "moves" => {
+                    let value = value.trim().parse::<u8>().map_err(|_e| {
+                        PuzzleError(format!(
+                            "'{}' is not a valid number of moves.",
+                            value.trim()
+                        ))
+                    })?;
+                    moves = Some(value);
+                }
+                key => return Err(PuzzleError(format!("Unknown key '{}'.", key))),
+            }
+        } else {
+            rows.push(line);
+        }
+    }
+
+    let to_move = to_move.ok_or(PuzzleError("Missing 'to_move'.".to_string()))?;
+    let moves = moves.ok_or(PuzzleError("Missing 'moves'.".to_string()))?;
+
+    if moves == 0 || moves > MAX_PUZZLE_MOVES {
+        return Err(PuzzleError(format!(
+            "'moves' must be between 1 and {}.",
+            MAX_PUZZLE_MOVES
+        )));
+    }
+
+    let columns = parse_board(&rows)?;
+    let puzzle = Puzzle {
+        to_move,
+        moves,
+        columns,
+    };
+
+    validate_puzzle(&puzzle)?;
+
+    Ok(puzzle)
+}
+
+fn parse_token_name(name: &str) -> Result<Token, PuzzleError> {
+    match name {
+        "red" => Ok(Token::Red),
+        "yellow" => Ok(Token::Yellow),
+        _ => Err(PuzzleError(format!(
+            "'{}' is not a colour, expected 'red' or 'yellow'.",
+            name
+        ))),
+    }
+}
+
+fn parse_board(rows: &[&str]) -> Result<Vec<Vec<Token>>, PuzzleError> {
+    if rows.len() != BOARD_ROWS {
+        return Err(PuzzleError(format!(
+            "Board has {} rows, expected {}.",
+            rows.len(),
+            BOARD_ROWS
+        )));
+    }
+
+    let mut columns: Vec<Vec<Token>> = vec![Vec::new(); BOARD_COLUMNS];
+
+    // Rows are written top first, so walk them from the bottom to stack the tokens up.
+    for (row_number, row) in rows.iter().rev().enumerate() {
+        let slots: Vec<char> = row.chars().collect();
+
+        if slots.len() != BOARD_COLUMNS {
+            return Err(PuzzleError(format!(
+                "Row '{}' has {} slots, expected {}.",
+                row,
+                slots.len(),
+                BOARD_COLUMNS
+            )));
+        }
+
+        for (column_number, slot) in slots.iter().enumerate() {
+            let token = match slot {
+                '.' => None,
+                'R' => Some(Token::Red),
+                'Y' => Some(Token::Yellow),
+                _ => {
+                    return Err(PuzzleError(format!(
+                        "Unexpected character '{}' in board.",
+                        slot
+                    )))
+                }
+            };
+
+            let column = &mut columns[column_number];
+
+            match token {
+                Some(token) if column.len() == row_number => column.push(token),
+                Some(_) => {
+                    return Err(PuzzleError(format!(
+                        "Token in column {} is floating above an empty slot.",
+                        column_number + 1
+                    )))
+                }
+                None => {}
+            }
+        }
+    }
+
+    Ok(columns)
+}
+
+fn validate_puzzle(puzzle: &Puzzle) -> Result<(), PuzzleError> {
+    let count = |token: Token| {
+        puzzle
+            .columns
+            .iter()
+            .flatten()
+            .filter(|t| **t == token)
+            .count()
+    };
+
+    let own_tokens = count(puzzle.to_move);
+    let opponent_tokens = count(puzzle.to_move.opposite());
+
+    // Either player may have started, but the player to move can't be ahead.
+    if own_tokens != opponent_tokens && own_tokens + 1 != opponent_tokens {
+        return Err(PuzzleError(format!(
+            "Token counts don't match the player to move ({} to {}).",
+            own_tokens, opponent_tokens
+        )));
+    }
+
+    let board = Board {
+        columns: puzzle.columns.clone(),
+    };
+
+    if board.has_line(Token::Red) || board.has_line(Token::Yellow) {
+        return Err(PuzzleError(
+            "Board already contains a line of four.".to_string(),
+        ));
+    }
+
+    if !board.can_force_win(puzzle.to_move, puzzle.moves) {
+        return Err(PuzzleError(format!(
+            "There is no forced win in {} moves.",
+            puzzle.moves
+        )));
+    }
+
+    if board.can_force_win(puzzle.to_move, puzzle.moves - 1) {
+        return Err(PuzzleError(format!(
+            "There is a forced win in fewer than {} moves.",
+            puzzle.moves
+        )));
+    }
+
+    let position = Position::from_slots(|column, row| {
+        puzzle.columns[column]
+            .get(row)
+            .map(|token| *token == puzzle.to_move)
+    });
+
+    check_player_searches(&position, puzzle.moves, puzzle.moves)
+}
+
+/// Play through every line the puzzle can take, running the same searches as the game with the
+/// same budget, so that none of them can run out before finding its answer.
+/// The player is to move, with `moves_left` moves left to win in.
+fn check_player_searches(
+    position: &Position,
+    moves_left: u8,
+    puzzle_moves: u8,
+) -> Result<(), PuzzleError> {
+    run_search("hint", |budget| {
+        solver_core::find_winning_column(position, moves_left, budget);
+    })?;
+
+    for column in playable_columns(position) {
+        // A winning move ends the puzzle, so nothing is searched after it.
+        if position.is_winning_move(column) {
+            continue;
+        }
+
+        // After each of the player's moves, the game checks that the win is still there.
+        let position = position.play(column);
+        let mut still_winning = false;
+
+        run_search("move check", |budget| {
+            still_winning = solver_core::opponent_is_lost(&position, moves_left - 1, budget);
+        })?;
+
+        if still_winning {
+            check_defender_searches(&position, moves_left - 1, puzzle_moves)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// The defender scores every column with its own search, then plays one of the best.
+fn check_defender_searches(
+    position: &Position,
+    moves_left: u8,
+    puzzle_moves: u8,
+) -> Result<(), PuzzleError> {
+    let mut scores = Vec::new();
+
+    for column in playable_columns(position) {
+        let mut score = 0;
+
+        run_search("defence", |budget| {
+            score = solver_core::moves_until_forced_loss(position, column, puzzle_moves, budget);
+        })?;
+
+        scores.push((column, score));
+    }
+
+    let best_score = scores.iter().map(|(_, score)| *score).max();
+
+    for (column, score) in scores {
+        if Some(score) == best_score {
+            check_player_searches(&position.play(column), moves_left, puzzle_moves)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn playable_columns(position: &Position) -> Vec<usize> {
+    (0..BOARD_COLUMNS)
+        .filter(|column| position.can_play(*column))
+        .collect()
+}
+
+fn run_search(name: &str, search: impl FnOnce(&mut SearchBudget)) -> Result<(), PuzzleError> {
+    let mut budget = SearchBudget::per_search();
+    search(&mut budget);
+
+    if budget.is_exhausted() {
+        return Err(PuzzleError(format!(
+            "The {} search visits more than {} positions.",
+            name, MAX_SEARCH_NODES
+        )));
+    }
+
+    Ok(())
+}
+
+impl Token {
+    fn opposite(&self) -> Self {
+        match self {
+            Token::Red => Token::Yellow,
+            Token::Yellow => Token::Red,
+        }
+    }
+}
+
+/// A simple board used to check that each puzzle really is a forced win.
+/// The game has its own faster solver, this one only needs to be obviously correct.
+#[derive(Clone)]
+struct Board {
+    columns: Vec<Vec<Token>>,
+}
+
+impl Board {
+    fn get(&self, column: i32, row: i32) -> Option<Token> {
+        if column < 0 || row < 0 {
+            return None;
+        }
+
+        self.columns
+            .get(column as usize)
+            .and_then(|c| c.get(row as usize))
+            .copied()
+    }
+
+    fn playable_columns(&self) -> Vec<usize> {
+        (0..BOARD_COLUMNS)
+            .filter(|c| self.columns[*c].len() < BOARD_ROWS)
+            .collect()
+    }
+
+    fn after_move(&self, column: usize, token: Token) -> Self {
+        let mut board = self.clone();
+        board.columns[column].push(token);
+        board
+    }
+
+    fn has_line(&self, token: Token) -> bool {
+        let directions = [(1, 0), (0, 1), (1, 1), (1, -1)];
+
+        (0..BOARD_COLUMNS as i32).any(|column| {
+            (0..BOARD_ROWS as i32).any(|row| {
+                directions.iter().any(|(dc, dr)| {
+                    (0..4).all(|i| self.get(column + dc * i, row + dr * i) == Some(token))
+                })
+            })
+        })
+    }
+
+    /// Whether `token` can win within `moves` of its own moves, whatever the opponent does.
+    fn can_force_win(&self, token: Token, moves: u8) -> bool {
+        if moves == 0 {
+            return false;
+        }
+
+        self.playable_columns().iter().any(|column| {
+            let board = self.after_move(*column, token);
+
+            if board.has_line(token) {
+                return true;
+            }
+
+            let opponent = token.opposite();
+            let replies = board.playable_columns();
+
+            // A full board is a draw, which doesn't count.
+            moves > 1
+                && !replies.is_empty()
+                && replies.iter().all(|reply| {
+                    let board = board.after_move(*reply, opponent);
+                    !board.has_line(opponent) && board.can_force_win(token, moves - 1)
+                })
+        })
+    }
+}
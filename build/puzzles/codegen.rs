@@ -0,0 +1,52 @@
+use quote::{quote, ToTokens};
+
+use crate::puzzles::{Puzzle, Token};
+
+pub fn generate_puzzle_array_src(puzzles: &[Puzzle]) -> String {
+    let num_puzzles = puzzles.len();
+
+    let puzzle_literals: Vec<String> = puzzles
+        .iter()
+        .map(|puzzle| puzzle.to_token_stream().to_string())
+        .collect();
+
+    let puzzle_literals = format!("[ {} ]", puzzle_literals.join(", "));
+    let puzzle_literals: syn::Expr =
+        syn::parse_str(&puzzle_literals).expect("Error producing puzzle array.");
+
+    quote! {
+        pub static PUZZLES: [Puzzle; #num_puzzles] = #puzzle_literals;
+    }
+    .to_string()
+}
+
+impl ToTokens for Puzzle {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        let columns: Vec<String> = self
+            .columns
+            .iter()
+            .map(|column| {
+                let column_tokens: Vec<String> = column.iter().map(token_color_src).collect();
+                format!("&[ {} ]", column_tokens.join(","))
+            })
+            .collect();
+
+        let puzzle = format!(
+            "Puzzle {{ to_move: {}, moves: {}, columns: [ {} ] }}",
+            token_color_src(&self.to_move),
+            self.moves,
+            columns.join(",")
+        );
+
+        let expr: syn::Expr = syn::parse_str(&puzzle).expect("Could not parse Puzzle");
+
+        expr.to_tokens(tokens);
+    }
+}
+
+fn token_color_src(token: &Token) -> String {
+    match token {
+        Token::Red => "TokenColor::Red".to_string(),
+        Token::Yellow => "TokenColor::Yellow".to_string(),
+    }
+}
@@ -2,13 +2,56 @@ use std::{fs::read_dir, path::Path};
 
 use asefile::{util, AsepriteFile};
 
-use crate::palette::{self, palette_entry_to_15bit_color};
+use crate::{
+    font::{self, Font},
+    palette::{self, palette_entry_to_15bit_color},
+};
 
 pub mod codegen;
 
 #[derive(Debug)]
 pub struct SpriteError;
 
+/// Recoloured copies of existing sprites, as (source sprite, variant name, tint colour).
+/// Each CPU personality gets its own colour of the same head.
+const TINTED_VARIANTS: [(&str, &str, u16); 3] = [
+    ("CPU_HEAD", "CPU_HEAD_AGGRESSIVE", rgb15(31, 6, 4)),
+    ("CPU_HEAD", "CPU_HEAD_TRICKSTER", rgb15(20, 8, 28)),
+    ("CPU_HEAD", "CPU_HEAD_CENTER_OBSESSED", rgb15(6, 26, 8)),
+];
+
+/// Copies of the tokens with a shape drawn on, as (source sprite, variant name, marker).
+/// These let the players be told apart without relying on colour.
+const MARKED_VARIANTS: [(&str, &str, Marker); 2] = [
+    ("RED_TOKEN", "RED_TOKEN_MARKED", Marker::Circle),
+    ("YELLOW_TOKEN", "YELLOW_TOKEN_MARKED", Marker::Cross),
+];
+
+/// Sprites to recolour in the colour-blind palette, as (sprite, channel mix).
+/// Each row of the mix gives one channel of the new colour, in eighths of the old red, green and
+/// blue. Red becomes blue and yellow becomes orange.
+const COLOUR_BLIND_MIXES: [(&str, [[u16; 3]; 3]); 2] = [
+    ("RED_TOKEN", RED_TO_BLUE),
+    ("YELLOW_TOKEN", YELLOW_TO_ORANGE),
+];
+
+/// The font's player colours get the same mixes as the tokens, as (logical index, channel mix).
+const FONT_COLOUR_BLIND_MIXES: [(u8, [[u16; 3]; 3]); 2] =
+    [(font::RED, RED_TO_BLUE), (font::YELLOW, YELLOW_TO_ORANGE)];
+
+const RED_TO_BLUE: [[u16; 3]; 3] = [[0, 0, 8], [0, 8, 0], [8, 0, 0]];
+const YELLOW_TO_ORANGE: [[u16; 3]; 3] = [[8, 0, 0], [0, 5, 0], [0, 0, 8]];
+
+// Colours whose channels are all within this of each other are left alone when recolouring,
+// so that outlines and highlights stay the same.
+const GREY_TOLERANCE: u16 = 4;
+
+#[derive(Clone, Copy)]
+enum Marker {
+    Circle,
+    Cross,
+}
+
 pub struct SpriteWithPalette {
     pub name: String,
     pub palette: palette::Palette,
@@ -48,6 +91,213 @@ pub fn find_sprites(directory: &Path) -> Result<Vec<SpriteWithPalette>, SpriteEr
     Ok(asefiles)
 }
 
+/// Generate the tinted copies listed in `TINTED_VARIANTS` from the sprites that have been found.
+pub fn add_tinted_variants(sprites: &mut Vec<SpriteWithPalette>) -> Result<(), SpriteError> {
+    for (source_name, variant_name, tint) in TINTED_VARIANTS {
+        let source = sprites
+            .iter()
+            .find(|s| s.name == source_name)
+            .ok_or(SpriteError)?;
+
+        let variant = source.tinted_copy(variant_name, tint);
+        sprites.push(variant);
+    }
+
+    Ok(())
+}
+
+/// Generate the marked copies listed in `MARKED_VARIANTS` from the sprites that have been found.
+pub fn add_marked_variants(sprites: &mut Vec<SpriteWithPalette>) -> Result<(), SpriteError> {
+    for (source_name, variant_name, marker) in MARKED_VARIANTS {
+        let source = sprites
+            .iter()
+            .find(|s| s.name == source_name)
+            .ok_or(SpriteError)?;
+
+        let variant = source.marked_copy(variant_name, marker);
+        sprites.push(variant);
+    }
+
+    Ok(())
+}
+
+/// The entries of the full object palette that change in the colour-blind palette,
+/// as (index, new colour).
+pub fn colour_blind_palette_entries(
+    sprites: &[SpriteWithPalette],
+    font: &Font,
+    palette_mapper: &palette::PaletteMapper,
+) -> Result<Vec<(usize, u16)>, SpriteError> {
+    let mut entries: Vec<(usize, u16)> = Vec::new();
+
+    for (sprite_name, mix) in COLOUR_BLIND_MIXES {
+        let sprite = sprites
+            .iter()
+            .find(|s| s.name == sprite_name)
+            .ok_or(SpriteError)?;
+
+        let mapped_palette = palette_mapper
+            .map_palette(&sprite.palette, sprite.transparency_index)
+            .ok_or(SpriteError)?;
+
+        let bank_start = usize::from(mapped_palette.palette_bank()) * palette::PAL_BANK_SIZE;
+
+        for (i, color) in sprite.palette.iter().enumerate() {
+            let raw_index: u8 = i.try_into().unwrap();
+
+            if Some(raw_index) == sprite.transparency_index || is_grey(*color) {
+                continue;
+            }
+
+            let index = bank_start + usize::from(mapped_palette.map_index(raw_index));
+
+            // Sprites can share colours, in which case the first mix wins.
+            if !entries.iter().any(|(existing, _)| *existing == index) {
+                entries.push((index, mix_color(*color, mix)));
+            }
+        }
+    }
+
+    let font_palette = font.palette();
+    let mapped_palette = palette_mapper
+        .map_palette(&font_palette, None)
+        .ok_or(SpriteError)?;
+    let bank_start = usize::from(mapped_palette.palette_bank()) * palette::PAL_BANK_SIZE;
+
+    for (logical_index, mix) in FONT_COLOUR_BLIND_MIXES {
+        // The font's palette lists the colours in the order of their logical indices.
+        let raw_index = logical_index - 1;
+        let index = bank_start + usize::from(mapped_palette.map_index(raw_index));
+        let color = font_palette[usize::from(raw_index)];
+
+        if !entries.iter().any(|(existing, _)| *existing == index) {
+            entries.push((index, mix_color(color, mix)));
+        }
+    }
+
+    Ok(entries)
+}
+
+impl SpriteWithPalette {
+    /// Copy the sprite with the marker drawn over its centre in its darkest colour.
+    /// Only opaque pixels are drawn on, so the marker never spills outside the sprite.
+    fn marked_copy(&self, name: &str, marker: Marker) -> Self {
+        let marker_index = (0..self.palette.len())
+            .filter(|i| Some(*i) != self.transparency_index.map(usize::from))
+            .min_by_key(|i| luma(self.palette[*i]))
+            .expect("Marked sprites need at least one opaque colour.");
+        let marker_index: u8 = marker_index.try_into().unwrap();
+
+        let image_data = self
+            .image_data
+            .iter()
+            .map(|frame| {
+                frame
+                    .iter()
+                    .enumerate()
+                    .map(|(i, pixel)| {
+                        // Measured from the centre in half pixels, so that even sizes are symmetric.
+                        let x = (2 * (i % self.width)) as i32 - self.width as i32 + 1;
+                        let y = (2 * (i / self.width)) as i32 - self.height as i32 + 1;
+
+                        if Some(*pixel) != self.transparency_index && marker.covers(x, y) {
+                            marker_index
+                        } else {
+                            *pixel
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Self {
+            name: name.to_string(),
+            palette: self.palette.clone(),
+            width: self.width,
+            height: self.height,
+            image_data,
+            transparency_index: self.transparency_index,
+            num_frames: self.num_frames,
+        }
+    }
+
+    /// Copy the sprite with every colour replaced by the tint, at the colour's original brightness.
+    fn tinted_copy(&self, name: &str, tint: u16) -> Self {
+        let colors: Vec<u16> = self
+            .palette
+            .iter()
+            .enumerate()
+            .map(|(i, color)| {
+                if Some(i) == self.transparency_index.map(usize::from) {
+                    *color
+                } else {
+                    tint_color(*color, tint)
+                }
+            })
+            .collect();
+
+        Self {
+            name: name.to_string(),
+            palette: palette::Palette::new(colors),
+            width: self.width,
+            height: self.height,
+            image_data: self.image_data.clone(),
+            transparency_index: self.transparency_index,
+            num_frames: self.num_frames,
+        }
+    }
+}
+
+const fn rgb15(red: u16, green: u16, blue: u16) -> u16 {
+    red | (green << 5) | (blue << 10)
+}
+
+fn channels(color: u16) -> [u16; 3] {
+    [color & 0x1F, (color >> 5) & 0x1F, (color >> 10) & 0x1F]
+}
+
+/// Perceived brightness, weighted towards green.
+fn luma(color: u16) -> u16 {
+    let [red, green, blue] = channels(color);
+    (red * 77 + green * 150 + blue * 29) >> 8
+}
+
+fn tint_color(color: u16, tint: u16) -> u16 {
+    let luma = luma(color);
+    let [red, green, blue] = channels(tint);
+
+    rgb15(red * luma / 31, green * luma / 31, blue * luma / 31)
+}
+
+fn mix_color(color: u16, mix: [[u16; 3]; 3]) -> u16 {
+    let old = channels(color);
+    let [red, green, blue] = mix.map(|row| {
+        let sum: u16 = row.iter().zip(old).map(|(weight, c)| weight * c).sum();
+        (sum / 8).min(0x1F)
+    });
+
+    rgb15(red, green, blue)
+}
+
+fn is_grey(color: u16) -> bool {
+    let channels = channels(color);
+    let max = channels.iter().max().unwrap();
+    let min = channels.iter().min().unwrap();
+
+    max - min <= GREY_TOLERANCE
+}
+
+impl Marker {
+    /// Whether the marker covers a point, measured from the centre of the sprite in half pixels.
+    fn covers(&self, x: i32, y: i32) -> bool {
+        match self {
+            // A ring around three and a half pixels across.
+            Marker::Circle => (31..=71).contains(&(x * x + y * y)),
+            Marker::Cross => x.abs() == y.abs() && x.abs() <= 7,
+        }
+    }
+}
+
 fn extract_sprite_palette(ase: AsepriteFile, filename: String) -> SpriteWithPalette {
     assert!(
         ase.is_indexed_color(),
@@ -18,6 +18,25 @@ pub fn generate_palette_array_src(palette: &palette::Palette, var_prefix: &str)
     .to_string()
 }
 
+/// Entries to overwrite in a palette, as (index, colour).
+pub fn generate_palette_entries_src(entries: &[(usize, u16)], var_prefix: &str) -> String {
+    let ident = format_ident!("{}_PALETTE_ENTRIES", var_prefix);
+    let num_entries = entries.len();
+
+    let entries: Vec<String> = entries
+        .iter()
+        .map(|(index, color)| quote! { (#index, gba::video::Color(#color)) }.to_string())
+        .collect();
+
+    let entries = format!("[ {} ]", entries.join(","));
+    let entries: syn::Expr = syn::parse_str(&entries).unwrap();
+
+    quote! {
+        pub static #ident: [(usize, gba::video::Color); #num_entries] = #entries;
+    }
+    .to_string()
+}
+
 pub fn generate_sprite_struct_src(
     sprite: &SpriteWithPalette,
     palette_mapper: &palette::PaletteMapper,
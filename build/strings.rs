@@ -0,0 +1,252 @@
+use std::{fs::read_dir, path::Path};
+
+use crate::font::Font;
+
+pub mod codegen;
+
+/// Every other language must translate the messages in this one. It is also the default language.
+const REFERENCE_LANGUAGE: &str = "en.txt";
+
+/// The language is stored in a single byte of save data, and counted from one in the settings.
+const MAX_LANGUAGES: usize = 255;
+
+// Marks where a value, such as a player's name, is filled in at runtime.
+const PLACEHOLDER: &str = "{}";
+
+#[derive(Debug)]
+pub struct StringsError(String);
+
+pub struct Language {
+    /// The name of the language, written in that language.
+    name: String,
+    /// In the same order as the string table's message IDs.
+    messages: Vec<String>,
+}
+
+pub struct StringTable {
+    message_ids: Vec<String>,
+    /// The reference language comes first, then the rest in filename order.
+    languages: Vec<Language>,
+}
+
+impl StringTable {
+    /// Make sure every message can be drawn with the font.
+    pub fn check_font(&self, font: &Font) -> Result<(), StringsError> {
+        for language in &self.languages {
+            // Placeholders are swapped for text that is checked separately.
+            let text: Vec<String> = language
+                .messages
+                .iter()
+                .chain([&language.name])
+                .map(|message| message.replace(PLACEHOLDER, ""))
+                .collect();
+
+            if let Some(character) = text
+                .iter()
+                .flat_map(|message| message.chars())
+                .find(|character| !font.has_glyph(*character))
+            {
+                return Err(StringsError(format!(
+                    "{}: The font has no glyph for '{}'.",
+                    language.name, character
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Read every language in the directory.
+///
+/// Languages are text files made up of `key: value` lines. The `language` key gives the
+/// language's name, and every other key is a message ID. `{}` marks where a value is filled in.
+/// Lines starting with `#` are comments.
+///
+/// ```text
+/// language: English
+///
+/// press_start: Press Start
+/// wins: {} wins!
+/// ```
+pub fn find_string_table(directory: &Path) -> Result<StringTable, StringsError> {
+    let entries = read_dir(directory)
+        .map_err(|_e| StringsError("Failed to read strings dir.".to_string()))?;
+
+    let mut paths = Vec::new();
+
+    for entry in entries {
+        let entry = entry.map_err(|_e| StringsError("Failed to read strings file.".to_string()))?;
+        let path = entry.path();
+
+        assert!(
+            path.is_file(),
+            "/strings dir cannot contain nested directories."
+        );
+
+        paths.push(path);
+    }
+
+    paths.sort();
+
+    if paths.len() > MAX_LANGUAGES {
+        return Err(StringsError(format!(
+            "Found {} languages, but at most {} are supported.",
+            paths.len(),
+            MAX_LANGUAGES
+        )));
+    }
+
+    // Move the reference language to the front.
+    let reference_index = paths
+        .iter()
+        .position(|path| path.file_name().unwrap() == REFERENCE_LANGUAGE)
+        .ok_or_else(|| StringsError(format!("Missing '{}'.", REFERENCE_LANGUAGE)))?;
+    let reference_path = paths.remove(reference_index);
+    paths.insert(0, reference_path);
+
+    let files = paths
+        .iter()
+        .map(|path| {
+            let filename = path.file_name().unwrap().to_string_lossy().to_string();
+
+            let text = std::fs::read_to_string(path)
+                .map_err(|_e| StringsError(format!("{}: Failed to read file.", filename)))?;
+
+            let file = parse_language_file(&text)
+                .map_err(|e| StringsError(format!("{}: {}", filename, e.0)))?;
+
+            Ok((filename, file))
+        })
+        .collect::<Result<Vec<(String, LanguageFile)>, StringsError>>()?;
+
+    let (_, reference) = &files[0];
+    let message_ids: Vec<String> = reference
+        .messages
+        .iter()
+        .map(|(id, _)| id.clone())
+        .collect();
+
+    let languages = files
+        .iter()
+        .map(|(filename, file)| {
+            file.translate(reference)
+                .map_err(|e| StringsError(format!("{}: {}", filename, e.0)))
+        })
+        .collect::<Result<Vec<Language>, StringsError>>()?;
+
+    Ok(StringTable {
+        message_ids,
+        languages,
+    })
+}
+
+/// A language as written in its file, before it has been checked against the reference language.
+struct LanguageFile {
+    name: String,
+    /// Pairs of message ID and text, in the order they were written.
+    messages: Vec<(String, String)>,
+}
+
+impl LanguageFile {
+    fn get(&self, message_id: &str) -> Option<&str> {
+        self.messages
+            .iter()
+            .find(|(id, _)| id == message_id)
+            .map(|(_, text)| text.as_str())
+    }
+
+    /// Put the messages in the reference language's order, making sure none are missing or extra.
+    fn translate(&self, reference: &LanguageFile) -> Result<Language, StringsError> {
+        if let Some((id, _)) = self
+            .messages
+            .iter()
+            .find(|(id, _)| reference.get(id).is_none())
+        {
+            return Err(StringsError(format!(
+                "'{}' is not in the reference language.",
+                id
+            )));
+        }
+
+        let messages = reference
+            .messages
+            .iter()
+            .map(|(id, reference_text)| {
+                let text = self
+                    .get(id)
+                    .ok_or_else(|| StringsError(format!("Missing message '{}'.", id)))?;
+
+                if text.matches(PLACEHOLDER).count() != reference_text.matches(PLACEHOLDER).count()
+                {
+                    return Err(StringsError(format!(
+                        "'{}' must have the same number of '{}' as the reference language.",
+                        id, PLACEHOLDER
+                    )));
+                }
+
+                Ok(text.to_string())
+            })
+            .collect::<Result<Vec<String>, StringsError>>()?;
+
+        Ok(Language {
+            name: self.name.clone(),
+            messages,
+        })
+    }
+}
+
+fn parse_language_file(text: &str) -> Result<LanguageFile, StringsError> {
+    let mut name = None;
+    let mut messages: Vec<(String, String)> = Vec::new();
+
+    for line in text.lines().map(|line| line.trim()) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (key, value) = line
+            .split_once(':')
+            .ok_or_else(|| StringsError(format!("Expected 'key: value', found '{}'.", line)))?;
+
+        let key = key.trim();
+        let value = value.trim();
+
+        if key == "language" {
+            name = Some(value.to_string());
+            continue;
+        }
+
+        if key.is_empty()
+            || key.starts_with(|c: char| c.is_ascii_digit())
+            || !key
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+        {
+            return Err(StringsError(format!(
+                "'{}' is not a valid message ID. Use lower case letters, digits and underscores.",
+                key
+            )));
+        }
+
+        if messages.iter().any(|(id, _)| id == key) {
+            return Err(StringsError(format!(
+                "Message '{}' is defined more than once.",
+                key
+            )));
+        }
+
+        if value.matches(PLACEHOLDER).count() > 1 {
+            return Err(StringsError(format!(
+                "Message '{}' has more than one '{}'.",
+                key, PLACEHOLDER
+            )));
+        }
+
+        messages.push((key.to_string(), value.to_string()));
+    }
+
+    let name = name.ok_or(StringsError("Missing 'language'.".to_string()))?;
+
+    Ok(LanguageFile { name, messages })
+}
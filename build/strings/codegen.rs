@@ -0,0 +1,55 @@
+use quote::{format_ident, quote};
+use syn::Ident;
+
+use crate::strings::StringTable;
+
+/// Message IDs become the variants of a `Message` enum, which index into each language's strings.
+pub fn generate_string_table_src(table: &StringTable) -> String {
+    let num_messages = table.message_ids.len();
+    let num_languages = table.languages.len();
+
+    let variants: Vec<Ident> = table
+        .message_ids
+        .iter()
+        .map(|id| format_ident!("{}", variant_name(id)))
+        .collect();
+
+    let languages: Vec<String> = table
+        .languages
+        .iter()
+        .map(|language| {
+            let name = &language.name;
+            let messages = &language.messages;
+
+            quote! { StringTable { name: #name, messages: [ #(#messages),* ] } }.to_string()
+        })
+        .collect();
+
+    let languages = format!("[ {} ]", languages.join(","));
+    let languages: syn::Expr = syn::parse_str(&languages).expect("Error producing string tables.");
+
+    quote! {
+        #[derive(Clone, Copy, PartialEq, Eq)]
+        pub enum Message {
+            #(#variants),*
+        }
+
+        const NUM_MESSAGES: usize = #num_messages;
+
+        static LANGUAGES: [StringTable; #num_languages] = #languages;
+    }
+    .to_string()
+}
+
+/// Turn a `snake_case` message ID into a `CamelCase` variant name.
+fn variant_name(message_id: &str) -> String {
+    message_id
+        .split('_')
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            let first = chars.next().unwrap().to_ascii_uppercase();
+            first.to_string() + chars.as_str()
+        })
+        .collect()
+}
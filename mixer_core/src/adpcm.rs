@@ -0,0 +1,148 @@
+/// Step sizes for IMA-ADPCM. The build script's encoder uses these too.
+pub const STEP_TABLE: [i32; 89] = [
+    7, 8, 9, 10, 11, 12, 13, 14, 16, 17, 19, 21, 23, 25, 28, 31, 34, 37, 41, 45, 50, 55, 60, 66,
+    73, 80, 88, 97, 107, 118, 130, 143, 157, 173, 190, 209, 230, 253, 279, 307, 337, 371, 408, 449,
+    494, 544, 598, 658, 724, 796, 876, 963, 1060, 1166, 1282, 1411, 1552, 1707, 1878, 2066, 2272,
+    2499, 2749, 3024, 3327, 3660, 4026, 4428, 4871, 5358, 5894, 6484, 7132, 7845, 8630, 9493,
+    10442, 11487, 12635, 13899, 15289, 16818, 18500, 20350, 22385, 24623, 27086, 29794, 32767,
+];
+
+/// How far along the step table each code moves, by the code's magnitude.
+pub const INDEX_TABLE: [i32; 8] = [-1, -1, -1, -1, 2, 4, 6, 8];
+
+/// Decodes IMA-ADPCM samples as they are needed, so only the compressed data is kept in ROM.
+/// ADPCM can only be decoded in order, so the decoder holds on to the two samples either side of
+/// the playback position for interpolating between.
+pub struct AdpcmDecoder {
+    data: &'static [u8],
+    // The last byte is only half used when there's an odd number of samples.
+    num_samples: usize,
+    // Each byte holds two samples, so this counts 4-bit codes.
+    code_position: usize,
+    predictor: i32,
+    step_index: usize,
+    loop_start: Option<usize>,
+    loop_predictor: i16,
+    loop_step_index: u8,
+    current: i8,
+    next: i8,
+    // The fractional position between the current and next samples, in 16.16 fixed point.
+    fraction: u32,
+    is_finished: bool,
+}
+
+impl AdpcmDecoder {
+    pub fn new(
+        data: &'static [u8],
+        num_samples: usize,
+        loop_start: Option<usize>,
+        loop_predictor: i16,
+        loop_step_index: u8,
+    ) -> Self {
+        let mut decoder = Self {
+            data,
+            num_samples,
+            code_position: 0,
+            predictor: 0,
+            step_index: 0,
+            loop_start,
+            loop_predictor,
+            loop_step_index,
+            current: 0,
+            next: 0,
+            fraction: 0,
+            is_finished: false,
+        };
+
+        // Prime the first pair of samples.
+        for _ in 0..2 {
+            decoder.advance();
+        }
+
+        decoder
+    }
+
+    /// Fill the buffer, stepping through the samples at the given 16.16 fixed point rate.
+    /// Returns false once a non-looping sound has run out of samples.
+    pub fn read_samples(&mut self, buffer: &mut [u8], rate: u32) -> bool {
+        for output in buffer.iter_mut() {
+            if self.is_finished {
+                return false;
+            }
+
+            let current: i32 = self.current.into();
+            let next: i32 = self.next.into();
+
+            // Only the top 8 bits of the fraction are needed, which keeps the multiply small.
+            let weight: i32 = (self.fraction >> 8).try_into().unwrap();
+            let sample: i8 = (current + (((next - current) * weight) >> 8))
+                .try_into()
+                .unwrap();
+            *output = sample.to_ne_bytes()[0];
+
+            let fraction = self.fraction + rate;
+            self.fraction = fraction & 0xFFFF;
+
+            for _ in 0..(fraction >> 16) {
+                self.advance();
+            }
+        }
+
+        !self.is_finished
+    }
+
+    fn advance(&mut self) {
+        self.current = self.next;
+
+        match self.decode_next() {
+            Some(sample) => self.next = sample,
+            None => self.is_finished = true,
+        }
+    }
+
+    fn decode_next(&mut self) -> Option<i8> {
+        if self.code_position >= self.num_samples {
+            let loop_start = self.loop_start?;
+
+            self.code_position = loop_start;
+            self.predictor = self.loop_predictor.into();
+            self.step_index = self.loop_step_index.into();
+        }
+
+        let byte = self.data[self.code_position / 2];
+        let code = if self.code_position % 2 == 0 {
+            byte & 0x0F
+        } else {
+            byte >> 4
+        };
+
+        self.code_position += 1;
+
+        let step = STEP_TABLE[self.step_index];
+        let mut delta = step >> 3;
+
+        if code & 4 != 0 {
+            delta += step;
+        }
+        if code & 2 != 0 {
+            delta += step >> 1;
+        }
+        if code & 1 != 0 {
+            delta += step >> 2;
+        }
+
+        self.predictor = if code & 8 != 0 {
+            self.predictor - delta
+        } else {
+            self.predictor + delta
+        };
+        self.predictor = self.predictor.clamp(i16::MIN.into(), i16::MAX.into());
+
+        let step_index =
+            i32::try_from(self.step_index).unwrap() + INDEX_TABLE[usize::from(code & 7)];
+        self.step_index = step_index.clamp(0, 88).try_into().unwrap();
+
+        // The samples were encoded at 16-bit precision.
+        Some((self.predictor >> 8).try_into().unwrap())
+    }
+}
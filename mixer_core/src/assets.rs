@@ -0,0 +1,23 @@
+/// A sound converted by the build script, along with its default playback settings.
+pub struct SoundAsset {
+    /// Samples at the mixer's sample rate, in the given format.
+    pub samples: &'static [u8],
+    /// Where playback jumps back to after the last sample, for looping sounds.
+    /// This counts samples, not bytes, so it doesn't depend on the format.
+    pub loop_start: Option<usize>,
+    pub volume: u8,
+    pub format: SampleFormat,
+}
+
+pub enum SampleFormat {
+    /// Signed 8-bit samples.
+    Pcm,
+    /// 4-bit IMA-ADPCM, two samples to a byte.
+    /// The decoder's state at the loop start is stored, so that it can jump back there.
+    /// An odd number of samples leaves the last byte half padding, so the real count is kept.
+    Adpcm {
+        num_samples: usize,
+        loop_predictor: i16,
+        loop_step_index: u8,
+    },
+}
@@ -0,0 +1,9 @@
+//! The parts of the audio that only work on buffers of samples: the mixer, its sound sources,
+//! ADPCM decoding and the tracker. Nothing here touches the GBA's hardware, so it can be built
+//! and tested on the host, with the game handling the DMA, timers and FIFOs.
+#![no_std]
+
+pub mod adpcm;
+pub mod assets;
+pub mod mixer;
+pub mod tracker;
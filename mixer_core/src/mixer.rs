@@ -0,0 +1,789 @@
+use crate::{
+    adpcm::AdpcmDecoder,
+    assets::{SampleFormat, SoundAsset},
+    tracker::{Song, SongPlayer},
+};
+
+// One frame's worth of samples at the sample rate, see the game's audio/mixer/output.rs for how
+// these were chosen.
+pub const AUDIO_BUFFER_SIZE: usize = 304; // Note that this is not a multiple of 32, therefore the DMA writes must be done 16 bits at a time.
+pub const SAMPLE_RATE: u32 = 18157;
+
+// Sounds are panned across the width of the GBA's screen.
+const SCREEN_WIDTH: u16 = 240;
+
+// Channels 0 and 1 are reserved for music, so that one track can fade out while the next fades in.
+// The rest are shared by sound effects.
+const NUM_CHANNELS: usize = 5;
+const NUM_MUSIC_CHANNELS: usize = 2;
+
+// Samples are scaled by volume / 64, so this plays them unchanged.
+const FULL_VOLUME: u8 = 64;
+
+// Gains are fixed point, with this leaving the volume unchanged.
+pub const UNITY_GAIN: u16 = 256;
+
+// Music drops to a quarter of its volume while a stinger plays.
+const DUCKED_GAIN: u16 = UNITY_GAIN / 4;
+const DUCK_ATTACK_FRAMES: u16 = 6;
+const DUCK_RELEASE_FRAMES: u16 = 45;
+
+const EMPTY_CHANNEL: MixerChannel = MixerChannel {
+    source: None,
+    music: None,
+    priority: SfxPriority::Low,
+    id: 0,
+    gain: Gain::UNITY,
+    stop_when_silent: false,
+};
+
+pub struct AudioVolume(u8);
+
+/// How many source samples to step through per output sample, in 16.16 fixed point.
+/// Rates above normal raise the pitch, and rates below lower it.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct PlaybackRate(u32);
+
+/// Stereo position, from -64 for fully left to 64 for fully right.
+#[derive(Clone, Copy)]
+pub struct Pan(i8);
+
+/// Background music, either a looping sound or a tracker song.
+#[derive(Clone, Copy)]
+pub enum Music {
+    Sound(&'static SoundAsset),
+    Song(&'static Song),
+}
+
+/// When every channel is busy, a new sound effect takes over the channel with the lowest priority.
+/// It will never replace a sound effect with a higher priority than its own.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SfxPriority {
+    Low,
+    Normal,
+    High,
+}
+
+/// Refers to a sound effect started by `play_sfx`.
+/// Once the sound has finished, or its channel has been taken by another sound, the handle does nothing.
+#[derive(Clone, Copy)]
+pub struct SfxHandle {
+    channel: usize,
+    id: u16,
+}
+
+pub struct AudioSource {
+    samples: Samples,
+    position: usize,
+    // The fractional part of the position, in the same fixed point format as the rate.
+    fraction: u32,
+    rate: PlaybackRate,
+    pan: Pan,
+    loop_start: Option<usize>,
+    volume: AudioVolume,
+}
+
+/// Uncompressed samples can be read from anywhere, but compressed ones are decoded as they play.
+enum Samples {
+    Pcm(&'static [u8]),
+    Adpcm(AdpcmDecoder),
+}
+
+enum ChannelSource {
+    Sound(AudioSource),
+    Song(SongPlayer),
+}
+
+struct MixerChannel {
+    source: Option<ChannelSource>,
+    // Set on the music channels, so that the same music isn't restarted.
+    music: Option<Music>,
+    priority: SfxPriority,
+    // Distinguishes this sound from earlier ones played on the same channel.
+    id: u16,
+    gain: Gain,
+    // Set when fading out, to free up the channel once it can't be heard.
+    stop_when_silent: bool,
+}
+
+/// A gain that ramps linearly towards its target, moving on once per frame.
+#[derive(Clone, Copy)]
+struct Gain {
+    value: u16,
+    target: u16,
+    step: u16,
+}
+
+/// Mixes the channels down to stereo, a frame at a time. This doesn't touch the hardware, so the
+/// output can also be rendered offline.
+pub struct AudioMixer {
+    channels: [MixerChannel; NUM_CHANNELS],
+    next_sfx_id: u16,
+    // Which of the music channels is playing the current music.
+    active_music_channel: usize,
+    // Applied to the music channels, to bring them down under stingers.
+    duck: Gain,
+    stinger: Option<SfxHandle>,
+    // The player's volume settings for music and sound effects.
+    music_gain: u16,
+    sfx_gain: u16,
+}
+
+impl AudioVolume {
+    pub fn new(value: u8) -> Self {
+        if value > 63 {
+            panic!("Volume may not exceed 63");
+        }
+        Self(value)
+    }
+
+    pub fn get(&self) -> u8 {
+        self.0
+    }
+}
+
+impl PlaybackRate {
+    pub const NORMAL: Self = Self(1 << 16);
+
+    /// A rate of numerator / denominator, so (3, 2) plays a sound a fifth higher.
+    pub fn from_ratio(numerator: u32, denominator: u32) -> Self {
+        Self((numerator << 16) / denominator)
+    }
+}
+
+impl Pan {
+    pub const CENTRE: Self = Self(0);
+
+    pub fn new(value: i8) -> Self {
+        if !(-64..=64).contains(&value) {
+            panic!("Pan must be between -64 and 64");
+        }
+        Self(value)
+    }
+
+    /// Pan towards the side of the screen that something is on.
+    pub fn from_screen_x(x: u16) -> Self {
+        let x: i32 = x.min(SCREEN_WIDTH).into();
+        let width: i32 = SCREEN_WIDTH.into();
+        Self::new(((x * 128) / width - 64).try_into().unwrap())
+    }
+
+    /// Which speakers a sound that can only be switched on or off for each side should play from,
+    /// such as a PSG channel. Anything that isn't far off centre plays from both.
+    pub fn sides(&self) -> (bool, bool) {
+        let (left, right) = self.apply(64);
+        (left >= 32, right >= 32)
+    }
+
+    /// Split a volume between the left and right speakers.
+    /// A centred sound plays at full volume in both.
+    fn apply(&self, volume: u8) -> (u8, u8) {
+        let pan: i16 = self.0.into();
+        let volume: i16 = volume.into();
+
+        let left = volume * (64 - pan.max(0)) / 64;
+        let right = volume * (64 + pan.min(0)) / 64;
+
+        (left.try_into().unwrap(), right.try_into().unwrap())
+    }
+}
+
+impl AudioSource {
+    pub fn new(samples: &'static [u8], volume: AudioVolume, is_loop: bool) -> Self {
+        Self {
+            samples: Samples::Pcm(samples),
+            loop_start: is_loop.then_some(0),
+            volume,
+            position: 0,
+            fraction: 0,
+            rate: PlaybackRate::NORMAL,
+            pan: Pan::CENTRE,
+        }
+    }
+
+    /// Play a sound with the volume and loop point that it was built with.
+    pub fn from_asset(asset: &SoundAsset) -> Self {
+        let samples = match asset.format {
+            SampleFormat::Pcm => Samples::Pcm(asset.samples),
+            SampleFormat::Adpcm {
+                num_samples,
+                loop_predictor,
+                loop_step_index,
+            } => Samples::Adpcm(AdpcmDecoder::new(
+                asset.samples,
+                num_samples,
+                asset.loop_start,
+                loop_predictor,
+                loop_step_index,
+            )),
+        };
+
+        Self {
+            samples,
+            loop_start: asset.loop_start,
+            volume: AudioVolume::new(asset.volume),
+            position: 0,
+            fraction: 0,
+            rate: PlaybackRate::NORMAL,
+            pan: Pan::CENTRE,
+        }
+    }
+
+    pub fn with_rate(mut self, rate: PlaybackRate) -> Self {
+        self.rate = rate;
+        self
+    }
+
+    pub fn with_pan(mut self, pan: Pan) -> Self {
+        self.pan = pan;
+        self
+    }
+
+    /// Fill the buffer with the next samples, looping if needed.
+    /// Returns false once a non-looping source has run out of samples.
+    fn read_samples(&mut self, buffer: &mut [u8]) -> bool {
+        let samples = match self.samples {
+            Samples::Pcm(samples) => samples,
+            Samples::Adpcm(ref mut decoder) => return decoder.read_samples(buffer, self.rate.0),
+        };
+
+        if self.rate == PlaybackRate::NORMAL && self.fraction == 0 {
+            self.copy_samples(samples, buffer)
+        } else {
+            self.resample(samples, buffer)
+        }
+    }
+
+    /// At the normal rate, the samples can be copied straight across.
+    fn copy_samples(&mut self, samples: &[u8], buffer: &mut [u8]) -> bool {
+        let mut filled = 0;
+
+        while filled < buffer.len() {
+            let count = (samples.len() - self.position).min(buffer.len() - filled);
+
+            buffer[filled..filled + count]
+                .copy_from_slice(&samples[self.position..self.position + count]);
+
+            filled += count;
+            self.position += count;
+
+            if self.position == samples.len() {
+                match self.loop_start {
+                    Some(loop_start) => self.position = loop_start,
+                    None => return false,
+                }
+            }
+        }
+
+        true
+    }
+
+    /// Step through the samples at the playback rate, interpolating between neighbouring samples.
+    fn resample(&mut self, samples: &[u8], buffer: &mut [u8]) -> bool {
+        for output in buffer.iter_mut() {
+            let next_position = match (self.position + 1, self.loop_start) {
+                (next, _) if next < samples.len() => next,
+                (_, Some(loop_start)) => loop_start,
+                (_, None) => self.position,
+            };
+
+            // The buffers are represented as u8, but the data is actually i8.
+            let current: i32 = i8::from_ne_bytes([samples[self.position]]).into();
+            let next: i32 = i8::from_ne_bytes([samples[next_position]]).into();
+
+            // Only the top 8 bits of the fraction are needed, which keeps the multiply small.
+            let weight: i32 = (self.fraction >> 8).try_into().unwrap();
+            let sample: i8 = (current + (((next - current) * weight) >> 8))
+                .try_into()
+                .unwrap();
+            *output = sample.to_ne_bytes()[0];
+
+            let fraction = self.fraction + self.rate.0;
+            self.position += usize::try_from(fraction >> 16).unwrap();
+            self.fraction = fraction & 0xFFFF;
+
+            if self.position >= samples.len() {
+                match self.loop_start {
+                    Some(loop_start) => {
+                        let loop_length = samples.len() - loop_start;
+                        self.position = loop_start + (self.position - samples.len()) % loop_length;
+                    }
+                    None => return false,
+                }
+            }
+        }
+
+        true
+    }
+}
+
+impl Gain {
+    const UNITY: Self = Self {
+        value: UNITY_GAIN,
+        target: UNITY_GAIN,
+        step: 0,
+    };
+
+    /// Start moving towards the target, reaching it after the given number of frames.
+    fn ramp_to(&mut self, target: u16, frames: u16) {
+        self.target = target;
+
+        if frames == 0 {
+            self.value = target;
+            self.step = 0;
+        } else {
+            self.step = (self.value.abs_diff(target) / frames).max(1);
+        }
+    }
+
+    fn update(&mut self) {
+        if self.value < self.target {
+            self.value = (self.value + self.step).min(self.target);
+        } else {
+            self.value = self.value.saturating_sub(self.step).max(self.target);
+        }
+    }
+
+    fn apply(&self, volume: u8) -> u8 {
+        apply_gain(volume, self.value)
+    }
+
+    fn is_silent(&self) -> bool {
+        self.value == 0 && self.target == 0
+    }
+}
+
+impl MixerChannel {
+    /// The volume the channel is currently playing at, taking the gain into account.
+    /// Music is also ducked under stingers.
+    fn get_volume(&self, duck: &Gain) -> u8 {
+        let volume = match self.source {
+            Some(ChannelSource::Sound(ref source)) => source.volume.get(),
+            Some(ChannelSource::Song(_)) => FULL_VOLUME,
+            None => return 0,
+        };
+
+        let volume = self.gain.apply(volume);
+
+        match self.music {
+            Some(_) => duck.apply(volume),
+            None => volume,
+        }
+    }
+
+    fn fade_in(&mut self, frames: u16) {
+        self.gain.value = 0;
+        self.gain.ramp_to(UNITY_GAIN, frames);
+        self.stop_when_silent = false;
+    }
+
+    fn fade_out(&mut self, frames: u16) {
+        self.gain.ramp_to(0, frames);
+        self.stop_when_silent = true;
+    }
+
+    /// Move the gain on by a frame, stopping the channel once it has faded out.
+    fn update_gain(&mut self) {
+        self.gain.update();
+
+        if self.stop_when_silent && self.gain.is_silent() {
+            self.stop();
+        }
+    }
+
+    fn stop(&mut self) {
+        self.source = None;
+        self.music = None;
+        self.gain = Gain::UNITY;
+        self.stop_when_silent = false;
+    }
+}
+
+impl Music {
+    fn is_same(&self, other: &Music) -> bool {
+        match (self, other) {
+            (Music::Sound(a), Music::Sound(b)) => core::ptr::eq(*a, *b),
+            (Music::Song(a), Music::Song(b)) => core::ptr::eq(*a, *b),
+            _ => false,
+        }
+    }
+}
+
+impl AudioMixer {
+    pub const fn new() -> Self {
+        Self {
+            channels: [EMPTY_CHANNEL; NUM_CHANNELS],
+            next_sfx_id: 0,
+            active_music_channel: 0,
+            duck: Gain::UNITY,
+            stinger: None,
+            music_gain: UNITY_GAIN,
+            sfx_gain: UNITY_GAIN,
+        }
+    }
+
+    /// Fade the current music out while the new music fades in.
+    pub fn crossfade_music(&mut self, music: Music, frames: u16) {
+        let active_channel = &mut self.channels[self.active_music_channel];
+
+        // Carry on playing if the next screen uses the same music.
+        if let Some(ref current) = active_channel.music {
+            if current.is_same(&music) && !active_channel.stop_when_silent {
+                return;
+            }
+        }
+
+        active_channel.fade_out(frames);
+
+        let source = match music {
+            Music::Sound(asset) => ChannelSource::Sound(AudioSource::from_asset(asset)),
+            Music::Song(song) => ChannelSource::Song(SongPlayer::new(song)),
+        };
+
+        let next_channel = (self.active_music_channel + 1) % NUM_MUSIC_CHANNELS;
+
+        self.channels[next_channel] = MixerChannel {
+            source: Some(source),
+            music: Some(music),
+            ..EMPTY_CHANNEL
+        };
+        self.channels[next_channel].fade_in(frames);
+
+        self.active_music_channel = next_channel;
+    }
+
+    /// Set the overall gain of the music and of the sound effects, on top of their own volumes.
+    pub fn set_master_gains(&mut self, music_gain: u16, sfx_gain: u16) {
+        self.music_gain = music_gain;
+        self.sfx_gain = sfx_gain;
+    }
+
+    /// Play an important sound, with the music ducked underneath it until it finishes.
+    pub fn play_stinger(&mut self, source: AudioSource) -> Option<SfxHandle> {
+        let handle = self.play_sfx(source, SfxPriority::High)?;
+
+        self.duck.ramp_to(DUCKED_GAIN, DUCK_ATTACK_FRAMES);
+        self.stinger = Some(handle);
+
+        Some(handle)
+    }
+
+    fn update_duck(&mut self) {
+        if let Some(stinger) = self.stinger {
+            if self.get_sfx_channel(&stinger).is_none() {
+                self.stinger = None;
+                self.duck.ramp_to(UNITY_GAIN, DUCK_RELEASE_FRAMES);
+            }
+        }
+
+        self.duck.update();
+    }
+
+    pub fn play_sfx(&mut self, source: AudioSource, priority: SfxPriority) -> Option<SfxHandle> {
+        let channel = self.allocate_sfx_channel(priority)?;
+
+        let id = self.next_sfx_id;
+        self.next_sfx_id = self.next_sfx_id.wrapping_add(1);
+
+        self.channels[channel] = MixerChannel {
+            source: Some(ChannelSource::Sound(source)),
+            priority,
+            id,
+            ..EMPTY_CHANNEL
+        };
+
+        Some(SfxHandle { channel, id })
+    }
+
+    /// Pick a free channel if there is one, otherwise steal the quietest of the lowest priority
+    /// sounds. Returns None if every channel is playing something more important.
+    fn allocate_sfx_channel(&self, priority: SfxPriority) -> Option<usize> {
+        let sfx_channels = NUM_MUSIC_CHANNELS..NUM_CHANNELS;
+
+        let free_channel = sfx_channels
+            .clone()
+            .find(|c| self.channels[*c].source.is_none());
+
+        if free_channel.is_some() {
+            return free_channel;
+        }
+
+        sfx_channels
+            .filter(|c| self.channels[*c].priority <= priority)
+            .min_by_key(|c| {
+                let channel = &self.channels[*c];
+                (channel.priority, channel.get_volume(&self.duck))
+            })
+    }
+
+    pub fn is_sfx_playing(&mut self, handle: &SfxHandle) -> bool {
+        self.get_sfx_channel(handle).is_some()
+    }
+
+    pub fn stop_sfx(&mut self, handle: &SfxHandle) {
+        if let Some(channel) = self.get_sfx_channel(handle) {
+            channel.stop();
+        }
+    }
+
+    /// Fade the sound in from silence over the given number of frames.
+    pub fn fade_in_sfx(&mut self, handle: &SfxHandle, frames: u16) {
+        if let Some(channel) = self.get_sfx_channel(handle) {
+            channel.fade_in(frames);
+        }
+    }
+
+    /// Fade the sound out over the given number of frames, then stop it.
+    pub fn fade_out_sfx(&mut self, handle: &SfxHandle, frames: u16) {
+        if let Some(channel) = self.get_sfx_channel(handle) {
+            channel.fade_out(frames);
+        }
+    }
+
+    fn get_sfx_channel(&mut self, handle: &SfxHandle) -> Option<&mut MixerChannel> {
+        let channel = &mut self.channels[handle.channel];
+
+        if channel.id == handle.id && channel.source.is_some() {
+            Some(channel)
+        } else {
+            None
+        }
+    }
+
+    /// Mix the next frame of every channel into the buffers, which should start out silent.
+    pub fn mix_frame(&mut self, left: &mut [u8], right: &mut [u8]) {
+        for c in 0..NUM_CHANNELS {
+            let channel = &mut self.channels[c];
+            let master_gain = match channel.music {
+                Some(_) => self.music_gain,
+                None => self.sfx_gain,
+            };
+            let volume = apply_gain(channel.get_volume(&self.duck), master_gain);
+
+            // Copy the audio source into this buffer, then mix it into the back buffers.
+            let mut next_buffer = [0u8; AUDIO_BUFFER_SIZE];
+
+            // Songs are rendered in the centre.
+            let (is_playing, pan) = match channel.source {
+                Some(ChannelSource::Sound(ref mut audio_source)) => (
+                    audio_source.read_samples(&mut next_buffer),
+                    audio_source.pan,
+                ),
+                Some(ChannelSource::Song(ref mut song_player)) => {
+                    song_player.render(&mut next_buffer);
+                    (true, Pan::CENTRE)
+                }
+                None => continue,
+            };
+
+            // If it's not a loop then we can drop the audio source once it has finished.
+            if !is_playing {
+                channel.stop();
+            }
+
+            channel.update_gain();
+
+            let (left_volume, right_volume) = pan.apply(volume);
+            mix_samples(left, &next_buffer, left_volume);
+            mix_samples(right, &next_buffer, right_volume);
+        }
+
+        self.update_duck();
+    }
+
+    /// Render the given number of frames without any hardware, passing each frame's left and right
+    /// samples to the callback. Used for checking the mixer's output away from the GBA.
+    pub fn render_frames<F>(&mut self, frames: usize, mut on_frame: F)
+    where
+        F: FnMut(&[u8], &[u8]),
+    {
+        for _ in 0..frames {
+            let mut left = [0u8; AUDIO_BUFFER_SIZE];
+            let mut right = [0u8; AUDIO_BUFFER_SIZE];
+
+            self.mix_frame(&mut left, &mut right);
+            on_frame(&left, &right);
+        }
+    }
+}
+
+/// Scale a volume by a fixed point gain.
+fn apply_gain(volume: u8, gain: u16) -> u8 {
+    (u32::from(volume) * u32::from(gain) / u32::from(UNITY_GAIN))
+        .try_into()
+        .unwrap()
+}
+
+/// Mix samples into the buffer at the given volume, clipping the result.
+fn mix_samples(buffer: &mut [u8], samples: &[u8], volume: u8) {
+    let volume: i16 = volume.into();
+
+    for (buffered, sample) in buffer.iter_mut().zip(samples.iter()) {
+        // The buffers are represented as u8, but the data is actually i8
+        // So reinterpret the data as i8 before these shifts.
+        let sample: i8 = i8::from_ne_bytes([*sample]);
+        let current: i8 = i8::from_ne_bytes([*buffered]);
+        let current: i16 = current.into();
+
+        // Store samples in i16 before mixing, then clip afterwards.
+        let mut sample: i16 = sample.into();
+        sample = (sample * volume) >> 6;
+
+        let mut mixed: i16 = current + sample;
+
+        // Clip to i8
+        mixed = mixed.clamp(i8::MIN.into(), i8::MAX.into());
+
+        // Reinterpret as u8 before writing it to the buffer.
+        let mixed: i8 = mixed.try_into().unwrap();
+        *buffered = mixed.to_ne_bytes()[0];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOUDEST: u8 = 63;
+
+    // Long enough to need a second buffer, so the loop wraps part way through it.
+    const LOOP_LENGTH: usize = 500;
+    const LOOP_START: usize = 100;
+    static LOOP_SAMPLES: [u8; LOOP_LENGTH] = sawtooth();
+
+    // Any data is valid ADPCM, so this is only checked against its own earlier output.
+    static ADPCM_ASSET: SoundAsset = SoundAsset {
+        samples: &ADPCM_DATA,
+        loop_start: Some(150),
+        volume: 48,
+        format: SampleFormat::Adpcm {
+            num_samples: 399,
+            loop_predictor: -1200,
+            loop_step_index: 30,
+        },
+    };
+    static ADPCM_DATA: [u8; 200] = sawtooth();
+
+    /// Samples that step through every value, so a sample read from the wrong place stands out.
+    const fn sawtooth<const N: usize>() -> [u8; N] {
+        let mut samples = [0; N];
+        let mut i = 0;
+
+        while i < N {
+            samples[i] = (i * 7 + 3) as u8;
+            i += 1;
+        }
+
+        samples
+    }
+
+    /// FNV-1a over each frame's left and then right samples.
+    fn render_checksum(mixer: &mut AudioMixer, frames: usize) -> u32 {
+        let mut hash: u32 = 0x811c_9dc5;
+
+        mixer.render_frames(frames, |left, right| {
+            for byte in left.iter().chain(right) {
+                hash = (hash ^ u32::from(*byte)).wrapping_mul(0x0100_0193);
+            }
+        });
+
+        hash
+    }
+
+    /// What the mixer makes of a single sample at the given volume.
+    fn scaled(sample: u8, volume: u8) -> u8 {
+        let sample: i16 = i8::from_ne_bytes([sample]).into();
+        let scaled: i8 = ((sample * i16::from(volume)) >> 6).try_into().unwrap();
+        scaled.to_ne_bytes()[0]
+    }
+
+    fn looping_source() -> AudioSource {
+        AudioSource::new(&LOOP_SAMPLES, AudioVolume::new(LOUDEST), true)
+    }
+
+    fn looping_source_from(loop_start: usize) -> AudioSource {
+        let mut source = looping_source();
+        source.loop_start = Some(loop_start);
+        source
+    }
+
+    #[test]
+    fn looping_source_wraps_across_buffer_boundary() {
+        let mut mixer = AudioMixer::new();
+        mixer.play_sfx(looping_source_from(LOOP_START), SfxPriority::Normal);
+
+        let mut played = 0;
+        mixer.render_frames(4, |left, right| {
+            for (left, right) in left.iter().zip(right) {
+                let position = if played < LOOP_LENGTH {
+                    played
+                } else {
+                    LOOP_START + (played - LOOP_LENGTH) % (LOOP_LENGTH - LOOP_START)
+                };
+
+                let expected = scaled(LOOP_SAMPLES[position], LOUDEST);
+                assert_eq!(*left, expected, "left sample {played}");
+                assert_eq!(*right, expected, "right sample {played}");
+                played += 1;
+            }
+        });
+
+        let mut mixer = AudioMixer::new();
+        mixer.play_sfx(looping_source_from(LOOP_START), SfxPriority::Normal);
+        assert_eq!(render_checksum(&mut mixer, 4), 0xa496966d);
+    }
+
+    #[test]
+    fn resampled_loop_wraps_across_buffer_boundary() {
+        let mut mixer = AudioMixer::new();
+        let source = looping_source_from(LOOP_START).with_rate(PlaybackRate::from_ratio(3, 2));
+        mixer.play_sfx(source, SfxPriority::Normal);
+
+        assert_eq!(render_checksum(&mut mixer, 6), 0x6c2b51a1);
+    }
+
+    #[test]
+    fn gain_ramp_and_pan_apply_together() {
+        let mut mixer = AudioMixer::new();
+        let source = looping_source().with_pan(Pan::new(-32));
+        let handle = mixer.play_sfx(source, SfxPriority::Normal).unwrap();
+        mixer.fade_in_sfx(&handle, 4);
+
+        // The gain starts from silence, and the pan halves the right side on top of it.
+        mixer.render_frames(1, |left, right| {
+            assert!(left.iter().chain(right).all(|sample| *sample == 0));
+        });
+        mixer.render_frames(1, |left, right| {
+            let position = AUDIO_BUFFER_SIZE;
+            assert_eq!(left[0], scaled(LOOP_SAMPLES[position], LOUDEST / 4));
+            assert_eq!(right[0], scaled(LOOP_SAMPLES[position], LOUDEST / 4 / 2));
+        });
+
+        assert_eq!(render_checksum(&mut mixer, 6), 0x004bd843);
+    }
+
+    #[test]
+    fn adpcm_loop_decodes_consistently() {
+        let mut mixer = AudioMixer::new();
+        mixer.play_sfx(AudioSource::from_asset(&ADPCM_ASSET), SfxPriority::Normal);
+
+        assert_eq!(render_checksum(&mut mixer, 4), 0x39c36b9d);
+    }
+
+    #[test]
+    fn loud_sounds_clip() {
+        static LOUD_SAMPLES: [u8; AUDIO_BUFFER_SIZE] =
+            [i8::MAX.to_ne_bytes()[0]; AUDIO_BUFFER_SIZE];
+
+        let mut mixer = AudioMixer::new();
+        for _ in 0..2 {
+            let source = AudioSource::new(&LOUD_SAMPLES, AudioVolume::new(LOUDEST), false);
+            mixer.play_sfx(source, SfxPriority::Normal);
+        }
+
+        mixer.render_frames(1, |left, right| {
+            let clipped = i8::MAX.to_ne_bytes()[0];
+            assert!(left.iter().chain(right).all(|sample| *sample == clipped));
+        });
+    }
+}
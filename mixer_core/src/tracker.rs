@@ -0,0 +1,456 @@
+use crate::mixer::SAMPLE_RATE;
+
+pub const NUM_CHANNELS: usize = 4;
+const ROWS_PER_PATTERN: usize = 64;
+
+const DEFAULT_SPEED: u8 = 6;
+const DEFAULT_TEMPO: u8 = 125;
+
+// The Amiga's Paula chip plays a sample at this clock rate divided by the note's period.
+// Pre-divided by the mixer's sample rate, in 16.16 fixed point.
+const PERIOD_STEP_NUMERATOR: u32 = ((3_546_895_u64 << 16) / SAMPLE_RATE as u64) as u32;
+
+// Portamento can't slide outside of the three octaves that modules use.
+const MIN_PERIOD: u16 = 113;
+const MAX_PERIOD: u16 = 856;
+
+// Each channel is mixed in at half volume, so that a few loud channels rarely clip.
+const CHANNEL_VOLUME_SHIFT: i16 = 7;
+
+/// Amiga periods for the three octaves a module can use, with no finetune.
+const PERIODS: [u16; 36] = [
+    856, 808, 762, 720, 678, 640, 604, 570, 538, 508, 480, 453, // Octave 1
+    428, 404, 381, 360, 339, 320, 302, 285, 269, 254, 240, 226, // Octave 2
+    214, 202, 190, 180, 170, 160, 151, 143, 135, 127, 120, 113, // Octave 3
+];
+
+/// Each finetune step is an eighth of a semitone. Multipliers for finetunes -8 to 7, in 1.15 fixed point.
+const FINETUNE_MULTIPLIERS: [u32; 16] = [
+    34_716, 34_467, 34_219, 33_973, 33_728, 33_486, 33_245, 33_005, 32_768, 32_532, 32_298, 32_066,
+    31_835, 31_606, 31_379, 31_153,
+];
+
+/// Half a sine wave, used for vibrato.
+const VIBRATO_TABLE: [u8; 32] = [
+    0, 24, 49, 74, 97, 120, 141, 161, 180, 197, 212, 224, 235, 244, 250, 253, 255, 253, 250, 244,
+    235, 224, 212, 197, 180, 161, 141, 120, 97, 74, 49, 24,
+];
+
+/// An instrument's samples, converted from a MOD file by the build script.
+pub struct Instrument {
+    /// Signed 8-bit samples, played back at a rate set by the note's period.
+    pub samples: &'static [u8],
+    pub loop_start: Option<usize>,
+    pub volume: u8,
+    pub finetune: i8,
+}
+
+/// One channel of one row of a pattern.
+#[derive(Clone, Copy)]
+pub struct Cell {
+    /// Index into the period table, plus one. Zero means there is no note.
+    note: u8,
+    /// Instrument number, starting from one. Zero means there is no instrument.
+    instrument: u8,
+    effect: u8,
+    param: u8,
+}
+
+pub struct Song {
+    pub instruments: &'static [Instrument],
+    /// The patterns to play, in order.
+    pub orders: &'static [u8],
+    /// Every row of every pattern, one pattern after the other.
+    pub rows: &'static [[Cell; NUM_CHANNELS]],
+}
+
+/// Plays a song's patterns, rendering the instruments into the mixer's buffer.
+/// The song loops back to the start once the last pattern has played.
+pub struct SongPlayer {
+    song: &'static Song,
+    order: usize,
+    row: usize,
+    tick: u8,
+    speed: u8,
+    tempo: u8,
+    samples_until_tick: usize,
+    // Set by pattern break and position jump effects, to take effect at the end of the row.
+    jump: Option<(usize, usize)>,
+    channels: [Channel; NUM_CHANNELS],
+}
+
+#[derive(Clone, Copy)]
+struct Channel {
+    instrument: Option<&'static Instrument>,
+    note: u8,
+    period: u16,
+    target_period: u16,
+    portamento_speed: u8,
+    volume: u8,
+    effect: u8,
+    param: u8,
+    vibrato_position: u8,
+    vibrato_speed: u8,
+    vibrato_depth: u8,
+    sample_offset: u8,
+    position: usize,
+    // The fractional part of the position, and the per-sample step, are 16.16 fixed point.
+    fraction: u32,
+    step: u32,
+    is_playing: bool,
+}
+
+impl Cell {
+    pub const fn new(note: u8, instrument: u8, effect: u8, param: u8) -> Self {
+        Self {
+            note,
+            instrument,
+            effect,
+            param,
+        }
+    }
+}
+
+impl SongPlayer {
+    pub fn new(song: &'static Song) -> Self {
+        Self {
+            song,
+            order: 0,
+            row: 0,
+            tick: 0,
+            speed: DEFAULT_SPEED,
+            tempo: DEFAULT_TEMPO,
+            samples_until_tick: 0,
+            jump: None,
+            channels: [Channel::new(); NUM_CHANNELS],
+        }
+    }
+
+    /// Mix the next samples of the song into the buffer.
+    pub fn render(&mut self, buffer: &mut [u8]) {
+        let mut offset = 0;
+
+        while offset < buffer.len() {
+            if self.samples_until_tick == 0 {
+                self.process_tick();
+                self.samples_until_tick = self.samples_per_tick();
+            }
+
+            let count = self.samples_until_tick.min(buffer.len() - offset);
+
+            for channel in self.channels.iter_mut() {
+                channel.render(&mut buffer[offset..offset + count]);
+            }
+
+            offset += count;
+            self.samples_until_tick -= count;
+        }
+    }
+
+    fn samples_per_tick(&self) -> usize {
+        // A tempo of 125 gives 50 ticks per second.
+        let sample_rate: usize = SAMPLE_RATE.try_into().unwrap();
+        sample_rate * 5 / (2 * usize::from(self.tempo))
+    }
+
+    fn process_tick(&mut self) {
+        if self.tick == 0 {
+            self.play_row();
+        } else {
+            for channel in self.channels.iter_mut() {
+                channel.update_effect(self.tick);
+            }
+        }
+
+        self.tick += 1;
+
+        if self.tick >= self.speed {
+            self.tick = 0;
+            self.next_row();
+        }
+    }
+
+    fn play_row(&mut self) {
+        let pattern = usize::from(self.song.orders[self.order]);
+        let cells = &self.song.rows[pattern * ROWS_PER_PATTERN + self.row];
+
+        for (channel, cell) in self.channels.iter_mut().zip(cells.iter()) {
+            channel.play_cell(self.song, cell);
+
+            match cell.effect {
+                0xB => self.jump = Some((cell.param.into(), 0)),
+                0xD => {
+                    // The row is stored in decimal.
+                    let row = usize::from(cell.param >> 4) * 10 + usize::from(cell.param & 0x0F);
+                    let order = match self.jump {
+                        Some((order, _)) => order,
+                        None => self.order + 1,
+                    };
+
+                    self.jump = Some((order, row.min(ROWS_PER_PATTERN - 1)));
+                }
+                0xF => match cell.param {
+                    0 => {}
+                    1..=31 => self.speed = cell.param,
+                    _ => self.tempo = cell.param,
+                },
+                _ => {}
+            }
+        }
+    }
+
+    fn next_row(&mut self) {
+        match self.jump.take() {
+            Some((order, row)) => {
+                self.order = order;
+                self.row = row;
+            }
+            None => {
+                self.row += 1;
+
+                if self.row == ROWS_PER_PATTERN {
+                    self.row = 0;
+                    self.order += 1;
+                }
+            }
+        }
+
+        if self.order >= self.song.orders.len() {
+            self.order = 0;
+        }
+    }
+}
+
+impl Channel {
+    const fn new() -> Self {
+        Self {
+            instrument: None,
+            note: 0,
+            period: 0,
+            target_period: 0,
+            portamento_speed: 0,
+            volume: 0,
+            effect: 0,
+            param: 0,
+            vibrato_position: 0,
+            vibrato_speed: 0,
+            vibrato_depth: 0,
+            sample_offset: 0,
+            position: 0,
+            fraction: 0,
+            step: 0,
+            is_playing: false,
+        }
+    }
+
+    fn play_cell(&mut self, song: &'static Song, cell: &Cell) {
+        self.effect = cell.effect;
+        self.param = cell.param;
+
+        if cell.instrument != 0 {
+            self.instrument = song.instruments.get(usize::from(cell.instrument) - 1);
+            self.volume = self.instrument.map_or(0, |i| i.volume);
+        }
+
+        let is_portamento = cell.effect == 0x3 || cell.effect == 0x5;
+
+        if cell.note != 0 {
+            let period = self.note_period(cell.note, 0);
+
+            if is_portamento {
+                self.target_period = period;
+            } else {
+                self.note = cell.note;
+                self.period = period;
+                self.position = 0;
+                self.fraction = 0;
+                self.vibrato_position = 0;
+                self.is_playing = self.instrument.is_some();
+            }
+        }
+
+        match (cell.effect, cell.param >> 4, cell.param & 0x0F) {
+            (0x3, _, _) if cell.param != 0 => self.portamento_speed = cell.param,
+            (0x4, speed, depth) => {
+                if speed != 0 {
+                    self.vibrato_speed = speed;
+                }
+                if depth != 0 {
+                    self.vibrato_depth = depth;
+                }
+            }
+            (0x9, _, _) => {
+                if cell.param != 0 {
+                    self.sample_offset = cell.param;
+                }
+                if cell.note != 0 {
+                    self.position = usize::from(self.sample_offset) * 256;
+                }
+            }
+            (0xC, _, _) => self.volume = cell.param.min(64),
+            (0xE, 0x1, amount) => self.slide_period(-i32::from(amount)),
+            (0xE, 0x2, amount) => self.slide_period(i32::from(amount)),
+            (0xE, 0xA, amount) => self.slide_volume(amount as i8),
+            (0xE, 0xB, amount) => self.slide_volume(-(amount as i8)),
+            (0xE, 0xC, 0) => self.volume = 0,
+            _ => {}
+        }
+
+        self.set_step(self.period);
+    }
+
+    fn update_effect(&mut self, tick: u8) {
+        let (x, y) = (self.param >> 4, self.param & 0x0F);
+
+        // Arpeggio and vibrato only change the pitch that is heard, not the channel's period.
+        let output_period = match self.effect {
+            0x0 if self.param != 0 => {
+                let semitones = [0, x, y][usize::from(tick % 3)];
+                Some(self.note_period(self.note, semitones))
+            }
+            0x1 => {
+                self.slide_period(-i32::from(self.param));
+                None
+            }
+            0x2 => {
+                self.slide_period(i32::from(self.param));
+                None
+            }
+            0x3 => {
+                self.tone_portamento();
+                None
+            }
+            0x4 => Some(self.vibrato()),
+            0x5 => {
+                self.tone_portamento();
+                self.volume_slide(x, y);
+                None
+            }
+            0x6 => {
+                self.volume_slide(x, y);
+                Some(self.vibrato())
+            }
+            0xA => {
+                self.volume_slide(x, y);
+                None
+            }
+            0xE if x == 0xC && y == tick => {
+                self.volume = 0;
+                None
+            }
+            _ => None,
+        };
+
+        self.set_step(output_period.unwrap_or(self.period));
+    }
+
+    fn note_period(&self, note: u8, semitones: u8) -> u16 {
+        if note == 0 {
+            return self.period;
+        }
+
+        let index = (usize::from(note) - 1 + usize::from(semitones)).min(PERIODS.len() - 1);
+        let finetune = self.instrument.map_or(0, |i| i.finetune);
+        let multiplier = FINETUNE_MULTIPLIERS[usize::try_from(i32::from(finetune) + 8).unwrap()];
+
+        ((u32::from(PERIODS[index]) * multiplier) >> 15)
+            .try_into()
+            .unwrap()
+    }
+
+    fn slide_period(&mut self, amount: i32) {
+        if self.period == 0 {
+            return;
+        }
+
+        let period = (i32::from(self.period) + amount).clamp(MIN_PERIOD.into(), MAX_PERIOD.into());
+        self.period = period.try_into().unwrap();
+    }
+
+    fn tone_portamento(&mut self) {
+        let speed = u16::from(self.portamento_speed);
+
+        if self.target_period == 0 || self.period == 0 {
+            return;
+        }
+
+        if self.period < self.target_period {
+            self.period = (self.period + speed).min(self.target_period);
+        } else {
+            self.period = self.period.saturating_sub(speed).max(self.target_period);
+        }
+    }
+
+    fn vibrato(&mut self) -> u16 {
+        let position = self.vibrato_position & 0x3F;
+        let delta = (u16::from(VIBRATO_TABLE[usize::from(position & 0x1F)])
+            * u16::from(self.vibrato_depth))
+            >> 7;
+
+        self.vibrato_position = self.vibrato_position.wrapping_add(self.vibrato_speed);
+
+        // The second half of the wave is the first half, negated.
+        if position < 32 {
+            self.period.saturating_add(delta)
+        } else {
+            self.period.saturating_sub(delta)
+        }
+    }
+
+    fn volume_slide(&mut self, up: u8, down: u8) {
+        if up != 0 {
+            self.slide_volume(up as i8);
+        } else {
+            self.slide_volume(-(down as i8));
+        }
+    }
+
+    fn slide_volume(&mut self, amount: i8) {
+        self.volume = (self.volume as i8 + amount).clamp(0, 64) as u8;
+    }
+
+    fn set_step(&mut self, period: u16) {
+        self.step = match period {
+            0 => 0,
+            _ => PERIOD_STEP_NUMERATOR / u32::from(period),
+        };
+    }
+
+    fn render(&mut self, buffer: &mut [u8]) {
+        let instrument = match self.instrument {
+            Some(instrument) if self.is_playing && self.step != 0 => instrument,
+            _ => return,
+        };
+
+        let samples = instrument.samples;
+        let volume: i16 = self.volume.into();
+
+        for output in buffer.iter_mut() {
+            if self.position >= samples.len() {
+                match instrument.loop_start {
+                    Some(loop_start) => {
+                        let loop_length = samples.len() - loop_start;
+                        self.position = loop_start + (self.position - samples.len()) % loop_length;
+                    }
+                    None => {
+                        self.is_playing = false;
+                        return;
+                    }
+                }
+            }
+
+            // The buffers are represented as u8, but the data is actually i8.
+            let sample: i16 = i8::from_ne_bytes([samples[self.position]]).into();
+            let buffered: i16 = i8::from_ne_bytes([*output]).into();
+
+            let mixed = (buffered + ((sample * volume) >> CHANNEL_VOLUME_SHIFT))
+                .clamp(i8::MIN.into(), i8::MAX.into());
+            let mixed: i8 = mixed.try_into().unwrap();
+            *output = mixed.to_ne_bytes()[0];
+
+            let fraction = self.fraction + self.step;
+            self.position += usize::try_from(fraction >> 16).unwrap();
+            self.fraction = fraction & 0xFFFF;
+        }
+    }
+}
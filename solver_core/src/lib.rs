@@ -0,0 +1,243 @@
+//! Searches ahead for forced wins. Positions are plain bitboards with no link to the game's
+//! board, so the same searches can be run by the build script to check the puzzles.
+#![no_std]
+
+pub const NUM_COLUMNS: usize = 7;
+pub const NUM_ROWS: usize = 6;
+const NUM_SLOTS: usize = NUM_COLUMNS * NUM_ROWS;
+
+// Each column gets an extra empty bit above its top row, so that lines can't wrap between columns.
+const COLUMN_HEIGHT: usize = NUM_ROWS + 1;
+
+// Central columns take part in more lines, so searching them first finds wins sooner.
+const SEARCH_ORDER: [usize; NUM_COLUMNS] = [3, 2, 4, 1, 5, 0, 6];
+
+/// How many positions a single search may visit. A search runs in the middle of a frame,
+/// so this keeps it well short of the next vblank, when the audio buffer needs refilling.
+pub const MAX_SEARCH_NODES: u32 = 400;
+
+/// A compact copy of the board used for searching ahead.
+/// Every slot is a bit, column by column, so moves and win checks are just a few integer operations.
+#[derive(Clone, Copy)]
+pub struct Position {
+    /// Tokens belonging to the player whose turn it is.
+    current: u64,
+    /// Every token on the board.
+    mask: u64,
+}
+
+/// Limits how many positions a search can visit, so it can't hold up the frame it runs in.
+/// Once it runs out, a search gives up as though it found nothing, so a forced win is never
+/// claimed without being proven. Searches that share a budget share the cap.
+pub struct SearchBudget {
+    nodes_left: u32,
+}
+
+impl SearchBudget {
+    /// Enough for a single search.
+    pub fn per_search() -> Self {
+        Self {
+            nodes_left: MAX_SEARCH_NODES,
+        }
+    }
+
+    pub fn is_exhausted(&self) -> bool {
+        self.nodes_left == 0
+    }
+
+    /// Use up one position, returning false if there were none left.
+    fn spend(&mut self) -> bool {
+        if self.nodes_left == 0 {
+            return false;
+        }
+
+        self.nodes_left -= 1;
+        true
+    }
+}
+
+impl Position {
+    pub fn empty() -> Self {
+        Self {
+            current: 0,
+            mask: 0,
+        }
+    }
+
+    /// Build a position from what is in each slot, by column and row from the bottom:
+    /// `Some(true)` for the player to move, `Some(false)` for the opponent, `None` if empty.
+    pub fn from_slots(slot: impl Fn(usize, usize) -> Option<bool>) -> Self {
+        let mut current = 0;
+        let mut mask = 0;
+
+        for column in 0..NUM_COLUMNS {
+            for row in 0..NUM_ROWS {
+                if let Some(is_current) = slot(column, row) {
+                    let bit = bottom_mask(column) << row;
+                    mask |= bit;
+
+                    if is_current {
+                        current |= bit;
+                    }
+                }
+            }
+        }
+
+        Self { current, mask }
+    }
+
+    pub fn can_play(&self, column: usize) -> bool {
+        self.mask & top_mask(column) == 0
+    }
+
+    /// The position after the player to move drops a token in the column.
+    /// The other player is to move in the new position.
+    pub fn play(&self, column: usize) -> Self {
+        Self {
+            current: self.current ^ self.mask,
+            mask: self.mask | (self.mask + bottom_mask(column)),
+        }
+    }
+
+    pub fn is_winning_move(&self, column: usize) -> bool {
+        let new_token = (self.mask + bottom_mask(column)) & column_mask(column);
+        self.can_play(column) && has_line(self.current | new_token)
+    }
+
+    /// The row a token dropped in this column would land in.
+    pub fn next_free_row(&self, column: usize) -> usize {
+        let column_tokens = (self.mask & column_mask(column)) >> (column * COLUMN_HEIGHT);
+        column_tokens.count_ones() as usize
+    }
+
+    fn is_full(&self) -> bool {
+        self.mask.count_ones() as usize == NUM_SLOTS
+    }
+
+    fn winning_moves(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..NUM_COLUMNS).filter(|column| self.is_winning_move(*column))
+    }
+
+    /// The same board, but with the other player to move.
+    fn swapped(&self) -> Self {
+        Self {
+            current: self.current ^ self.mask,
+            mask: self.mask,
+        }
+    }
+
+    fn playable_columns(&self) -> impl Iterator<Item = usize> + '_ {
+        SEARCH_ORDER
+            .into_iter()
+            .filter(|column| self.can_play(*column))
+    }
+}
+
+/// Whether the player to move can win within `moves` of their own moves, however the opponent replies.
+pub fn can_force_win(position: &Position, moves: u8, budget: &mut SearchBudget) -> bool {
+    if moves == 0 || !budget.spend() {
+        return false;
+    }
+
+    if position.winning_moves().next().is_some() {
+        return true;
+    }
+
+    if moves == 1 {
+        return false;
+    }
+
+    // If the opponent is threatening to win, then the only move worth trying is to block them.
+    // With two threats there is no way to block both.
+    let opponent = position.swapped();
+    let mut threats = opponent.winning_moves();
+
+    match (threats.next(), threats.next()) {
+        (Some(column), None) => opponent_is_lost(&position.play(column), moves - 1, budget),
+        (Some(_), Some(_)) => false,
+        (None, _) => position
+            .playable_columns()
+            .any(|column| opponent_is_lost(&position.play(column), moves - 1, budget)),
+    }
+}
+
+/// Whether every reply available to the player to move still leaves the opponent
+/// with a forced win within `moves`.
+pub fn opponent_is_lost(position: &Position, moves: u8, budget: &mut SearchBudget) -> bool {
+    if !budget.spend() || position.is_full() || position.winning_moves().next().is_some() {
+        return false;
+    }
+
+    position
+        .playable_columns()
+        .all(|column| can_force_win(&position.play(column), moves, budget))
+}
+
+/// A column that wins within `moves`, if the player to move has a forced win.
+pub fn find_winning_column(
+    position: &Position,
+    moves: u8,
+    budget: &mut SearchBudget,
+) -> Option<usize> {
+    if let Some(column) = position.winning_moves().next() {
+        return Some(column);
+    }
+
+    if moves <= 1 {
+        return None;
+    }
+
+    position
+        .playable_columns()
+        .find(|column| opponent_is_lost(&position.play(*column), moves - 1, budget))
+}
+
+/// A column after which the opponent can't force a win within `moves`, if there is one.
+/// A column isn't counted as safe if the budget ran out while checking it.
+pub fn find_safe_column(
+    position: &Position,
+    moves: u8,
+    budget: &mut SearchBudget,
+) -> Option<usize> {
+    position.playable_columns().find(|column| {
+        !can_force_win(&position.play(*column), moves, budget) && !budget.is_exhausted()
+    })
+}
+
+/// How many moves the opponent would need to force a win after the player to move plays
+/// in `column`, searching up to `max_moves` deep. Higher is better for the player to move,
+/// and `max_moves + 1` means there is no forced loss in sight, or none was found in the budget.
+pub fn moves_until_forced_loss(
+    position: &Position,
+    column: usize,
+    max_moves: u8,
+    budget: &mut SearchBudget,
+) -> u8 {
+    let position = position.play(column);
+
+    (1..=max_moves)
+        .find(|moves| can_force_win(&position, *moves, budget))
+        .unwrap_or(max_moves + 1)
+}
+
+fn bottom_mask(column: usize) -> u64 {
+    1 << (column * COLUMN_HEIGHT)
+}
+
+fn top_mask(column: usize) -> u64 {
+    bottom_mask(column) << (NUM_ROWS - 1)
+}
+
+fn column_mask(column: usize) -> u64 {
+    ((1 << NUM_ROWS) - 1) << (column * COLUMN_HEIGHT)
+}
+
+/// Check for four in a row in any direction: vertical, horizontal and both diagonals.
+fn has_line(tokens: u64) -> bool {
+    [1, COLUMN_HEIGHT, COLUMN_HEIGHT - 1, COLUMN_HEIGHT + 1]
+        .into_iter()
+        .any(|shift| {
+            let pairs = tokens & (tokens >> shift);
+            pairs & (pairs >> (2 * shift)) != 0
+        })
+}
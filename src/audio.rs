@@ -1,4 +1,7 @@
 pub mod assets;
 pub mod drum_roll;
+pub mod events;
 pub mod mixer;
 pub mod noise;
+pub mod psg;
+pub mod tracker;
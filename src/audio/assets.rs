@@ -1,2 +1,4 @@
-pub static BACKGROUND_MUSIC: &[u8] = include_bytes!("bgm.raw");
-pub static BOUNCE_NOISE: &[u8] = include_bytes!("bounce.raw");
+pub use mixer_core::assets::{SampleFormat, SoundAsset};
+
+// One static per file in assets/audio, named after the file.
+include!(concat!(env!("OUT_DIR"), "/sound_data.rs"));
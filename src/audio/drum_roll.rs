@@ -1,10 +1,14 @@
-use super::noise::play_impact_noise;
+use super::{
+    assets::BOUNCE_NOISE,
+    mixer::{self, AudioSource, PlaybackRate, SfxPriority},
+};
 
 #[derive(Clone)]
 pub struct DrumRoll {
     delay: u32,
     counter: u32,
     next_delay: u32,
+    rate: PlaybackRate,
 }
 
 impl DrumRoll {
@@ -13,6 +17,7 @@ impl DrumRoll {
             delay,
             next_delay: delay,
             counter: 0,
+            rate: PlaybackRate::NORMAL,
         }
     }
 
@@ -20,10 +25,16 @@ impl DrumRoll {
         self.next_delay = delay;
     }
 
+    /// Change the pitch of the following drum hits.
+    pub fn set_rate(&mut self, rate: PlaybackRate) {
+        self.rate = rate;
+    }
+
     pub fn update(&mut self) {
         self.counter += 1;
         if self.counter % self.delay == 0 {
-            play_impact_noise();
+            let sound = AudioSource::from_asset(&BOUNCE_NOISE).with_rate(self.rate);
+            mixer::play_sfx(sound, SfxPriority::Low);
             self.counter = 0;
             self.delay = self.next_delay;
         }
@@ -0,0 +1,62 @@
+use super::{
+    assets::{SoundAsset, WIN_JINGLE},
+    mixer::{self, AudioSource, Pan, SfxPriority},
+    noise::{
+        CANCEL_NOISE, DRAW_NOISE, LOSE_NOISE, MENU_CONFIRM_NOISE, SPINNER_STOP_NOISE, THREAT_NOISE,
+    },
+    psg::{play_psg_panned, PsgEffect},
+};
+
+/// Something happening in the game that should be heard.
+#[derive(Clone, Copy)]
+pub enum SoundEvent {
+    Win,
+    Lose,
+    Draw,
+    ThreatCreated,
+    Confirm,
+    Cancel,
+    SpinnerStop,
+}
+
+/// What plays when an event fires.
+pub enum EventSound {
+    /// A sample, played through the mixer as a sound effect.
+    Sample(&'static SoundAsset, SfxPriority),
+    /// A sample that ducks the music until it has finished.
+    Stinger(&'static SoundAsset),
+    /// A sequence on the PSG channels.
+    Psg(&'static PsgEffect),
+}
+
+/// The sound for each event. Events can be retuned here without touching the screens.
+pub fn event_sound(event: SoundEvent) -> EventSound {
+    match event {
+        SoundEvent::Win => EventSound::Stinger(&WIN_JINGLE),
+        SoundEvent::Lose => EventSound::Psg(&LOSE_NOISE),
+        SoundEvent::Draw => EventSound::Psg(&DRAW_NOISE),
+        SoundEvent::ThreatCreated => EventSound::Psg(&THREAT_NOISE),
+        SoundEvent::Confirm => EventSound::Psg(&MENU_CONFIRM_NOISE),
+        SoundEvent::Cancel => EventSound::Psg(&CANCEL_NOISE),
+        SoundEvent::SpinnerStop => EventSound::Psg(&SPINNER_STOP_NOISE),
+    }
+}
+
+impl SoundEvent {
+    pub fn play(&self) {
+        self.play_panned(Pan::CENTRE);
+    }
+
+    /// Play the event's sound towards the side of the screen that caused it.
+    pub fn play_panned(&self, pan: Pan) {
+        match event_sound(*self) {
+            EventSound::Sample(asset, priority) => {
+                mixer::play_sfx(AudioSource::from_asset(asset).with_pan(pan), priority);
+            }
+            EventSound::Stinger(asset) => {
+                mixer::play_stinger(AudioSource::from_asset(asset).with_pan(pan));
+            }
+            EventSound::Psg(effect) => play_psg_panned(effect, pan),
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use core::ffi::c_void;
+use gba::prelude::*;
+
+use super::AUDIO_BUFFER_SIZE;
+
+// We feed samples to the FIFO queues during vblank.
+// Vblank occurs every 280896 CPU cycles.
+// So the sample rate needs to be a multiple of 280896, we will use 18157 Hz.
+// These values are pre-calculated based on the sample rate
+//  For more details: https://deku.gbadev.org/program/sound1.html
+const AUDIO_TIMER_VALUE: u16 = 64612;
+
+/// The buffers that the DMA units stream to the Direct Sound FIFOs, one frame of samples at a time.
+pub struct AudioOutput {
+    // FIFO A plays the left speaker and FIFO B plays the right.
+    left_buffers: AudioDoubleBuffer,
+    right_buffers: AudioDoubleBuffer,
+    playing_second_buffer: bool,
+}
+
+struct AudioDoubleBuffer([u8; AUDIO_BUFFER_SIZE * 2]);
+
+impl AudioDoubleBuffer {
+    fn first_buffer_mut(&mut self) -> &mut [u8] {
+        self.0.split_at_mut(AUDIO_BUFFER_SIZE).0
+    }
+
+    fn second_buffer_mut(&mut self) -> &mut [u8] {
+        self.0.split_at_mut(AUDIO_BUFFER_SIZE).1
+    }
+}
+
+impl AudioOutput {
+    pub const fn new() -> Self {
+        Self {
+            left_buffers: AudioDoubleBuffer([0; AUDIO_BUFFER_SIZE * 2]),
+            right_buffers: AudioDoubleBuffer([0; AUDIO_BUFFER_SIZE * 2]),
+            playing_second_buffer: false,
+        }
+    }
+
+    pub fn init(&mut self) {
+        self.init_dma();
+        self.init_timer();
+        self.init_sound_channel();
+    }
+
+    /// Start playing the buffers that were just filled, and clear the others to be filled next.
+    pub fn swap_buffers(&mut self) {
+        if self.playing_second_buffer {
+            // Reset the DMA controller to point back at the first buffer.
+            self.init_dma();
+        }
+
+        self.playing_second_buffer = !self.playing_second_buffer;
+
+        let (left, right) = self.get_back_buffers();
+        left.fill(0);
+        right.fill(0);
+    }
+
+    /// The left and right buffers that aren't currently being played.
+    pub fn get_back_buffers(&mut self) -> (&mut [u8], &mut [u8]) {
+        if self.playing_second_buffer {
+            (
+                self.left_buffers.first_buffer_mut(),
+                self.right_buffers.first_buffer_mut(),
+            )
+        } else {
+            (
+                self.left_buffers.second_buffer_mut(),
+                self.right_buffers.second_buffer_mut(),
+            )
+        }
+    }
+
+    fn init_dma(&mut self) {
+        // Setup the DMA unit to copy samples into
+        let dma_control = DmaControl::new()
+            .with_src_addr_control(SrcAddrControl::Increment)
+            .with_dest_addr_control(DestAddrControl::Increment)
+            .with_start_time(DmaStartTime::Special)
+            .with_transfer_32bit(false)
+            .with_enabled(true)
+            .with_repeat(true);
+
+        unsafe {
+            let left_addr = &self.left_buffers.0 as *const [u8];
+            DMA1_CONTROL.write(DmaControl::new().with_enabled(false));
+            DMA1_SRC.write(left_addr as *const c_void);
+            DMA1_DEST.write(FIFO_A.as_mut_ptr() as *mut c_void);
+            DMA1_CONTROL.write(dma_control);
+
+            let right_addr = &self.right_buffers.0 as *const [u8];
+            DMA2_CONTROL.write(DmaControl::new().with_enabled(false));
+            DMA2_SRC.write(right_addr as *const c_void);
+            DMA2_DEST.write(FIFO_B.as_mut_ptr() as *mut c_void);
+            DMA2_CONTROL.write(dma_control);
+        }
+    }
+
+    fn init_timer(&mut self) {
+        TIMER0_RELOAD.write(AUDIO_TIMER_VALUE);
+        TIMER0_CONTROL.write(TimerControl::new().with_enabled(true));
+    }
+
+    fn init_sound_channel(&mut self) {
+        // Initialise direct sound channel A for the left speaker, and B for the right.
+        let sound_mix = SoundMix::new()
+            .with_sound_a_left(true)
+            .with_sound_a_right(false)
+            .with_sound_a_timer(false) // Use timer 0 for the sampling rate.
+            .with_sound_a_full(true)
+            .with_sound_a_reset(true)
+            .with_sound_b_left(false)
+            .with_sound_b_right(true)
+            .with_sound_b_timer(false)
+            .with_sound_b_full(true)
+            .with_sound_b_reset(true);
+
+        SOUND_MIX.write(sound_mix);
+    }
+}
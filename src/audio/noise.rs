@@ -1,20 +1,270 @@
 use gba::{
-    prelude::{
-        LEFT_RIGHT_VOLUME, NOISE_FREQ, NOISE_LEN_ENV, TONE1_FREQUENCY, TONE1_PATTERN, TONE1_SWEEP,
-    },
+    prelude::LEFT_RIGHT_VOLUME,
     sound::{
-        LeftRightVolume, NoiseFrequency, NoiseLenEnvelope, SweepControl, ToneFrequency, TonePattern,
+        LeftRightVolume, NoiseFrequency, NoiseLenEnvelope, SweepControl, ToneFrequency,
+        TonePattern, WaveFrequency, WaveLenVolume,
     },
 };
 
+use super::psg::{play_psg, PsgCommand, PsgEffect, PsgStep, Waveform};
+
+// The PSG master volume has 8 levels, with 0 here meaning the channels are switched off.
+pub const MAX_PSG_VOLUME: u16 = 8;
+
+/// A short "impact" noise.
+pub static IMPACT_NOISE: PsgEffect = PsgEffect {
+    steps: &[PsgStep {
+        frame: 0,
+        command: PsgCommand::Noise {
+            envelope: NoiseLenEnvelope::new()
+                .with_step_increasing(false)
+                .with_volume(0b0111)
+                .with_length(1)
+                .with_step_time(0b001),
+            frequency: NoiseFrequency::new()
+                .with_enabled(true)
+                .with_stop_when_expired(true)
+                .with_r(0b011) // Clock divider
+                .with_s(0b0000), // Pre-step frequency
+        },
+    }],
+};
+
+pub static MENU_MOVE_NOISE: PsgEffect = PsgEffect {
+    steps: &[PsgStep {
+        frame: 0,
+        command: PsgCommand::Tone1 {
+            sweep: SweepControl::new()
+                .with_sweep_increasing(false)
+                .with_sweep_num(0b100)
+                .with_sweep_time(0b010),
+            pattern: TonePattern::new()
+                .with_step_increasing(false)
+                .with_volume(0b1111)
+                .with_step_time(0b001)
+                .with_length(0x0001)
+                .with_duty(0b10),
+            frequency: ToneFrequency::new()
+                .with_enabled(true)
+                .with_stop_when_expired(true)
+                .with_frequency(0b111111111),
+        },
+    }],
+};
+
+/// A soft, rounded waveform for the wave channel.
+pub static TRIANGLE_WAVE: Waveform = Waveform([
+    0x01, 0x23, 0x45, 0x67, 0x89, 0xAB, 0xCD, 0xEF, 0xFE, 0xDC, 0xBA, 0x98, 0x76, 0x54, 0x32, 0x10,
+]);
+
+/// Two rising notes on the wave channel, with a quiet square wave an octave above the second.
+pub static MENU_CONFIRM_NOISE: PsgEffect = PsgEffect {
+    steps: &[
+        PsgStep {
+            frame: 0,
+            command: PsgCommand::Wave {
+                waveform: &TRIANGLE_WAVE,
+                volume: WaveLenVolume::new().with_volume(0b10).with_length(0xE0),
+                frequency: WaveFrequency::new()
+                    .with_enabled(true)
+                    .with_stop_when_expired(true)
+                    .with_length(1974), // Around 880Hz
+            },
+        },
+        PsgStep {
+            frame: 5,
+            command: PsgCommand::Wave {
+                waveform: &TRIANGLE_WAVE,
+                volume: WaveLenVolume::new().with_volume(0b10).with_length(0xC0),
+                frequency: WaveFrequency::new()
+                    .with_enabled(true)
+                    .with_stop_when_expired(true)
+                    .with_length(1998), // Around 1320Hz
+            },
+        },
+        PsgStep {
+            frame: 8,
+            command: PsgCommand::Tone2 {
+                pattern: TonePattern::new()
+                    .with_step_increasing(false)
+                    .with_volume(0b0100)
+                    .with_step_time(0b001)
+                    .with_duty(0b10),
+                frequency: ToneFrequency::new().with_enabled(true).with_frequency(1998),
+            },
+        },
+    ],
+};
+
+/// A note on the second tone channel, which fades out at the given rate.
+const fn tone2_note(frequency: u16, volume: u16, step_time: u16) -> PsgCommand {
+    PsgCommand::Tone2 {
+        pattern: TonePattern::new()
+            .with_step_increasing(false)
+            .with_volume(volume)
+            .with_step_time(step_time)
+            .with_duty(0b10),
+        frequency: ToneFrequency::new()
+            .with_enabled(true)
+            .with_frequency(frequency),
+    }
+}
+
+/// Four falling notes, ending on a long one.
+pub static LOSE_NOISE: PsgEffect = PsgEffect {
+    steps: &[
+        PsgStep {
+            frame: 0,
+            command: tone2_note(1714, 0b1010, 0b000), // G4
+        },
+        PsgStep {
+            frame: 10,
+            command: tone2_note(1651, 0b1010, 0b000), // E4
+        },
+        PsgStep {
+            frame: 20,
+            command: tone2_note(1548, 0b1010, 0b000), // C4
+        },
+        PsgStep {
+            frame: 30,
+            command: tone2_note(1517, 0b1010, 0b101), // B3
+        },
+    ],
+};
+
+/// The same note twice on the wave channel, neither happy nor sad.
+pub static DRAW_NOISE: PsgEffect = PsgEffect {
+    steps: &[
+        PsgStep {
+            frame: 0,
+            command: PsgCommand::Wave {
+                waveform: &TRIANGLE_WAVE,
+                volume: WaveLenVolume::new().with_volume(0b01).with_length(0xF0),
+                frequency: WaveFrequency::new()
+                    .with_enabled(true)
+                    .with_stop_when_expired(true)
+                    .with_length(1798), // Around 262Hz
+            },
+        },
+        PsgStep {
+            frame: 14,
+            command: PsgCommand::Wave {
+                waveform: &TRIANGLE_WAVE,
+                volume: WaveLenVolume::new().with_volume(0b01).with_length(0xC0),
+                frequency: WaveFrequency::new()
+                    .with_enabled(true)
+                    .with_stop_when_expired(true)
+                    .with_length(1798),
+            },
+        },
+    ],
+};
+
+const THREAT_SWEEP: SweepControl = SweepControl::new()
+    .with_sweep_increasing(true)
+    .with_sweep_num(0b011)
+    .with_sweep_time(0b001);
+
+const THREAT_PATTERN: TonePattern = TonePattern::new()
+    .with_step_increasing(false)
+    .with_volume(0b1100)
+    .with_step_time(0b001)
+    .with_length(0x30)
+    .with_duty(0b01);
+
+const THREAT_FREQUENCY: ToneFrequency = ToneFrequency::new()
+    .with_enabled(true)
+    .with_stop_when_expired(true)
+    .with_frequency(1750); // Around 440Hz
+
+/// Two quick rising blips, warning that a player can win on their next move.
+pub static THREAT_NOISE: PsgEffect = PsgEffect {
+    steps: &[
+        PsgStep {
+            frame: 0,
+            command: PsgCommand::Tone1 {
+                sweep: THREAT_SWEEP,
+                pattern: THREAT_PATTERN,
+                frequency: THREAT_FREQUENCY,
+            },
+        },
+        PsgStep {
+            frame: 8,
+            command: PsgCommand::Tone1 {
+                sweep: THREAT_SWEEP,
+                pattern: THREAT_PATTERN,
+                frequency: THREAT_FREQUENCY,
+            },
+        },
+    ],
+};
+
+/// A short falling tone for backing out of a menu.
+pub static CANCEL_NOISE: PsgEffect = PsgEffect {
+    steps: &[PsgStep {
+        frame: 0,
+        command: PsgCommand::Tone1 {
+            sweep: SweepControl::new()
+                .with_sweep_increasing(false)
+                .with_sweep_num(0b010)
+                .with_sweep_time(0b010),
+            pattern: TonePattern::new()
+                .with_step_increasing(false)
+                .with_volume(0b1100)
+                .with_step_time(0b001)
+                .with_length(0x20)
+                .with_duty(0b10),
+            frequency: ToneFrequency::new()
+                .with_enabled(true)
+                .with_stop_when_expired(true)
+                .with_frequency(1600), // Around 290Hz
+        },
+    }],
+};
+
+/// A thump as the spinner comes to rest, followed by a bell for the chosen player.
+pub static SPINNER_STOP_NOISE: PsgEffect = PsgEffect {
+    steps: &[
+        PsgStep {
+            frame: 0,
+            command: PsgCommand::Noise {
+                envelope: NoiseLenEnvelope::new()
+                    .with_step_increasing(false)
+                    .with_volume(0b1100)
+                    .with_step_time(0b010),
+                frequency: NoiseFrequency::new()
+                    .with_enabled(true)
+                    .with_r(0b010)
+                    .with_s(0b0011),
+            },
+        },
+        PsgStep {
+            frame: 3,
+            command: tone2_note(1899, 0b1010, 0b011), // Around 880Hz
+        },
+    ],
+};
+
 pub fn enable_noise() {
+    set_psg_volume(MAX_PSG_VOLUME);
+}
+
+/// Set the volume of all the PSG channels, from 0 for silent up to MAX_PSG_VOLUME.
+pub fn set_psg_volume(volume: u16) {
+    let enabled = volume > 0;
+    let level = volume.min(MAX_PSG_VOLUME).saturating_sub(1);
+
     let vol = LeftRightVolume::new()
-        .with_left_volume(u16::MAX)
-        .with_right_volume(u16::MAX)
-        .with_noise_left(true)
-        .with_noise_right(true)
-        .with_tone1_left(true)
-        .with_tone1_right(true);
+        .with_left_volume(level)
+        .with_right_volume(level)
+        .with_noise_left(enabled)
+        .with_noise_right(enabled)
+        .with_tone1_left(enabled)
+        .with_tone1_right(enabled)
+        .with_tone2_left(enabled)
+        .with_tone2_right(enabled)
+        .with_wave_left(enabled)
+        .with_wave_right(enabled);
 
     LEFT_RIGHT_VOLUME.write(vol);
 }
@@ -22,41 +272,9 @@ pub fn enable_noise() {
 /// Plays a short "impact" noise.
 /// enable_noise must be called in order for any noise to be heard.
 pub fn play_impact_noise() {
-    let freq = NoiseFrequency::new()
-        .with_enabled(true)
-        .with_stop_when_expired(true)
-        .with_r(0b011) // Clock divider
-        .with_s(0b0000); // Pre-step frequency
-
-    let env = NoiseLenEnvelope::new()
-        .with_step_increasing(false)
-        .with_volume(0b0111)
-        .with_length(1)
-        .with_step_time(0b001);
-
-    NOISE_LEN_ENV.write(env);
-    NOISE_FREQ.write(freq);
+    play_psg(&IMPACT_NOISE);
 }
 
 pub fn play_menu_move_noise() {
-    let freq = ToneFrequency::new()
-        .with_enabled(true)
-        .with_stop_when_expired(true)
-        .with_frequency(0b111111111);
-
-    let sweep = SweepControl::new()
-        .with_sweep_increasing(false)
-        .with_sweep_num(0b100)
-        .with_sweep_time(0b010);
-
-    let env = TonePattern::new()
-        .with_step_increasing(false)
-        .with_volume(0b1111)
-        .with_step_time(0b001)
-        .with_length(0x0001)
-        .with_duty(0b10);
-
-    TONE1_SWEEP.write(sweep);
-    TONE1_PATTERN.write(env);
-    TONE1_FREQUENCY.write(freq);
+    play_psg(&MENU_MOVE_NOISE);
 }
@@ -0,0 +1,292 @@
+use gba::{
+    prelude::{
+        LEFT_RIGHT_VOLUME, NOISE_FREQ, NOISE_LEN_ENV, TONE1_FREQUENCY, TONE1_PATTERN, TONE1_SWEEP,
+        TONE2_FREQUENCY, TONE2_PATTERN, WAVE_BANK, WAVE_FREQ, WAVE_LEN_VOLUME, WAVE_RAM,
+    },
+    sound::{
+        NoiseFrequency, NoiseLenEnvelope, SweepControl, ToneFrequency, TonePattern, WaveBank,
+        WaveFrequency, WaveLenVolume,
+    },
+};
+
+use super::mixer::Pan;
+
+static mut SEQUENCER: PsgSequencer = PsgSequencer {
+    effects: [None, None, None, None],
+};
+
+// Every effect uses at least one of the four channels, so there can't be more playing at once.
+const MAX_EFFECTS: usize = 4;
+
+/// The legacy sound channels, which generate their sound in hardware.
+#[derive(Clone, Copy)]
+pub enum PsgChannel {
+    Tone1,
+    Tone2,
+    Wave,
+    Noise,
+}
+
+/// 32 4-bit samples for the wave channel, two to a byte with the first in the high bits.
+pub struct Waveform(pub [u8; 16]);
+
+/// Register settings for one of the channels. The frequency is written last, since that is what
+/// (re)starts the sound.
+pub enum PsgCommand {
+    Tone1 {
+        sweep: SweepControl,
+        pattern: TonePattern,
+        frequency: ToneFrequency,
+    },
+    Tone2 {
+        pattern: TonePattern,
+        frequency: ToneFrequency,
+    },
+    Wave {
+        waveform: &'static Waveform,
+        volume: WaveLenVolume,
+        frequency: WaveFrequency,
+    },
+    Noise {
+        envelope: NoiseLenEnvelope,
+        frequency: NoiseFrequency,
+    },
+    /// Cut a channel off before its length runs out.
+    Stop(PsgChannel),
+}
+
+pub struct PsgStep {
+    /// Frames after the start of the effect, at which the command runs.
+    pub frame: u16,
+    pub command: PsgCommand,
+}
+
+/// A sound effect made from a list of steps, which must be in frame order.
+pub struct PsgEffect {
+    pub steps: &'static [PsgStep],
+}
+
+#[derive(Clone, Copy)]
+struct ActiveEffect {
+    effect: &'static PsgEffect,
+    frame: u16,
+    next_step: usize,
+}
+
+struct PsgSequencer {
+    effects: [Option<ActiveEffect>; MAX_EFFECTS],
+}
+
+impl PsgChannel {
+    const ALL: [PsgChannel; 4] = [
+        PsgChannel::Tone1,
+        PsgChannel::Tone2,
+        PsgChannel::Wave,
+        PsgChannel::Noise,
+    ];
+
+    fn mask(&self) -> u8 {
+        match self {
+            PsgChannel::Tone1 => 1,
+            PsgChannel::Tone2 => 2,
+            PsgChannel::Wave => 4,
+            PsgChannel::Noise => 8,
+        }
+    }
+
+    /// Switch the channel on or off in each speaker. A channel that has been silenced by the
+    /// sound effect volume is left alone.
+    fn route(&self, left: bool, right: bool) {
+        let vol = LEFT_RIGHT_VOLUME.read();
+
+        let vol = match self {
+            PsgChannel::Tone1 if vol.tone1_left() || vol.tone1_right() => {
+                vol.with_tone1_left(left).with_tone1_right(right)
+            }
+            PsgChannel::Tone2 if vol.tone2_left() || vol.tone2_right() => {
+                vol.with_tone2_left(left).with_tone2_right(right)
+            }
+            PsgChannel::Wave if vol.wave_left() || vol.wave_right() => {
+                vol.with_wave_left(left).with_wave_right(right)
+            }
+            PsgChannel::Noise if vol.noise_left() || vol.noise_right() => {
+                vol.with_noise_left(left).with_noise_right(right)
+            }
+            _ => return,
+        };
+
+        LEFT_RIGHT_VOLUME.write(vol);
+    }
+
+    fn stop(&self) {
+        match self {
+            PsgChannel::Tone1 => TONE1_PATTERN.write(TonePattern::new()),
+            PsgChannel::Tone2 => TONE2_PATTERN.write(TonePattern::new()),
+            PsgChannel::Wave => WAVE_BANK.write(WaveBank::new()),
+            PsgChannel::Noise => NOISE_LEN_ENV.write(NoiseLenEnvelope::new()),
+        }
+    }
+}
+
+impl Waveform {
+    fn load(&self) {
+        // Wave RAM can only be written while the other bank is selected for playback,
+        // so select bank 1, fill bank 0, then switch back over to play it.
+        WAVE_BANK.write(WaveBank::new().with_bank1(true));
+
+        for (i, word) in self.0.chunks_exact(4).enumerate() {
+            let word = u32::from_le_bytes([word[0], word[1], word[2], word[3]]);
+            WAVE_RAM.index(i).write(word);
+        }
+
+        WAVE_BANK.write(WaveBank::new().with_enabled(true));
+    }
+}
+
+impl PsgCommand {
+    fn channel(&self) -> PsgChannel {
+        match self {
+            PsgCommand::Tone1 { .. } => PsgChannel::Tone1,
+            PsgCommand::Tone2 { .. } => PsgChannel::Tone2,
+            PsgCommand::Wave { .. } => PsgChannel::Wave,
+            PsgCommand::Noise { .. } => PsgChannel::Noise,
+            PsgCommand::Stop(channel) => *channel,
+        }
+    }
+
+    fn run(&self) {
+        match self {
+            PsgCommand::Tone1 {
+                sweep,
+                pattern,
+                frequency,
+            } => {
+                TONE1_SWEEP.write(*sweep);
+                TONE1_PATTERN.write(*pattern);
+                TONE1_FREQUENCY.write(*frequency);
+            }
+            PsgCommand::Tone2 { pattern, frequency } => {
+                TONE2_PATTERN.write(*pattern);
+                TONE2_FREQUENCY.write(*frequency);
+            }
+            PsgCommand::Wave {
+                waveform,
+                volume,
+                frequency,
+            } => {
+                waveform.load();
+                WAVE_LEN_VOLUME.write(*volume);
+                WAVE_FREQ.write(*frequency);
+            }
+            PsgCommand::Noise {
+                envelope,
+                frequency,
+            } => {
+                NOISE_LEN_ENV.write(*envelope);
+                NOISE_FREQ.write(*frequency);
+            }
+            PsgCommand::Stop(channel) => channel.stop(),
+        }
+    }
+}
+
+impl PsgEffect {
+    /// Which channels the effect uses, as a bitmask.
+    fn channel_mask(&self) -> u8 {
+        self.steps
+            .iter()
+            .fold(0, |mask, step| mask | step.command.channel().mask())
+    }
+
+    /// Send the effect's channels to the speakers on the pan's side.
+    fn route(&self, pan: Pan) {
+        let (left, right) = pan.sides();
+        let mask = self.channel_mask();
+
+        for channel in PsgChannel::ALL {
+            if channel.mask() & mask != 0 {
+                channel.route(left, right);
+            }
+        }
+    }
+}
+
+impl ActiveEffect {
+    /// Run every step that is due. Returns false once there are no steps left.
+    fn run_due_steps(&mut self) -> bool {
+        let steps = self.effect.steps;
+
+        while self.next_step < steps.len() && steps[self.next_step].frame <= self.frame {
+            steps[self.next_step].command.run();
+            self.next_step += 1;
+        }
+
+        self.next_step < steps.len()
+    }
+}
+
+impl PsgSequencer {
+    fn play(&mut self, effect: &'static PsgEffect, pan: Pan) {
+        let mask = effect.channel_mask();
+
+        // A new effect takes over its channels from anything already using them.
+        for slot in self.effects.iter_mut() {
+            if let Some(active) = slot {
+                if active.effect.channel_mask() & mask != 0 {
+                    *slot = None;
+                }
+            }
+        }
+
+        effect.route(pan);
+
+        let mut active = ActiveEffect {
+            effect,
+            frame: 0,
+            next_step: 0,
+        };
+
+        // The first steps run straight away, rather than waiting for the next frame.
+        if !active.run_due_steps() {
+            effect.route(Pan::CENTRE);
+            return;
+        }
+
+        if let Some(slot) = self.effects.iter_mut().find(|slot| slot.is_none()) {
+            *slot = Some(active);
+        }
+    }
+
+    fn update(&mut self) {
+        for slot in self.effects.iter_mut() {
+            if let Some(active) = slot {
+                active.frame += 1;
+
+                if !active.run_due_steps() {
+                    active.effect.route(Pan::CENTRE);
+                    *slot = None;
+                }
+            }
+        }
+    }
+}
+
+/// Start playing an effect, cutting off any effects that use the same channels.
+pub fn play_psg(effect: &'static PsgEffect) {
+    play_psg_panned(effect, Pan::CENTRE);
+}
+
+/// Like `play_psg`, but from one side. The channels can only be switched on or off in each
+/// speaker, so the effect is heard from one side or both until it finishes.
+pub fn play_psg_panned(effect: &'static PsgEffect, pan: Pan) {
+    unsafe {
+        SEQUENCER.play(effect, pan);
+    }
+}
+
+/// Runs the steps of the playing effects. Must be called once per frame.
+pub fn update_psg() {
+    unsafe {
+        SEQUENCER.update();
+    }
+}
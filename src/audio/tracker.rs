@@ -0,0 +1,4 @@
+pub use mixer_core::tracker::{Cell, Instrument, Song};
+
+// One static per file in assets/music, named after the file.
+include!(concat!(env!("OUT_DIR"), "/song_data.rs"));
@@ -1,4 +1,6 @@
 pub mod affine;
 pub mod background;
 pub mod effects;
+pub mod palette;
 pub mod sprite;
+pub mod text;
@@ -4,6 +4,7 @@ use gba::{
 };
 use voladdress::{Safe, VolAddress};
 
+use super::palette::{self, PaletteKind};
 use crate::system::{
     constants,
     gba::{ClaimedGridFrames, ClaimedVolRegion, GBA},
@@ -107,7 +108,7 @@ impl BackgroundLayer {
         }
     }
 
-    fn get_priority(&self) -> u16 {
+    pub fn get_priority(&self) -> u16 {
         match self {
             BackgroundLayer::Bg0 => 3,
             BackgroundLayer::Bg1 => 2,
@@ -116,7 +117,7 @@ impl BackgroundLayer {
         }
     }
 
-    fn get_display_control_register(&self) -> VolAddress<BackgroundControl, Safe, Safe> {
+    pub fn get_display_control_register(&self) -> VolAddress<BackgroundControl, Safe, Safe> {
         match self {
             BackgroundLayer::Bg0 => BG0CNT,
             BackgroundLayer::Bg1 => BG1CNT,
@@ -125,7 +126,7 @@ impl BackgroundLayer {
         }
     }
 
-    fn enable(&self) -> DisplayControl {
+    pub fn enable(&self) -> DisplayControl {
         match self {
             BackgroundLayer::Bg0 => DISPCNT.read().with_show_bg0(true),
             BackgroundLayer::Bg1 => DISPCNT.read().with_show_bg1(true),
@@ -134,7 +135,7 @@ impl BackgroundLayer {
         }
     }
 
-    fn disable(&self) -> DisplayControl {
+    pub fn disable(&self) -> DisplayControl {
         match self {
             BackgroundLayer::Bg0 => DISPCNT.read().with_show_bg0(false),
             BackgroundLayer::Bg1 => DISPCNT.read().with_show_bg1(false),
@@ -155,6 +156,11 @@ impl<'a> LoadedBackground<'a> {
         self.layer
     }
 
+    /// Which background palette bank this background's colours were loaded into, for tinting.
+    pub fn get_palette_bank(&self) -> usize {
+        self.palette_bank.into()
+    }
+
     fn new(background: &'a Background, gba: &'a GBA, layer: BackgroundLayer) -> Self {
         let charblock_region = gba
             .charblock_memory
@@ -167,7 +173,7 @@ impl<'a> LoadedBackground<'a> {
             .expect("Out of screenblock memory.");
 
         // We only have 4BBP tiles, so request a palette bank.
-        let mut palette_memory = gba
+        let palette_memory = gba
             .bg_palette_memory
             .request_aligned_memory(1, 16)
             .expect("Out of BG palette memory.");
@@ -176,12 +182,12 @@ impl<'a> LoadedBackground<'a> {
 
         let pal_bank_number = pal_bank_number / 16;
 
-        // Write the palette to memory.
-        let bg_pal_bank_region = palette_memory.as_vol_region();
-
-        for (i, color) in background.palette.iter().enumerate() {
-            bg_pal_bank_region.get(i).unwrap().write(*color);
-        }
+        // Write the palette to the shadow palette, which will be copied over in the next VBlank.
+        palette::write_colors(
+            PaletteKind::Background,
+            palette_memory.get_start(),
+            background.palette,
+        );
 
         let mut loaded_bg = LoadedBackground {
             background,
@@ -270,6 +276,9 @@ impl<'a> Drop for LoadedBackground<'a> {
     fn drop(&mut self) {
         let disp_control = self.layer.disable();
         DISPCNT.write(disp_control);
+
+        // Don't leave the tint behind for whichever background uses the bank next.
+        palette::set_tint(PaletteKind::Background, self.get_palette_bank(), None);
     }
 }
 
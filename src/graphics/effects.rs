@@ -1,4 +1,7 @@
 pub mod background_scroller;
 pub mod blending;
 pub mod blinker;
+pub mod scanline;
 pub mod spinner;
+pub mod transition;
+pub mod window;
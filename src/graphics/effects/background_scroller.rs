@@ -47,4 +47,12 @@ impl BackgroundScroller {
         h_scroll.write(self.x_offset);
         v_scroll.write(self.y_offset);
     }
+
+    /// Only set the vertical scroll, for a background whose horizontal scroll is set line by line
+    /// by a `ScanlineEffect`.
+    pub fn apply_vertical_to_background(&self, background: &LoadedBackground) {
+        let v_scroll = background.get_layer().get_vertical_scroll_register();
+
+        v_scroll.write(self.y_offset);
+    }
 }
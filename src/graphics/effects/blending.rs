@@ -1,6 +1,6 @@
 use bitfrob::u8x2;
 use gba::{
-    prelude::{BLDALPHA, BLDCNT},
+    prelude::{BLDALPHA, BLDCNT, BLDY},
     video::BlendControl,
 };
 
@@ -16,12 +16,19 @@ impl BlendController {
         BLDCNT.write(blend_control);
         BLDALPHA.write(blend_weights);
     }
+
+    /// For the brighten and darken modes, which fade the first targets by `amount` sixteenths.
+    pub fn update_fade(&mut self, blend_control: BlendControl, amount: u8) {
+        BLDCNT.write(blend_control);
+        BLDY.write(amount);
+    }
 }
 
 impl Drop for BlendController {
     fn drop(&mut self) {
         BLDCNT.write(BlendControl::new());
         BLDALPHA.write([0u8, 0u8].into());
+        BLDY.write(0);
     }
 }
 
@@ -1,6 +1,6 @@
 use gba::prelude::ObjDisplayStyle;
 
-use crate::graphics::sprite::LoadedObjectEntry;
+use crate::graphics::{sprite::LoadedObjectEntry, text::TextSprite};
 
 #[derive(Clone)]
 enum BlinkState {
@@ -49,6 +49,10 @@ impl Blinker {
         }
     }
 
+    pub fn is_on(&self) -> bool {
+        matches!(self.state, BlinkState::On(_))
+    }
+
     pub fn apply_to_object(&self, obj: &mut LoadedObjectEntry<'_>) {
         let oa = obj.get_obj_attr_data();
         oa.set_style(match self.state {
@@ -56,4 +60,12 @@ impl Blinker {
             BlinkState::Off(_) => ObjDisplayStyle::NotDisplayed,
         })
     }
+
+    pub fn apply_to_text<const N: usize>(&self, text: &mut TextSprite<'_, N>) {
+        if self.is_on() {
+            text.set_visible();
+        } else {
+            text.set_hidden();
+        }
+    }
 }
@@ -0,0 +1,254 @@
+use core::{
+    ffi::c_void,
+    sync::atomic::{compiler_fence, Ordering},
+};
+
+use gba::{
+    dma::{DestAddrControl, DmaControl, DmaStartTime, SrcAddrControl},
+    mmio::{BACKDROP_COLOR, DMA0_CONTROL, DMA0_COUNT, DMA0_DEST, DMA0_SRC},
+    video::Color,
+};
+
+use crate::{
+    graphics::{
+        background::BackgroundLayer,
+        palette::{self, PaletteKind},
+    },
+    math,
+    system::{constants::SCREEN_HEIGHT, irq::critical_section},
+};
+
+// One value per line. The DMA runs a line ahead, so there's a spare one on the end
+// for the copy made in the last line's hblank.
+const TABLE_SIZE: usize = SCREEN_HEIGHT as usize + 1;
+
+// A whole turn of the angles used by `math::sin`.
+const FULL_TURN: u32 = 0x10000;
+
+// One table is copied from while the other is filled in, so a frame never shows half of each.
+static mut TABLES: [[u16; TABLE_SIZE]; 2] = [[0; TABLE_SIZE]; 2];
+
+// The table being copied from, or `None` if one hasn't been filled in yet.
+static mut FRONT_TABLE: Option<usize> = None;
+
+// Set once the other table has been filled in, to be swapped in at the start of the next frame.
+static mut SWAP_PENDING: bool = false;
+
+static mut TARGET: Option<ScanlineTarget> = None;
+
+/// The register that a scanline effect changes in each hblank.
+#[derive(Clone, Copy)]
+pub enum ScanlineTarget {
+    /// The colour shown wherever nothing else is drawn, ie background palette colour 0.
+    /// Palette tints and fades don't reach it while the effect is running, so the colours
+    /// should be passed through `palette::apply_effects` each frame.
+    Backdrop,
+    /// Don't also apply a `BackgroundScroller` to the same background.
+    HorizontalScroll(BackgroundLayer),
+    VerticalScroll(BackgroundLayer),
+}
+
+/// Lines from `first_line` onwards, up until the next band, are given `value`.
+#[derive(Clone, Copy)]
+pub struct ScanlineBand {
+    pub first_line: u16,
+    pub value: u16,
+}
+
+// Changes one register line by line, with DMA 0 copying the next line's value in each hblank.
+// The effect stops, and the register goes back to normal, once it's dropped.
+pub struct ScanlineEffect {}
+
+impl ScanlineTarget {
+    fn address(&self) -> *mut c_void {
+        match self {
+            ScanlineTarget::Backdrop => BACKDROP_COLOR.as_mut_ptr() as *mut c_void,
+            ScanlineTarget::HorizontalScroll(layer) => {
+                layer.get_horizontal_scroll_register().as_mut_ptr() as *mut c_void
+            }
+            ScanlineTarget::VerticalScroll(layer) => {
+                layer.get_vertical_scroll_register().as_mut_ptr() as *mut c_void
+            }
+        }
+    }
+
+    fn write(&self, value: u16) {
+        match self {
+            ScanlineTarget::Backdrop => BACKDROP_COLOR.write(Color(value)),
+            ScanlineTarget::HorizontalScroll(layer) => {
+                layer.get_horizontal_scroll_register().write(value)
+            }
+            ScanlineTarget::VerticalScroll(layer) => {
+                layer.get_vertical_scroll_register().write(value)
+            }
+        }
+    }
+
+    /// What the register holds when there's no effect on it.
+    fn resting_value(&self) -> u16 {
+        match self {
+            ScanlineTarget::Backdrop => {
+                let color = palette::get_color(PaletteKind::Background, 0);
+                palette::apply_effects(PaletteKind::Background, 0, color).0
+            }
+            ScanlineTarget::HorizontalScroll(_) | ScanlineTarget::VerticalScroll(_) => 0,
+        }
+    }
+}
+
+impl ScanlineEffect {
+    /// Nothing changes until the first set of lines is given.
+    /// Only one effect can run at a time, as they all share DMA 0.
+    pub fn new(target: ScanlineTarget) -> Self {
+        critical_section(|| unsafe {
+            assert!(
+                TARGET.is_none(),
+                "Only one scanline effect can run at a time."
+            );
+
+            TARGET = Some(target);
+            FRONT_TABLE = None;
+            SWAP_PENDING = false;
+        });
+
+        Self {}
+    }
+
+    /// Work out the value for each line, from 0 at the top of the screen.
+    /// They're shown from the start of the next frame.
+    pub fn set_lines<F: FnMut(u16) -> u16>(&mut self, mut value: F) {
+        unsafe {
+            // Keep the table from being swapped in while it's half filled.
+            SWAP_PENDING = false;
+            compiler_fence(Ordering::SeqCst);
+
+            let back = FRONT_TABLE.map_or(0, |front| 1 - front);
+            let table = &mut TABLES[back];
+
+            for (line, entry) in table.iter_mut().take(SCREEN_HEIGHT.into()).enumerate() {
+                *entry = value(line.try_into().unwrap());
+            }
+
+            table[TABLE_SIZE - 1] = table[TABLE_SIZE - 2];
+
+            compiler_fence(Ordering::SeqCst);
+            SWAP_PENDING = true;
+        }
+    }
+
+    /// Blend smoothly from one colour at the top of the screen to another at the bottom.
+    /// Meant for `ScanlineTarget::Backdrop`.
+    pub fn set_gradient(&mut self, top: Color, bottom: Color) {
+        let last_line = i32::from(SCREEN_HEIGHT - 1);
+
+        self.set_lines(|line| {
+            let line = i32::from(line);
+            let mix = |a: u16, b: u16| {
+                let (a, b) = (i32::from(a), i32::from(b));
+                let mixed = a + (b - a) * line / last_line;
+                u16::try_from(mixed).unwrap()
+            };
+
+            Color::from_rgb(
+                mix(top.red(), bottom.red()),
+                mix(top.green(), bottom.green()),
+                mix(top.blue(), bottom.blue()),
+            )
+            .0
+        });
+    }
+
+    /// Sway each line either side of `offset` by up to `amplitude` pixels, repeating every
+    /// `wavelength` lines. Moving `phase` on each frame makes the wave ripple along.
+    /// Meant for the scroll targets.
+    pub fn set_wave(&mut self, offset: u16, amplitude: i16, wavelength: u16, phase: u16) {
+        let step = FULL_TURN / u32::from(wavelength.max(1));
+
+        self.set_lines(|line| {
+            let angle = u32::from(phase) + u32::from(line) * step;
+            let sine = math::sin(angle as u16).into_raw();
+
+            // The sine has 8 fractional bits.
+            let sway = (i32::from(sine) * i32::from(amplitude)) >> 8;
+            offset.wrapping_add_signed(sway as i16)
+        });
+    }
+
+    /// Split the screen into horizontal bands, each with its own value. With a scroll target,
+    /// each band can be moved at its own speed, giving one background several layers of parallax.
+    /// Lines above the first band take its value. The bands must be in order, top to bottom.
+    pub fn set_bands(&mut self, bands: &[ScanlineBand]) {
+        let first = match bands.first() {
+            Some(first) => first,
+            None => return,
+        };
+
+        self.set_lines(|line| {
+            bands
+                .iter()
+                .take_while(|band| band.first_line <= line)
+                .last()
+                .unwrap_or(first)
+                .value
+        });
+    }
+}
+
+impl Drop for ScanlineEffect {
+    fn drop(&mut self) {
+        critical_section(|| unsafe {
+            DMA0_CONTROL.write(DmaControl::new().with_enabled(false));
+
+            if let Some(target) = TARGET {
+                target.write(target.resting_value());
+            }
+
+            TARGET = None;
+            FRONT_TABLE = None;
+            SWAP_PENDING = false;
+        });
+    }
+}
+
+/// Point DMA 0 back at the top of the table, swapping in a newly filled one if there is one.
+/// Called from the interrupt on the last line of vblank, so the first line's value can be
+/// written just before it's drawn. The DMA then copies each following line's value in the
+/// hblank before it, and isn't triggered again until this is next called.
+pub fn restart() {
+    unsafe {
+        let target = match TARGET {
+            Some(target) => target,
+            None => return,
+        };
+
+        if SWAP_PENDING {
+            FRONT_TABLE = Some(FRONT_TABLE.map_or(0, |front| 1 - front));
+            SWAP_PENDING = false;
+        }
+
+        let front = match FRONT_TABLE {
+            Some(front) => front,
+            None => return,
+        };
+
+        let table = &TABLES[front];
+
+        DMA0_CONTROL.write(DmaControl::new().with_enabled(false));
+
+        target.write(table[0]);
+
+        // Only DMA 0 is touched here, the audio keeps DMA 1 and 2 to itself.
+        let dma_control = DmaControl::new()
+            .with_src_addr_control(SrcAddrControl::Increment)
+            .with_dest_addr_control(DestAddrControl::Fixed)
+            .with_start_time(DmaStartTime::HBlank)
+            .with_transfer_32bit(false)
+            .with_repeat(true)
+            .with_enabled(true);
+
+        DMA0_SRC.write(&table[1] as *const u16 as *const c_void);
+        DMA0_DEST.write(target.address());
+        DMA0_COUNT.write(1);
+        DMA0_CONTROL.write(dma_control);
+    }
+}
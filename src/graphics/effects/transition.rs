@@ -0,0 +1,223 @@
+use gba::{
+    mmio::{BG0CNT, BG1CNT, BG2CNT, BG3CNT, BLDCNT, BLDY, MOSAIC},
+    video::{BlendControl, ColorEffectMode, Mosaic},
+};
+
+use super::window::{self, WindowRect};
+use crate::{
+    graphics::palette::FadeColor,
+    system::{
+        constants::{SCREEN_CENTER, SCREEN_HEIGHT, SCREEN_WIDTH},
+        gba::GBA,
+    },
+};
+
+// The transition that the next screen should open with, left behind by the one that just closed.
+static mut INCOMING: Transition = Transition::Cut;
+
+/// How much of the screen is hidden, in sixteenths. Matches the range of the BLDY register.
+const FULLY_COVERED: u16 = 16;
+
+// The mosaic registers are 4 bits, so blocks can be up to 16 pixels.
+const MAX_MOSAIC: u16 = 15;
+
+#[derive(Clone, Copy)]
+pub enum Transition {
+    Cut,
+    /// Brighten to white or darken to black, using the colour special effects.
+    Fade(FadeColor, u16),
+    /// Break the screen up into ever bigger blocks.
+    Mosaic(u16),
+    /// Shrink a window in on the centre of the screen.
+    Iris(u16),
+    /// Sweep a window edge across the screen, from left to right.
+    Wipe(u16),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum TransitionDirection {
+    /// Hiding the outgoing screen.
+    Out,
+    /// Revealing the incoming screen.
+    In,
+}
+
+/// Runs a transition one frame at a time, and undoes its effect on the hardware once a
+/// screen has been fully revealed.
+pub struct TransitionPlayer<'a> {
+    gba: &'a GBA,
+    transition: Transition,
+    direction: TransitionDirection,
+    frame: u16,
+    // The blend settings the screen was using before the fade took them over.
+    saved_blend: BlendControl,
+}
+
+impl Transition {
+    fn frames(&self) -> u16 {
+        match self {
+            Transition::Cut => 0,
+            Transition::Fade(_, frames)
+            | Transition::Mosaic(frames)
+            | Transition::Iris(frames)
+            | Transition::Wipe(frames) => *frames,
+        }
+    }
+}
+
+impl<'a> TransitionPlayer<'a> {
+    pub fn new(gba: &'a GBA, transition: Transition, direction: TransitionDirection) -> Self {
+        let player = Self {
+            gba,
+            transition,
+            direction,
+            frame: 0,
+            saved_blend: BLDCNT.read(),
+        };
+
+        // The next screen opens with the same transition that this one closes with.
+        if direction == TransitionDirection::Out {
+            unsafe { INCOMING = transition };
+        }
+
+        // Set up the first frame straight away, so that nothing shows before the transition starts.
+        player.apply();
+        player
+    }
+
+    /// The transition that the previous screen closed with, to open the next screen with.
+    pub fn incoming(gba: &'a GBA) -> Self {
+        let transition = unsafe { INCOMING };
+        Self::new(gba, transition, TransitionDirection::In)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.frame >= self.transition.frames()
+    }
+
+    pub fn update(&mut self) {
+        if self.is_finished() {
+            return;
+        }
+
+        self.frame += 1;
+        self.apply();
+
+        // Once hidden, the screen stays hidden for the next one to open from.
+        if self.is_finished() && self.direction == TransitionDirection::In {
+            self.reset();
+        }
+    }
+
+    /// Stop early, leaving the screen fully shown.
+    pub fn cancel(&mut self) {
+        if !self.is_finished() {
+            self.frame = self.transition.frames();
+            self.reset();
+        }
+    }
+
+    /// How much of the screen should be hidden on this frame.
+    fn coverage(&self) -> u16 {
+        let frames = self.transition.frames();
+
+        if frames == 0 {
+            return match self.direction {
+                TransitionDirection::Out => FULLY_COVERED,
+                TransitionDirection::In => 0,
+            };
+        }
+
+        let progress = self.frame.min(frames) * FULLY_COVERED / frames;
+
+        match self.direction {
+            TransitionDirection::Out => progress,
+            TransitionDirection::In => FULLY_COVERED - progress,
+        }
+    }
+
+    /// Write the settings for the current frame. This can be used to restore them after a
+    /// screen has reset them while being dropped.
+    pub fn apply(&self) {
+        let coverage = self.coverage();
+
+        match self.transition {
+            Transition::Cut => {}
+            Transition::Fade(color, _) => apply_fade(color, coverage),
+            Transition::Mosaic(_) => self.apply_mosaic(coverage),
+            Transition::Iris(_) => {
+                let half_width = SCREEN_CENTER.0 * (FULLY_COVERED - coverage) / FULLY_COVERED;
+                let half_height = SCREEN_CENTER.1 * (FULLY_COVERED - coverage) / FULLY_COVERED;
+
+                window::show_only(WindowRect::new(
+                    SCREEN_CENTER.0 - half_width,
+                    SCREEN_CENTER.1 - half_height,
+                    half_width * 2,
+                    half_height * 2,
+                ));
+            }
+            Transition::Wipe(_) => {
+                let left = SCREEN_WIDTH * coverage / FULLY_COVERED;
+                window::show_only(WindowRect::new(left, 0, SCREEN_WIDTH - left, SCREEN_HEIGHT));
+            }
+        }
+    }
+
+    fn apply_mosaic(&self, coverage: u16) {
+        let size = MAX_MOSAIC * coverage / FULLY_COVERED;
+
+        MOSAIC.write(
+            Mosaic::new()
+                .with_bg_h_extra(size)
+                .with_bg_v_extra(size)
+                .with_obj_h_extra(size)
+                .with_obj_v_extra(size),
+        );
+
+        // Backgrounds may have been loaded since the last frame, so set the flag every time.
+        set_background_mosaic(true);
+        self.gba.shadow_oam.set_mosaic(true);
+    }
+
+    /// Put back everything the transition changed.
+    fn reset(&self) {
+        match self.transition {
+            Transition::Cut => {}
+            Transition::Fade(..) => {
+                BLDCNT.write(self.saved_blend);
+                BLDY.write(0);
+            }
+            Transition::Mosaic(_) => {
+                MOSAIC.write(Mosaic::new());
+                set_background_mosaic(false);
+                self.gba.shadow_oam.set_mosaic(false);
+            }
+            Transition::Iris(_) | Transition::Wipe(_) => window::restore(),
+        }
+    }
+}
+
+fn apply_fade(color: FadeColor, coverage: u16) {
+    let mode = match color {
+        FadeColor::Black => ColorEffectMode::Darken,
+        FadeColor::White => ColorEffectMode::Brighten,
+    };
+
+    BLDCNT.write(
+        BlendControl::new()
+            .with_mode(mode)
+            .with_target1_bg0(true)
+            .with_target1_bg1(true)
+            .with_target1_bg2(true)
+            .with_target1_bg3(true)
+            .with_target1_obj(true)
+            .with_target1_backdrop(true),
+    );
+    BLDY.write(coverage.try_into().unwrap());
+}
+
+fn set_background_mosaic(enabled: bool) {
+    for register in [BG0CNT, BG1CNT, BG2CNT, BG3CNT] {
+        register.write(register.read().with_mosaic(enabled));
+    }
+}
@@ -0,0 +1,266 @@
+use gba::{
+    mmio::{DISPCNT, WIN0H, WIN0V, WIN1H, WIN1V, WININ, WINOUT},
+    video::{WindowInside, WindowOutside},
+};
+
+use crate::{
+    graphics::background::BackgroundLayer,
+    system::constants::{SCREEN_HEIGHT, SCREEN_WIDTH},
+};
+
+// What the screen has asked the windows to show. Kept separately from the registers,
+// so it can be put back after a transition has borrowed the windows.
+static mut WINDOWS: WindowSettings = WindowSettings::OFF;
+
+// Set while a transition is using the windows, during which the screen's settings are only stored.
+static mut TAKEN_OVER: bool = false;
+
+#[derive(Clone, Copy)]
+pub enum Window {
+    /// Drawn over window 1 where the two overlap.
+    Win0,
+    Win1,
+}
+
+/// The layers that are drawn in an area of the screen, and whether the colour special
+/// effects (blending and fades) apply to them there.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct WindowLayers {
+    backgrounds: [bool; 4],
+    objects: bool,
+    effects: bool,
+}
+
+/// An area of the screen, in pixels. The right and bottom edges are just outside of it.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct WindowRect {
+    left: u16,
+    top: u16,
+    right: u16,
+    bottom: u16,
+}
+
+#[derive(Clone, Copy)]
+struct WindowSettings {
+    win0: Option<(WindowRect, WindowLayers)>,
+    win1: Option<(WindowRect, WindowLayers)>,
+    obj_window: Option<WindowLayers>,
+    outside: WindowLayers,
+}
+
+// Owns the window settings, and turns the windows off once it's dropped.
+pub struct WindowController {}
+
+impl WindowLayers {
+    /// Nothing but the backdrop.
+    pub const NONE: Self = Self {
+        backgrounds: [false; 4],
+        objects: false,
+        effects: false,
+    };
+
+    /// Everything, with the colour special effects.
+    pub const ALL: Self = Self {
+        backgrounds: [true; 4],
+        objects: true,
+        effects: true,
+    };
+
+    pub fn with_background(mut self, layer: BackgroundLayer, shown: bool) -> Self {
+        self.backgrounds[layer as usize] = shown;
+        self
+    }
+
+    pub fn with_objects(mut self, shown: bool) -> Self {
+        self.objects = shown;
+        self
+    }
+
+    pub fn with_effects(mut self, enabled: bool) -> Self {
+        self.effects = enabled;
+        self
+    }
+}
+
+impl WindowRect {
+    /// Anything past the edge of the screen is cut off.
+    pub fn new(x: u16, y: u16, width: u16, height: u16) -> Self {
+        Self {
+            left: x.min(SCREEN_WIDTH),
+            top: y.min(SCREEN_HEIGHT),
+            right: (x + width).min(SCREEN_WIDTH),
+            bottom: (y + height).min(SCREEN_HEIGHT),
+        }
+    }
+}
+
+impl WindowSettings {
+    const OFF: Self = Self {
+        win0: None,
+        win1: None,
+        obj_window: None,
+        outside: WindowLayers::ALL,
+    };
+}
+
+impl WindowController {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    /// Show `layers` inside the rectangle.
+    pub fn set_window(&mut self, window: Window, rect: WindowRect, layers: WindowLayers) {
+        update_settings(|settings| match window {
+            Window::Win0 => settings.win0 = Some((rect, layers)),
+            Window::Win1 => settings.win1 = Some((rect, layers)),
+        });
+    }
+
+    pub fn clear_window(&mut self, window: Window) {
+        update_settings(|settings| match window {
+            Window::Win0 => settings.win0 = None,
+            Window::Win1 => settings.win1 = None,
+        });
+    }
+
+    /// Show `layers` wherever an object in `ObjEffectMode::Window` has a solid pixel.
+    pub fn set_obj_window(&mut self, layers: WindowLayers) {
+        update_settings(|settings| settings.obj_window = Some(layers));
+    }
+
+    pub fn clear_obj_window(&mut self) {
+        update_settings(|settings| settings.obj_window = None);
+    }
+
+    /// Show `layers` everywhere that isn't inside one of the windows.
+    pub fn set_outside(&mut self, layers: WindowLayers) {
+        update_settings(|settings| settings.outside = layers);
+    }
+
+    /// Turn every window off, leaving the whole screen shown.
+    pub fn clear(&mut self) {
+        update_settings(|settings| *settings = WindowSettings::OFF);
+    }
+}
+
+impl Drop for WindowController {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl Default for WindowController {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Show only the backdrop outside of the rectangle, over the top of whatever the screen is
+/// using the windows for. This lasts until `restore` is called.
+pub fn show_only(rect: WindowRect) {
+    unsafe { TAKEN_OVER = true };
+
+    let settings = WindowSettings {
+        win0: Some((rect, WindowLayers::ALL)),
+        win1: None,
+        obj_window: None,
+        outside: WindowLayers::NONE,
+    };
+
+    write_settings(&settings);
+}
+
+/// Put back the screen's own window settings.
+pub fn restore() {
+    unsafe {
+        TAKEN_OVER = false;
+        write_settings(&WINDOWS);
+    }
+}
+
+fn update_settings<F: FnOnce(&mut WindowSettings)>(update: F) {
+    unsafe {
+        update(&mut WINDOWS);
+
+        if !TAKEN_OVER {
+            write_settings(&WINDOWS);
+        }
+    }
+}
+
+fn write_settings(settings: &WindowSettings) {
+    if let Some((rect, _)) = settings.win0 {
+        let (horizontal, vertical) = rect_registers(rect);
+        WIN0H.write(horizontal.into());
+        WIN0V.write(vertical.into());
+    }
+
+    if let Some((rect, _)) = settings.win1 {
+        let (horizontal, vertical) = rect_registers(rect);
+        WIN1H.write(horizontal.into());
+        WIN1V.write(vertical.into());
+    }
+
+    let win0_layers = settings
+        .win0
+        .map_or(WindowLayers::NONE, |(_, layers)| layers);
+    let win1_layers = settings
+        .win1
+        .map_or(WindowLayers::NONE, |(_, layers)| layers);
+    let obj_window_layers = settings.obj_window.unwrap_or(WindowLayers::NONE);
+    let [win0_bg0, win0_bg1, win0_bg2, win0_bg3] = win0_layers.backgrounds;
+    let [win1_bg0, win1_bg1, win1_bg2, win1_bg3] = win1_layers.backgrounds;
+    let [outside_bg0, outside_bg1, outside_bg2, outside_bg3] = settings.outside.backgrounds;
+    let [obj_win_bg0, obj_win_bg1, obj_win_bg2, obj_win_bg3] = obj_window_layers.backgrounds;
+
+    WININ.write(
+        WindowInside::new()
+            .with_win0_bg0(win0_bg0)
+            .with_win0_bg1(win0_bg1)
+            .with_win0_bg2(win0_bg2)
+            .with_win0_bg3(win0_bg3)
+            .with_win0_obj(win0_layers.objects)
+            .with_win0_effect(win0_layers.effects)
+            .with_win1_bg0(win1_bg0)
+            .with_win1_bg1(win1_bg1)
+            .with_win1_bg2(win1_bg2)
+            .with_win1_bg3(win1_bg3)
+            .with_win1_obj(win1_layers.objects)
+            .with_win1_effect(win1_layers.effects),
+    );
+
+    WINOUT.write(
+        WindowOutside::new()
+            .with_outside_bg0(outside_bg0)
+            .with_outside_bg1(outside_bg1)
+            .with_outside_bg2(outside_bg2)
+            .with_outside_bg3(outside_bg3)
+            .with_outside_obj(settings.outside.objects)
+            .with_outside_effect(settings.outside.effects)
+            .with_obj_win_bg0(obj_win_bg0)
+            .with_obj_win_bg1(obj_win_bg1)
+            .with_obj_win_bg2(obj_win_bg2)
+            .with_obj_win_bg3(obj_win_bg3)
+            .with_obj_win_obj(obj_window_layers.objects)
+            .with_obj_win_effect(obj_window_layers.effects),
+    );
+
+    // With every window off, the outside settings are ignored and the whole screen is shown.
+    DISPCNT.write(
+        DISPCNT
+            .read()
+            .with_enable_win0(settings.win0.is_some())
+            .with_enable_win1(settings.win1.is_some())
+            .with_enable_obj_win(settings.obj_window.is_some()),
+    );
+}
+
+/// The low byte holds the far edge, plus one, and the high byte holds the near edge.
+fn rect_registers(rect: WindowRect) -> ([u8; 2], [u8; 2]) {
+    let to_u8 = |value: u16| -> u8 { value.try_into().unwrap() };
+
+    (
+        [to_u8(rect.right), to_u8(rect.left)],
+        [to_u8(rect.bottom), to_u8(rect.top)],
+    )
+}
@@ -0,0 +1,377 @@
+use gba::{
+    mmio::{BG_PALETTE, OBJ_PALETTE},
+    video::Color,
+};
+
+static mut PALETTES: ShadowPalettes = ShadowPalettes {
+    background: ShadowPalette::new(),
+    object: ShadowPalette::new(),
+    fade: Fade {
+        color: FadeColor::Black,
+        level: 0,
+        start_level: 0,
+        target_level: 0,
+        frame: 0,
+        frames: 0,
+    },
+    cycles: [None, None, None, None],
+    needs_commit: false,
+};
+
+const PALETTE_SIZE: usize = 256;
+pub const BANK_SIZE: usize = 16;
+const NUM_BANKS: usize = PALETTE_SIZE / BANK_SIZE;
+const MAX_CYCLES: usize = 4;
+
+/// Tints and fades are blended in sixteenths, the same as the hardware blend registers.
+pub const MAX_BLEND: u16 = 16;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum PaletteKind {
+    Background,
+    Object,
+}
+
+/// The colour that the whole screen fades towards.
+#[derive(Clone, Copy)]
+pub enum FadeColor {
+    Black,
+    White,
+}
+
+/// Blends every colour in a palette bank towards another colour.
+#[derive(Clone, Copy)]
+pub struct Tint {
+    pub color: Color,
+    /// Out of MAX_BLEND.
+    pub amount: u16,
+}
+
+/// Rotates a run of colours along by one every few frames.
+#[derive(Clone, Copy)]
+pub struct ColorCycle {
+    pub kind: PaletteKind,
+    pub start: usize,
+    pub len: usize,
+    pub frames_per_step: u16,
+}
+
+#[derive(Clone, Copy)]
+struct ActiveCycle {
+    cycle: ColorCycle,
+    offset: usize,
+    timer: u16,
+}
+
+struct Fade {
+    color: FadeColor,
+    level: u16,
+    start_level: u16,
+    target_level: u16,
+    frame: u16,
+    frames: u16,
+}
+
+/// The colours as loaded, along with the colours after the effects have been applied.
+/// Only the latter is ever written to palette RAM.
+struct ShadowPalette {
+    colors: [Color; PALETTE_SIZE],
+    output: [Color; PALETTE_SIZE],
+    tints: [Option<Tint>; NUM_BANKS],
+    dirty: bool,
+}
+
+struct ShadowPalettes {
+    background: ShadowPalette,
+    object: ShadowPalette,
+    fade: Fade,
+    cycles: [Option<ActiveCycle>; MAX_CYCLES],
+    needs_commit: bool,
+}
+
+impl FadeColor {
+    fn color(&self) -> Color {
+        match self {
+            FadeColor::Black => Color::BLACK,
+            FadeColor::White => Color::WHITE,
+        }
+    }
+}
+
+impl Fade {
+    fn is_running(&self) -> bool {
+        self.frame < self.frames
+    }
+
+    /// Move the fade on by a frame. Returns true if the level changed.
+    fn update(&mut self) -> bool {
+        if !self.is_running() {
+            return false;
+        }
+
+        self.frame += 1;
+
+        let start = i32::from(self.start_level);
+        let target = i32::from(self.target_level);
+        let level = start + (target - start) * i32::from(self.frame) / i32::from(self.frames);
+        let level: u16 = level.try_into().unwrap();
+
+        let changed = level != self.level;
+        self.level = level;
+        changed
+    }
+}
+
+impl ActiveCycle {
+    /// Returns true if the colours moved along.
+    fn update(&mut self) -> bool {
+        self.timer += 1;
+
+        if self.timer < self.cycle.frames_per_step {
+            return false;
+        }
+
+        self.timer = 0;
+        self.offset = (self.offset + 1) % self.cycle.len;
+        true
+    }
+
+    /// Which of the loaded colours should be shown at the given index.
+    fn source_index(&self, index: usize) -> usize {
+        let start = self.cycle.start;
+
+        if (start..start + self.cycle.len).contains(&index) {
+            start + (index - start + self.offset) % self.cycle.len
+        } else {
+            index
+        }
+    }
+}
+
+impl ShadowPalette {
+    const fn new() -> Self {
+        Self {
+            colors: [Color::BLACK; PALETTE_SIZE],
+            output: [Color::BLACK; PALETTE_SIZE],
+            tints: [None; NUM_BANKS],
+            dirty: false,
+        }
+    }
+}
+
+impl ShadowPalettes {
+    fn palette_mut(&mut self, kind: PaletteKind) -> &mut ShadowPalette {
+        match kind {
+            PaletteKind::Background => &mut self.background,
+            PaletteKind::Object => &mut self.object,
+        }
+    }
+
+    fn mark_all_dirty(&mut self) {
+        self.background.dirty = true;
+        self.object.dirty = true;
+    }
+
+    fn update(&mut self) {
+        if self.fade.update() {
+            self.mark_all_dirty();
+        }
+
+        for active in self.cycles.iter_mut().flatten() {
+            if active.update() {
+                let kind = active.cycle.kind;
+                match kind {
+                    PaletteKind::Background => self.background.dirty = true,
+                    PaletteKind::Object => self.object.dirty = true,
+                }
+            }
+        }
+
+        for kind in [PaletteKind::Background, PaletteKind::Object] {
+            if self.palette_mut(kind).dirty {
+                self.rebuild_output(kind);
+                self.needs_commit = true;
+            }
+        }
+    }
+
+    /// Apply the colour cycles, tints and fade to the loaded colours.
+    fn rebuild_output(&mut self, kind: PaletteKind) {
+        let cycles = self.cycles;
+        let fade_color = self.fade.color.color();
+        let fade_level = self.fade.level;
+        let palette = self.palette_mut(kind);
+
+        for index in 0..PALETTE_SIZE {
+            let source = cycles
+                .iter()
+                .flatten()
+                .filter(|active| active.cycle.kind == kind)
+                .fold(index, |source, active| active.source_index(source));
+
+            let mut color = palette.colors[source];
+
+            if let Some(tint) = palette.tints[index / BANK_SIZE] {
+                color = blend(color, tint.color, tint.amount);
+            }
+
+            palette.output[index] = blend(color, fade_color, fade_level);
+        }
+
+        palette.dirty = false;
+    }
+
+    fn commit(&mut self) {
+        if !self.needs_commit {
+            return;
+        }
+
+        for (i, color) in self.background.output.iter().enumerate() {
+            BG_PALETTE.index(i).write(*color);
+        }
+
+        for (i, color) in self.object.output.iter().enumerate() {
+            OBJ_PALETTE.index(i).write(*color);
+        }
+
+        self.needs_commit = false;
+    }
+}
+
+/// Mix two colours, taking `amount` sixteenths of the second.
+fn blend(from: Color, to: Color, amount: u16) -> Color {
+    let amount = i32::from(amount.min(MAX_BLEND));
+    let mix = |a: u16, b: u16| {
+        let (a, b) = (i32::from(a), i32::from(b));
+        let mixed = a + (b - a) * amount / i32::from(MAX_BLEND);
+        u16::try_from(mixed).unwrap()
+    };
+
+    Color::from_rgb(
+        mix(from.red(), to.red()),
+        mix(from.green(), to.green()),
+        mix(from.blue(), to.blue()),
+    )
+}
+
+/// Load colours into the shadow palette. They'll reach the screen at the next commit.
+pub fn write_colors(kind: PaletteKind, start: usize, colors: &[Color]) {
+    unsafe {
+        let palette = PALETTES.palette_mut(kind);
+        palette.colors[start..start + colors.len()].copy_from_slice(colors);
+        palette.dirty = true;
+    }
+}
+
+pub fn set_color(kind: PaletteKind, index: usize, color: Color) {
+    write_colors(kind, index, &[color]);
+}
+
+/// The colour as it was loaded, before any effects.
+pub fn get_color(kind: PaletteKind, index: usize) -> Color {
+    unsafe { PALETTES.palette_mut(kind).colors[index] }
+}
+
+/// A colour as it would be shown from one of the 16-colour banks, with the bank's tint and
+/// the current fade applied.
+pub fn apply_effects(kind: PaletteKind, bank: usize, color: Color) -> Color {
+    unsafe {
+        let fade = &PALETTES.fade;
+        let tinted = match PALETTES.palette_mut(kind).tints[bank] {
+            Some(tint) => blend(color, tint.color, tint.amount),
+            None => color,
+        };
+
+        blend(tinted, fade.color.color(), fade.level)
+    }
+}
+
+/// Tint one of the 16-colour banks, or pass None to remove its tint.
+pub fn set_tint(kind: PaletteKind, bank: usize, tint: Option<Tint>) {
+    unsafe {
+        let palette = PALETTES.palette_mut(kind);
+        palette.tints[bank] = tint;
+        palette.dirty = true;
+    }
+}
+
+/// Fade both palettes from the current level to the given level, over a number of frames.
+/// A level of MAX_BLEND is solid colour, and zero shows the palettes as they are.
+pub fn start_fade(color: FadeColor, target_level: u16, frames: u16) {
+    unsafe {
+        let fade = &mut PALETTES.fade;
+        fade.color = color;
+        fade.start_level = fade.level;
+        fade.target_level = target_level.min(MAX_BLEND);
+        fade.frame = 0;
+        fade.frames = frames;
+
+        // Jump straight there if there's no time to fade.
+        if frames == 0 {
+            fade.level = fade.target_level;
+            PALETTES.mark_all_dirty();
+        }
+    }
+}
+
+/// Fade back from whichever colour was last faded to.
+pub fn fade_in(frames: u16) {
+    let color = unsafe { PALETTES.fade.color };
+    start_fade(color, 0, frames);
+}
+
+pub fn is_fading() -> bool {
+    unsafe { PALETTES.fade.is_running() }
+}
+
+pub fn fade_level() -> u16 {
+    unsafe { PALETTES.fade.level }
+}
+
+/// Start cycling a run of colours. Returns false if there are already too many cycles running.
+pub fn start_cycle(cycle: ColorCycle) -> bool {
+    unsafe {
+        let slot = PALETTES.cycles.iter_mut().find(|slot| slot.is_none());
+
+        match slot {
+            Some(slot) => {
+                *slot = Some(ActiveCycle {
+                    cycle,
+                    offset: 0,
+                    timer: 0,
+                });
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Stop any cycles in the given palette and put the colours back where they were.
+pub fn stop_cycles(kind: PaletteKind) {
+    unsafe {
+        for slot in PALETTES.cycles.iter_mut() {
+            if matches!(slot, Some(active) if active.cycle.kind == kind) {
+                *slot = None;
+            }
+        }
+
+        PALETTES.palette_mut(kind).dirty = true;
+    }
+}
+
+/// Move the fades and cycles along, and work out the colours for the next commit.
+/// Should be called once per frame, outside of VBlank, since rebuilding a palette is slow.
+pub fn update_palettes() {
+    unsafe {
+        PALETTES.update();
+    }
+}
+
+/// Copy the shadow palettes into palette RAM, if they've changed.
+///
+/// # Safety
+/// Must be called during VBlank, so the change doesn't tear.
+pub unsafe fn commit_palettes() {
+    PALETTES.commit();
+}
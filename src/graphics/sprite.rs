@@ -6,6 +6,7 @@ use gba::video::{
 use voladdress::Safe;
 
 use super::affine::AffineMatrix;
+use super::palette::{self, PaletteKind};
 use crate::system::gba::{ClaimedVolRegion, OAMEntry, GBA};
 
 pub struct Sprite {
@@ -255,6 +256,14 @@ impl<'a, const C: usize> AnimationController<'a, C> {
     }
 }
 
+/// Swap the player colours for ones that are easier to tell apart, or back to the originals.
+pub fn set_colour_blind_palette(enabled: bool) {
+    for (index, color) in OBJ_COLOUR_BLIND_PALETTE_ENTRIES {
+        let color = if enabled { color } else { OBJ_PALETTE[index] };
+        palette::set_color(PaletteKind::Object, index, color);
+    }
+}
+
 // Insert all of the code generated by the build sript.
 // This will contain static definitions for all of our aseprite files.
 include!(concat!(env!("OUT_DIR"), "/sprite_data.rs"));
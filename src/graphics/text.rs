@@ -0,0 +1,602 @@
+use core::fmt;
+
+use gba::mmio::{DISPCNT, SCREENBLOCK_INDEX_OFFSET};
+use gba::video::{
+    obj::{ObjDisplayStyle, ObjShape},
+    BackgroundControl, Color, TextEntry, Tile4,
+};
+use voladdress::{Safe, VolRegion};
+
+use super::background::BackgroundLayer;
+use super::palette::{self, ColorCycle, PaletteKind};
+use crate::system::{
+    constants,
+    gba::{ClaimedGridFrames, ClaimedVolRegion, OAMEntry, GBA},
+};
+
+const TILE_SIZE: usize = 8;
+
+// How many tiles make up the dimensions of a screenblock.
+const SCREENBLOCK_SIZE: usize = 32;
+
+// Text sprites are built from 32x8 objects.
+const SEGMENT_TILES: usize = 4;
+const SEGMENT_WIDTH: u16 = 32;
+const SEGMENT_SIZE: u16 = 1;
+
+// The colour indices used by the glyph tiles, before they are drawn in a particular palette.
+const GLYPH_INK: u32 = 1;
+const GLYPH_SHADOW: u32 = 2;
+
+const FALLBACK_CHAR: char = '?';
+
+const FRAMES_PER_SECOND: u32 = 60;
+
+pub struct Glyph {
+    tile: Tile4,
+    /// How far along to move before drawing the next glyph.
+    width: u8,
+}
+
+pub struct Font {
+    /// Sorted by character.
+    glyphs: &'static [(char, Glyph)],
+    ink: Color,
+    shadow: Color,
+    // Where the font's colours are in the object palette.
+    obj_palette_bank: u16,
+    obj_ink_index: u32,
+    obj_shadow_index: u32,
+    obj_dim_index: u32,
+    obj_red_index: u32,
+    obj_yellow_index: u32,
+    /// The glow colours are side by side, starting here.
+    obj_glow_index: u32,
+    glow_len: usize,
+}
+
+/// Which colour text sprites draw their ink in.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum TextInk {
+    Normal,
+    /// Greyed out.
+    Dimmed,
+    /// The players' colours, for their names.
+    Red,
+    Yellow,
+    /// Shines while the font's glow is cycling.
+    Glow,
+}
+
+/// The palette indices to draw the ink and the shadow with.
+#[derive(Clone, Copy)]
+struct GlyphColors {
+    ink: u32,
+    shadow: u32,
+}
+
+/// A grid of tiles that text can be drawn into at any pixel position, laid out row by row.
+#[derive(Clone, Copy)]
+struct TileCanvas {
+    tiles: VolRegion<Tile4, Safe, Safe>,
+    width_in_tiles: usize,
+    height_in_tiles: usize,
+    colors: GlyphColors,
+}
+
+/// Text drawn into part of a background layer.
+pub struct TextBox<'a> {
+    // Memory for the tiles that the text is drawn into, plus one blank tile for the rest of the layer.
+    charblock_memory: ClaimedVolRegion<'a, Tile4, Safe, Safe>,
+    // Memory for the tilemap
+    screenblock_memory:
+        ClaimedGridFrames<'a, TextEntry, Safe, Safe, 32, 32, 32, SCREENBLOCK_INDEX_OFFSET>,
+    // Memory for the palette
+    palette_memory: ClaimedVolRegion<'a, Color, Safe, Safe>,
+    layer: BackgroundLayer,
+    canvas: TileCanvas,
+}
+
+/// A single line of text drawn into a row of objects, so that it can be moved around freely.
+/// It can be up to 32 pixels wide for each object.
+pub struct TextSprite<'a, const N: usize> {
+    _memory: ClaimedVolRegion<'a, Tile4, Safe, Safe>,
+    objects: [OAMEntry<'a>; N],
+    canvas: TileCanvas,
+    text_width: u16,
+    position: (u16, u16),
+    visible: bool,
+}
+
+/// A fixed-size string that can be formatted into without allocating, for scores, timers and so on.
+/// Anything that doesn't fit is cut off.
+pub struct TextBuffer<const N: usize> {
+    bytes: [u8; N],
+    len: usize,
+}
+
+/// A number of frames, displayed as minutes and seconds.
+pub struct FrameTime(pub u32);
+
+impl Font {
+    /// The glyph for a character, or a question mark if the font doesn't have it.
+    pub fn glyph(&self, character: char) -> &Glyph {
+        self.find_glyph(character)
+            .or_else(|| self.find_glyph(FALLBACK_CHAR))
+            .expect("Font has no fallback glyph.")
+    }
+
+    /// How many pixels wide the text will be once drawn.
+    pub fn text_width(&self, text: &str) -> u16 {
+        text.chars().map(|c| u16::from(self.glyph(c).width)).sum()
+    }
+
+    pub fn line_height(&self) -> u16 {
+        TILE_SIZE.try_into().unwrap()
+    }
+
+    /// Cycles the glow colours, for any text drawn with `TextInk::Glow`.
+    pub fn glow_cycle(&self, frames_per_step: u16) -> ColorCycle {
+        let bank_start = usize::from(self.obj_palette_bank) * palette::BANK_SIZE;
+
+        ColorCycle {
+            kind: PaletteKind::Object,
+            start: bank_start + usize::try_from(self.obj_glow_index).unwrap(),
+            len: self.glow_len,
+            frames_per_step,
+        }
+    }
+
+    fn find_glyph(&self, character: char) -> Option<&Glyph> {
+        self.glyphs
+            .binary_search_by_key(&character, |(c, _)| *c)
+            .ok()
+            .map(|i| &self.glyphs[i].1)
+    }
+
+    fn obj_colors(&self, ink: TextInk) -> GlyphColors {
+        let ink = match ink {
+            TextInk::Normal => self.obj_ink_index,
+            TextInk::Dimmed => self.obj_dim_index,
+            TextInk::Red => self.obj_red_index,
+            TextInk::Yellow => self.obj_yellow_index,
+            TextInk::Glow => self.obj_glow_index,
+        };
+
+        GlyphColors {
+            ink,
+            shadow: self.obj_shadow_index,
+        }
+    }
+}
+
+impl TileCanvas {
+    fn width(&self) -> u16 {
+        (self.width_in_tiles * TILE_SIZE).try_into().unwrap()
+    }
+
+    fn clear(&self) {
+        for i in 0..self.width_in_tiles * self.height_in_tiles {
+            self.tiles.index(i).write([0; 8]);
+        }
+    }
+
+    /// Draw text with its top-left corner at the given position. Returns how wide it was.
+    fn draw_text(&self, x: u16, y: u16, text: &str) -> u16 {
+        let mut glyph_x = x;
+
+        for character in text.chars() {
+            let glyph = FONT.glyph(character);
+            self.draw_glyph(glyph_x, y, glyph);
+            glyph_x += u16::from(glyph.width);
+        }
+
+        glyph_x - x
+    }
+
+    fn draw_glyph(&self, x: u16, y: u16, glyph: &Glyph) {
+        let x = usize::from(x);
+        let y = usize::from(y);
+
+        for (row, glyph_row) in glyph.tile.iter().enumerate() {
+            let pixel_y = y + row;
+
+            if pixel_y >= self.height_in_tiles * TILE_SIZE {
+                break;
+            }
+
+            let (pixels, mask) = self.color_row(*glyph_row);
+
+            if mask == 0 {
+                continue;
+            }
+
+            // Each row of the glyph lands in at most two tiles, side by side.
+            let shift = (x % TILE_SIZE) * 4;
+            let pixels = u64::from(pixels) << shift;
+            let mask = u64::from(mask) << shift;
+
+            let tile_x = x / TILE_SIZE;
+            let tile_y = pixel_y / TILE_SIZE;
+
+            for (i, half) in [0, 32].into_iter().enumerate() {
+                let half_mask = (mask >> half) as u32;
+
+                if half_mask == 0 || tile_x + i >= self.width_in_tiles {
+                    continue;
+                }
+
+                let tile = tile_y * self.width_in_tiles + tile_x + i;
+                let half_pixels = (pixels >> half) as u32;
+
+                // Safety: the row is within the tile.
+                let address = unsafe {
+                    self.tiles
+                        .index(tile)
+                        .cast::<u32>()
+                        .add(pixel_y % TILE_SIZE)
+                };
+
+                address.write((address.read() & !half_mask) | half_pixels);
+            }
+        }
+    }
+
+    /// Swap the glyph's colour indices for the canvas's. Also returns a mask of the pixels
+    /// that aren't empty.
+    fn color_row(&self, glyph_row: u32) -> (u32, u32) {
+        let mut pixels = 0;
+        let mut mask = 0;
+
+        for i in 0..TILE_SIZE {
+            let shift = i * 4;
+
+            let color = match (glyph_row >> shift) & 0xf {
+                GLYPH_INK => self.colors.ink,
+                GLYPH_SHADOW => self.colors.shadow,
+                _ => continue,
+            };
+
+            pixels |= color << shift;
+            mask |= 0xf << shift;
+        }
+
+        (pixels, mask)
+    }
+}
+
+impl<'a> TextBox<'a> {
+    /// Set up a background layer to draw text into a box, given in tiles.
+    /// The rest of the layer is left transparent.
+    pub fn new(
+        gba: &'a GBA,
+        layer: BackgroundLayer,
+        tile_x: usize,
+        tile_y: usize,
+        width_in_tiles: usize,
+        height_in_tiles: usize,
+    ) -> Self {
+        let num_tiles = width_in_tiles * height_in_tiles;
+
+        let mut charblock_memory = gba
+            .charblock_memory
+            .request_memory(num_tiles + 1)
+            .expect("Out of charblock memory");
+
+        let screenblock_memory = gba
+            .screenblock_memory
+            .request_memory(1)
+            .expect("Out of screenblock memory.");
+
+        let palette_memory = gba
+            .bg_palette_memory
+            .request_aligned_memory(1, 16)
+            .expect("Out of BG palette memory.");
+
+        // Colour zero is always transparent, so the font's colours go after it.
+        let colors = GlyphColors {
+            ink: GLYPH_INK,
+            shadow: GLYPH_SHADOW,
+        };
+        palette::write_colors(
+            PaletteKind::Background,
+            palette_memory.get_start() + 1,
+            &[FONT.ink, FONT.shadow],
+        );
+
+        // The first tile is left blank for the rest of the layer, and the text starts after it.
+        let tiles = *charblock_memory.as_vol_region();
+        tiles.index(0).write([0; 8]);
+
+        let canvas = TileCanvas {
+            tiles: tiles.sub_slice(1..num_tiles + 1),
+            width_in_tiles,
+            height_in_tiles,
+            colors,
+        };
+        canvas.clear();
+
+        let text_box = Self {
+            charblock_memory,
+            screenblock_memory,
+            palette_memory,
+            layer,
+            canvas,
+        };
+
+        text_box.write_screenblock(tile_x, tile_y);
+        text_box.enable_background();
+
+        text_box
+    }
+
+    pub fn width(&self) -> u16 {
+        self.canvas.width()
+    }
+
+    pub fn clear(&mut self) {
+        self.canvas.clear();
+    }
+
+    /// Draw text with its top-left corner at the given position, relative to the box.
+    /// Returns how wide the text was.
+    pub fn write(&mut self, x: u16, y: u16, text: &str) -> u16 {
+        self.canvas.draw_text(x, y, text)
+    }
+
+    /// Draw text centred across the width of the box.
+    pub fn write_centered(&mut self, y: u16, text: &str) -> u16 {
+        let x = self.width().saturating_sub(FONT.text_width(text)) / 2;
+        self.write(x, y, text)
+    }
+
+    fn write_screenblock(&self, tile_x: usize, tile_y: usize) {
+        let charblock_start: u16 = self.charblock_memory.get_start().try_into().unwrap();
+        let palette_bank: u16 = (self.palette_memory.get_start() / 16).try_into().unwrap();
+
+        let screenblock = self.screenblock_memory.get_frame(0);
+
+        for row in 0..SCREENBLOCK_SIZE {
+            let row_mem = screenblock.get_row(row).unwrap();
+
+            for col in 0..SCREENBLOCK_SIZE {
+                let in_box = (tile_x..tile_x + self.canvas.width_in_tiles).contains(&col)
+                    && (tile_y..tile_y + self.canvas.height_in_tiles).contains(&row);
+
+                let tile = if in_box {
+                    (row - tile_y) * self.canvas.width_in_tiles + (col - tile_x) + 1
+                } else {
+                    0
+                };
+                let tile: u16 = tile.try_into().unwrap();
+
+                let text_entry = TextEntry::new()
+                    .with_tile(charblock_start + tile)
+                    .with_palbank(palette_bank);
+
+                row_mem.index(col).write(text_entry);
+            }
+        }
+    }
+
+    fn enable_background(&self) {
+        let screenblock_index: u16 = self.screenblock_memory.get_start().try_into().unwrap();
+
+        let bg_control = BackgroundControl::new()
+            .with_bpp8(false)
+            .with_screenblock(screenblock_index)
+            .with_charblock(constants::CHARBLOCK_BASE)
+            .with_priority(self.layer.get_priority());
+
+        self.layer.get_display_control_register().write(bg_control);
+        DISPCNT.write(self.layer.enable());
+
+        self.layer.get_horizontal_scroll_register().write(0);
+        self.layer.get_vertical_scroll_register().write(0);
+    }
+}
+
+impl<'a> Drop for TextBox<'a> {
+    fn drop(&mut self) {
+        DISPCNT.write(self.layer.disable());
+    }
+}
+
+impl<'a, const N: usize> TextSprite<'a, N> {
+    pub fn new(gba: &'a GBA) -> Self {
+        let mut memory = gba
+            .obj_tile_memory
+            .request_memory(N * SEGMENT_TILES)
+            .expect("Out of VRAM.");
+
+        let canvas = TileCanvas {
+            tiles: *memory.as_vol_region(),
+            width_in_tiles: N * SEGMENT_TILES,
+            height_in_tiles: 1,
+            colors: FONT.obj_colors(TextInk::Normal),
+        };
+        canvas.clear();
+
+        let tile_start = memory.get_start();
+
+        let objects = core::array::from_fn(|i| {
+            let mut entry = gba.shadow_oam.request_memory().expect("Out of OBJRAM");
+            let tile_id: u16 = (tile_start + i * SEGMENT_TILES).try_into().unwrap();
+
+            let oa = entry.get_obj_attr();
+            oa.0 = oa.0.with_bpp8(false).with_shape(ObjShape::Horizontal);
+            oa.1 = oa.1.with_size(SEGMENT_SIZE);
+            oa.2 =
+                oa.2.with_tile_id(tile_id)
+                    .with_palbank(FONT.obj_palette_bank);
+            oa.set_style(ObjDisplayStyle::NotDisplayed);
+
+            entry
+        });
+
+        Self {
+            _memory: memory,
+            objects,
+            canvas,
+            text_width: 0,
+            position: (0, 0),
+            visible: true,
+        }
+    }
+
+    /// Replace the text. Anything past the end of the objects is cut off.
+    pub fn set_text(&mut self, text: &str) {
+        self.canvas.clear();
+        self.text_width = self.canvas.draw_text(0, 0, text);
+        self.update_visibility();
+    }
+
+    /// Replace the text with several pieces drawn one after another, each in its own ink.
+    /// The sprite's own ink is left as it was.
+    pub fn set_text_parts(&mut self, parts: &[(&str, TextInk)]) {
+        let colors = self.canvas.colors;
+        let mut x = 0;
+
+        self.canvas.clear();
+
+        for (text, ink) in parts {
+            self.canvas.colors = FONT.obj_colors(*ink);
+            x += self.canvas.draw_text(x, 0, text);
+        }
+
+        self.canvas.colors = colors;
+        self.text_width = x;
+        self.update_visibility();
+    }
+
+    /// Draw the text greyed out. Takes effect the next time the text is set.
+    pub fn set_dimmed(&mut self, dimmed: bool) {
+        let ink = if dimmed {
+            TextInk::Dimmed
+        } else {
+            TextInk::Normal
+        };
+
+        self.set_ink(ink);
+    }
+
+    /// Takes effect the next time the text is set.
+    pub fn set_ink(&mut self, ink: TextInk) {
+        self.canvas.colors = FONT.obj_colors(ink);
+    }
+
+    /// How wide the current text is, in pixels.
+    pub fn width(&self) -> u16 {
+        self.text_width.min(self.canvas.width())
+    }
+
+    pub fn position(&self) -> (u16, u16) {
+        self.position
+    }
+
+    pub fn set_position(&mut self, x: u16, y: u16) {
+        self.position = (x, y);
+
+        for (i, object) in self.objects.iter_mut().enumerate() {
+            let offset: u16 = i.try_into().unwrap();
+            let oa = object.get_obj_attr();
+            oa.set_x(x + offset * SEGMENT_WIDTH);
+            oa.set_y(y);
+        }
+    }
+
+    pub fn set_visible(&mut self) {
+        self.visible = true;
+        self.update_visibility();
+    }
+
+    pub fn set_hidden(&mut self) {
+        self.visible = false;
+        self.update_visibility();
+    }
+
+    /// Only show the objects that have some of the text in them.
+    fn update_visibility(&mut self) {
+        let text_width = self.text_width;
+        let visible = self.visible;
+
+        for (i, object) in self.objects.iter_mut().enumerate() {
+            let start: u16 = i.try_into().unwrap();
+            let start = start * SEGMENT_WIDTH;
+
+            let style = if visible && start < text_width {
+                ObjDisplayStyle::Normal
+            } else {
+                ObjDisplayStyle::NotDisplayed
+            };
+
+            object.get_obj_attr().set_style(style);
+        }
+    }
+}
+
+impl<const N: usize> TextBuffer<N> {
+    pub const fn new() -> Self {
+        Self {
+            bytes: [0; N],
+            len: 0,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        // Only whole characters are ever pushed, so this is always valid.
+        core::str::from_utf8(&self.bytes[..self.len]).unwrap()
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+}
+
+impl<const N: usize> Default for TextBuffer<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> fmt::Write for TextBuffer<N> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for character in s.chars() {
+            let mut encoded = [0; 4];
+            let encoded = character.encode_utf8(&mut encoded).as_bytes();
+
+            if self.len + encoded.len() > N {
+                return Err(fmt::Error);
+            }
+
+            self.bytes[self.len..self.len + encoded.len()].copy_from_slice(encoded);
+            self.len += encoded.len();
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for FrameTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let seconds = self.0 / FRAMES_PER_SECOND;
+        write!(f, "{}:{:02}", seconds / 60, seconds % 60)
+    }
+}
+
+/// Lay out a row of items across a span, leaving equal gaps between them and at either end.
+/// Returns where each item starts. Items that don't fit are packed together from the left.
+pub fn spread_evenly(widths: &[u16], left: u16, span: u16) -> impl Iterator<Item = u16> + '_ {
+    let total_width: u16 = widths.iter().sum();
+    let num_gaps: u16 = (widths.len() + 1).try_into().unwrap();
+    let gap = span.saturating_sub(total_width) / num_gaps;
+
+    widths.iter().scan(left + gap, move |x, width| {
+        let start = *x;
+        *x += width + gap;
+        Some(start)
+    })
+}
+
+// Insert the font generated by the build script.
+include!(concat!(env!("OUT_DIR"), "/font_data.rs"));
@@ -1,17 +1,20 @@
 #![no_std]
 #![no_main]
 
-use audio::mixer;
-use graphics::sprite::OBJ_PALETTE;
+use graphics::{
+    palette::{self, PaletteKind},
+    sprite::{set_colour_blind_palette, OBJ_PALETTE},
+};
 
 use screens::ScreenState;
-use system::gba::GBA;
+use system::{gba::GBA, settings};
 
 pub mod audio;
 pub mod graphics;
 pub mod math;
 pub mod screens;
 pub mod system;
+pub mod ui;
 
 #[panic_handler]
 fn panic_handler(i: &core::panic::PanicInfo) -> ! {
@@ -30,21 +33,13 @@ fn panic_handler(i: &core::panic::PanicInfo) -> ! {
 extern "C" fn main() -> ! {
     let gba = GBA::take();
 
-    let mut palette_mem = gba
+    let palette_mem = gba
         .obj_palette_memory
         .request_memory(OBJ_PALETTE.len())
         .expect("Object palette cannot fit in memory.");
 
-    let palette_mem_region = palette_mem.as_vol_region();
-    palette_mem_region.write_from_slice(&OBJ_PALETTE);
-
-    let bgm = audio::mixer::AudioSource::new(
-        audio::assets::BACKGROUND_MUSIC,
-        audio::mixer::AudioVolume::new(45),
-        true,
-    );
-
-    mixer::set_channel_1(bgm);
+    palette::write_colors(PaletteKind::Object, palette_mem.get_start(), &OBJ_PALETTE);
+    set_colour_blind_palette(settings::current().colour_blind);
 
     // Top-level game loop just runs the currently active screen until it transitions.
     let mut screen_state = ScreenState::TitleScreen;
@@ -1,25 +1,62 @@
 use gba::prelude::VBlankIntrWait;
 
-use crate::{audio::mixer, system::gba::GBA};
+use crate::{
+    audio::{
+        assets::BACKGROUND_MUSIC,
+        mixer::{self, Music},
+        psg,
+        tracker::SERIES_OVER_SONG,
+    },
+    graphics::{
+        effects::transition::{Transition, TransitionDirection, TransitionPlayer},
+        palette::{self, FadeColor},
+    },
+    system::gba::GBA,
+};
 
 use self::{
+    character_select_screen::{CharacterSelectScreen, CharacterSelectScreenLoadedData},
     game_screen::{
         cpu_face::{CpuFace, CpuSprites},
+        cpu_personality::CpuPersonality,
         TokenColor,
     },
+    match_series::{MatchSeries, NextGameStart},
+    puzzles::PUZZLES,
+    series_over_screen::{SeriesOverScreen, SeriesOverScreenLoadedData},
+    settings_screen::{SettingsScreen, SettingsScreenLoadedData},
     spinner_screen::{SpinnerScreen, SpinnerScreenLoadedData},
 };
 
+pub mod character_select_screen;
 pub mod game_screen;
+pub mod match_series;
+pub mod puzzles;
+pub mod series_over_screen;
+pub mod settings_screen;
 pub mod spinner_screen;
 pub mod title_screen;
 
+// How long the music takes to change between screens.
+const MUSIC_CROSSFADE_FRAMES: u16 = 40;
+
+// How long each half of a transition takes, hiding one screen and then revealing the next.
+const FADE_FRAMES: u16 = 20;
+const MOSAIC_FRAMES: u16 = 16;
+const WINDOW_FRAMES: u16 = 24;
+
+#[derive(Clone, Copy)]
 pub enum ScreenState {
     TitleScreen,
-    VsCpuScreen(TokenColor),
-    VsCpuSpinnerScreen,
-    VsPlayerScreen(TokenColor),
-    VsPlayerSpinnerScreen,
+    CharacterSelectScreen(MatchSeries),
+    VsCpuScreen(TokenColor, MatchSeries, CpuPersonality),
+    VsCpuSpinnerScreen(MatchSeries, CpuPersonality),
+    VsCpuSeriesOverScreen(MatchSeries, CpuPersonality),
+    VsPlayerScreen(TokenColor, MatchSeries),
+    VsPlayerSpinnerScreen(MatchSeries),
+    VsPlayerSeriesOverScreen(MatchSeries),
+    PuzzleScreen(usize),
+    SettingsScreen,
 }
 
 pub trait Screen {
@@ -30,6 +67,8 @@ impl ScreenState {
     /// Run the game loop for the currently active screen.
     /// If a screen transition occurs, then the next screen ScreenState will be returned.
     pub fn exec_screen(&self, gba: &GBA) -> Self {
+        mixer::crossfade_music(self.music(), MUSIC_CROSSFADE_FRAMES);
+
         // Construct the required screen and run its loop until it transitions.
         match self {
             ScreenState::TitleScreen => {
@@ -38,33 +77,106 @@ impl ScreenState {
 
                 self.screen_loop(screen, gba)
             }
-            ScreenState::VsCpuScreen(starting_color) => {
-                let cpu_sprites = CpuSprites::new(gba);
+            ScreenState::CharacterSelectScreen(series) => {
+                let loaded_data = CharacterSelectScreenLoadedData::new(gba);
+                let screen = CharacterSelectScreen::new(gba, &loaded_data, *series);
+
+                self.screen_loop(screen, gba)
+            }
+            ScreenState::VsCpuScreen(starting_color, series, personality) => {
+                let cpu_sprites = CpuSprites::new(gba, *personality);
                 let cpu_face = CpuFace::new(gba, &cpu_sprites);
 
                 let red_agent = game_screen::Agent::new_human_agent();
                 let yellow_agent = game_screen::Agent::new_cpu_agent(cpu_face);
 
-                self.exec_game_screen(gba, red_agent, yellow_agent, *starting_color)
+                self.exec_game_screen(gba, red_agent, yellow_agent, *starting_color, *series)
             }
-            ScreenState::VsPlayerScreen(starting_color) => {
+            ScreenState::VsPlayerScreen(starting_color, series) => {
                 let red_agent = game_screen::Agent::new_human_agent();
                 let yellow_agent = game_screen::Agent::new_human_agent();
 
-                self.exec_game_screen(gba, red_agent, yellow_agent, *starting_color)
+                self.exec_game_screen(gba, red_agent, yellow_agent, *starting_color, *series)
+            }
+            ScreenState::VsCpuSpinnerScreen(series, personality) => self.exec_spinner_screen(
+                gba,
+                spinner_screen::SpinnerMode::VsCpu(*personality),
+                *series,
+            ),
+            ScreenState::VsPlayerSpinnerScreen(series) => {
+                self.exec_spinner_screen(gba, spinner_screen::SpinnerMode::VsPlayer, *series)
+            }
+            ScreenState::VsCpuSeriesOverScreen(series, personality) => self
+                .exec_series_over_screen(
+                    gba,
+                    series_over_screen::SeriesOverMode::VsCpu(*personality),
+                    *series,
+                ),
+            ScreenState::VsPlayerSeriesOverScreen(series) => self.exec_series_over_screen(
+                gba,
+                series_over_screen::SeriesOverMode::VsPlayer,
+                *series,
+            ),
+            ScreenState::PuzzleScreen(puzzle_index) => self.exec_puzzle_screen(gba, *puzzle_index),
+            ScreenState::SettingsScreen => {
+                let loaded_data = SettingsScreenLoadedData::new(gba);
+                let screen = SettingsScreen::new(gba, &loaded_data);
+
+                self.screen_loop(screen, gba)
+            }
+        }
+    }
+
+    /// The music for each screen. Screens that share music keep it playing across the transition.
+    fn music(&self) -> Music {
+        match self {
+            ScreenState::VsCpuSeriesOverScreen(..) | ScreenState::VsPlayerSeriesOverScreen(_) => {
+                Music::Song(&SERIES_OVER_SONG)
             }
-            ScreenState::VsCpuSpinnerScreen => {
-                self.exec_spinner_screen(gba, spinner_screen::SpinnerMode::VsCpu)
+            _ => Music::Sound(&BACKGROUND_MUSIC),
+        }
+    }
+
+    /// How to move from this screen on to the next one.
+    fn transition_to(&self, next: &ScreenState) -> Transition {
+        match (self, next) {
+            (_, ScreenState::SettingsScreen) | (ScreenState::SettingsScreen, _) => {
+                Transition::Wipe(WINDOW_FRAMES)
             }
-            ScreenState::VsPlayerSpinnerScreen => {
-                self.exec_spinner_screen(gba, spinner_screen::SpinnerMode::VsPlayer)
+            (_, ScreenState::VsCpuSpinnerScreen(..) | ScreenState::VsPlayerSpinnerScreen(_)) => {
+                Transition::Mosaic(MOSAIC_FRAMES)
             }
+            (
+                ScreenState::VsCpuSpinnerScreen(..) | ScreenState::VsPlayerSpinnerScreen(_),
+                ScreenState::VsCpuScreen(..) | ScreenState::VsPlayerScreen(..),
+            ) => Transition::Iris(WINDOW_FRAMES),
+            (
+                _,
+                ScreenState::VsCpuSeriesOverScreen(..) | ScreenState::VsPlayerSeriesOverScreen(_),
+            ) => Transition::Fade(FadeColor::White, FADE_FRAMES),
+            _ => Transition::Fade(FadeColor::Black, FADE_FRAMES),
         }
     }
 
-    pub fn exec_spinner_screen(&self, gba: &GBA, mode: spinner_screen::SpinnerMode) -> ScreenState {
-        let loaded_data = SpinnerScreenLoadedData::new(gba, &mode);
-        let screen = SpinnerScreen::new(gba, &loaded_data, mode);
+    pub fn exec_spinner_screen(
+        &self,
+        gba: &GBA,
+        mode: spinner_screen::SpinnerMode,
+        series: MatchSeries,
+    ) -> ScreenState {
+        let loaded_data = SpinnerScreenLoadedData::new(gba);
+        let screen = SpinnerScreen::new(gba, &loaded_data, mode, series);
+        self.screen_loop(screen, gba)
+    }
+
+    pub fn exec_series_over_screen(
+        &self,
+        gba: &GBA,
+        mode: series_over_screen::SeriesOverMode,
+        series: MatchSeries,
+    ) -> ScreenState {
+        let loaded_data = SeriesOverScreenLoadedData::new(gba, &mode, &series);
+        let screen = SeriesOverScreen::new(gba, &loaded_data, series);
         self.screen_loop(screen, gba)
     }
 
@@ -74,6 +186,7 @@ impl ScreenState {
         red_agent: game_screen::Agent,
         yellow_agent: game_screen::Agent,
         starting_color: TokenColor,
+        series: MatchSeries,
     ) -> ScreenState {
         let loaded_data = game_screen::GameScreenLoadedData::new(gba);
         let screen = game_screen::GameScreen::new(
@@ -82,33 +195,93 @@ impl ScreenState {
             red_agent,
             yellow_agent,
             starting_color,
-        );
+            series,
+        )
+        .with_suspended_game();
+
+        self.screen_loop(screen, gba)
+    }
+
+    pub fn exec_puzzle_screen(&self, gba: &GBA, puzzle_index: usize) -> ScreenState {
+        let puzzle = &PUZZLES[puzzle_index];
+
+        let cpu_sprites = CpuSprites::new(gba, CpuPersonality::Defensive);
+        let cpu_face = CpuFace::new(gba, &cpu_sprites);
+
+        let player_agent = game_screen::Agent::new_human_agent();
+        let defender_agent = game_screen::Agent::new_defender_agent(cpu_face, puzzle.moves);
+
+        let (red_agent, yellow_agent) = match puzzle.to_move {
+            TokenColor::Red => (player_agent, defender_agent),
+            TokenColor::Yellow => (defender_agent, player_agent),
+        };
+
+        let loaded_data = game_screen::GameScreenLoadedData::new(gba);
+        let screen = game_screen::GameScreen::new(
+            gba,
+            &loaded_data,
+            red_agent,
+            yellow_agent,
+            puzzle.to_move,
+            MatchSeries::new(1, NextGameStart::Spinner),
+        )
+        .with_puzzle(&loaded_data, puzzle_index)
+        .with_suspended_game();
 
         self.screen_loop(screen, gba)
     }
 
     fn screen_loop<S: Screen>(&self, mut screen: S, gba: &GBA) -> ScreenState {
+        let mut transition_in = TransitionPlayer::incoming(gba);
+
         loop {
             self.process_vblank(gba);
             let next_state = screen.update();
+            transition_in.update();
 
             // Break out of the loop when a transition happens.
             if let Some(state) = next_state {
-                self.clear_screen(screen, gba);
+                transition_in.cancel();
+
+                let transition_out = TransitionPlayer::new(
+                    gba,
+                    self.transition_to(&state),
+                    TransitionDirection::Out,
+                );
+                self.clear_screen(screen, transition_out, gba);
                 return state;
             }
         }
     }
 
-    fn clear_screen<S: Screen>(&self, screen: S, gba: &GBA) {
+    fn clear_screen<S: Screen>(&self, mut screen: S, mut transition: TransitionPlayer, gba: &GBA) {
+        // Keep the screen running while the transition hides it.
+        // It's already moving on, so it's given no input, and any other state it asks for is ignored.
+        // Otherwise it could still change settings or play a move that nobody sees saved.
+        gba.set_input_enabled(false);
+        while !transition.is_finished() {
+            self.process_vblank(gba);
+            let _ = screen.update();
+            transition.update();
+        }
+        gba.set_input_enabled(true);
+
         // Drop the screen, to drop all the OAM memory and hide all the objects.
         drop(screen);
+
+        // Dropping the screen may have undone some of the transition, so put it back.
+        transition.apply();
         self.process_vblank(gba);
     }
 
     fn process_vblank(&self, gba: &GBA) {
         VBlankIntrWait();
-        unsafe { gba.shadow_oam.sync() }
+        unsafe {
+            gba.shadow_oam.sync();
+            palette::commit_palettes();
+        }
         mixer::fill_next_buffer();
+        psg::update_psg();
+        palette::update_palettes();
     }
 }
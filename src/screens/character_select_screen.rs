@@ -0,0 +1,227 @@
+use crate::{
+    audio::{events::SoundEvent, noise::play_menu_move_noise},
+    graphics::{
+        background::{BackgroundLayer, LoadedBackground, SCROLLER_BACKGROUND},
+        effects::{
+            background_scroller::BackgroundScroller,
+            scanline::{ScanlineBand, ScanlineEffect, ScanlineTarget},
+        },
+        sprite::{AnimationController, LoadedAnimation, LoadedSprite, MENU_CURSOR_ANIMATION},
+        text::TextSprite,
+    },
+    system::{
+        constants::{SCREEN_HEIGHT, SCREEN_WIDTH},
+        gba::{GbaKey, GBA},
+    },
+};
+
+use super::{
+    game_screen::{
+        cpu_face::{CpuEmotion, CpuFace, CpuFaceSprites, CpuSprites},
+        cpu_personality::{CpuPersonality, CPU_PERSONALITIES, NUM_CPU_PERSONALITIES},
+    },
+    match_series::MatchSeries,
+    Screen, ScreenState,
+};
+
+// The heads are laid out in a 2x2 grid.
+const GRID_COLUMNS: usize = 2;
+const HEAD_SPACING: u16 = 16;
+const CURSOR_X_OFFSET: u16 = 10;
+
+// The highlighted CPU's name is shown this far below the grid.
+const NAME_SPACING: u16 = 8;
+// Enough 32 pixel wide objects for the longest translation of any name.
+const NAME_OBJECTS: usize = 3;
+
+// Start on the original CPU.
+const INITIAL_SELECTION: usize = 1;
+
+// The background is split into bands that drift sideways at different speeds, as if they were
+// layers at different depths. Each is the first line of the band and how many pixels it moves
+// every PARALLAX_DIVISOR frames.
+const PARALLAX_BANDS: [(u16, u16); 3] = [(0, 1), (56, 2), (112, 3)];
+// A power of two, so the bands carry on smoothly when the frame counter wraps around.
+const PARALLAX_DIVISOR: u16 = 4;
+
+// Only the heads differ between the personalities, so the faces are loaded once for all of them.
+pub struct CharacterSelectScreenLoadedData<'a> {
+    cpu_head_sprites: [LoadedSprite<'a>; NUM_CPU_PERSONALITIES],
+    cpu_face_sprites: CpuFaceSprites<'a>,
+    cursor_animation: LoadedAnimation<'a, 5>,
+}
+
+/// Lets the player choose which CPU personality to play against.
+/// The highlighted CPU pulls its signature face, while the others sulk.
+pub struct CharacterSelectScreen<'a> {
+    gba: &'a GBA,
+    background_scroller: BackgroundScroller,
+    scrolling_background: LoadedBackground<'a>,
+    parallax: ScanlineEffect,
+    parallax_frame: u16,
+    cpu_faces: [CpuFace<'a>; NUM_CPU_PERSONALITIES],
+    name_text: TextSprite<'a, NAME_OBJECTS>,
+    cursor_animation_controller: AnimationController<'a, 5>,
+    selection: usize,
+    series: MatchSeries,
+}
+
+impl<'a> CharacterSelectScreenLoadedData<'a> {
+    pub fn new(gba: &'a GBA) -> Self {
+        Self {
+            cpu_head_sprites: CPU_PERSONALITIES
+                .map(|personality| personality.head_sprite().load(gba)),
+            cpu_face_sprites: CpuFaceSprites::new(gba),
+            cursor_animation: MENU_CURSOR_ANIMATION.load(gba),
+        }
+    }
+}
+
+impl<'a> CharacterSelectScreen<'a> {
+    pub fn new(
+        gba: &'a GBA,
+        loaded_data: &'a CharacterSelectScreenLoadedData<'a>,
+        series: MatchSeries,
+    ) -> Self {
+        let scrolling_background = SCROLLER_BACKGROUND.load(gba, BackgroundLayer::Bg0);
+        let background_scroller = BackgroundScroller::new(0, 1).with_divisor(2);
+        let parallax = ScanlineEffect::new(ScanlineTarget::HorizontalScroll(BackgroundLayer::Bg0));
+
+        let cpu_faces = core::array::from_fn(|i| {
+            let mut cpu_face = CpuFace::with_head(
+                gba,
+                CPU_PERSONALITIES[i],
+                &loaded_data.cpu_head_sprites[i],
+                &loaded_data.cpu_face_sprites,
+            );
+            let (x, y) = get_head_position(i);
+            cpu_face.set_x(x);
+            cpu_face.set_y(y);
+            cpu_face
+        });
+
+        let mut screen = Self {
+            gba,
+            background_scroller,
+            scrolling_background,
+            parallax,
+            parallax_frame: 0,
+            cpu_faces,
+            name_text: TextSprite::new(gba),
+            cursor_animation_controller: loaded_data.cursor_animation.create_controller(gba),
+            selection: INITIAL_SELECTION,
+            series,
+        };
+
+        screen.update_selection();
+        screen
+    }
+
+    fn move_selection(&mut self) {
+        let column = self.selection % GRID_COLUMNS;
+        let row = self.selection / GRID_COLUMNS;
+        let num_rows = NUM_CPU_PERSONALITIES / GRID_COLUMNS;
+
+        let (column, row) = if self.gba.key_was_pressed(GbaKey::LEFT)
+            || self.gba.key_was_pressed(GbaKey::RIGHT)
+        {
+            ((column + 1) % GRID_COLUMNS, row)
+        } else if self.gba.key_was_pressed(GbaKey::UP) || self.gba.key_was_pressed(GbaKey::DOWN) {
+            (column, (row + 1) % num_rows)
+        } else {
+            return;
+        };
+
+        play_menu_move_noise();
+        self.selection = row * GRID_COLUMNS + column;
+        self.update_selection();
+    }
+
+    fn update_selection(&mut self) {
+        for (i, cpu_face) in self.cpu_faces.iter_mut().enumerate() {
+            if i == self.selection {
+                cpu_face.set_emotion(cpu_face.personality().signature_emotion());
+            } else {
+                cpu_face.set_emotion(CpuEmotion::Sad);
+            }
+        }
+
+        let (head_x, head_y) = get_head_position(self.selection);
+        let head_height: u16 = CpuSprites::height().try_into().unwrap();
+
+        let cursor_oa = self
+            .cursor_animation_controller
+            .get_obj_attr_entry()
+            .get_obj_attr_data();
+        cursor_oa.set_x(head_x - CURSOR_X_OFFSET);
+        cursor_oa.set_y(head_y + head_height / 2);
+
+        let (_, last_row_y) = get_head_position(NUM_CPU_PERSONALITIES - 1);
+        self.name_text
+            .set_text(self.selected_personality().name().get());
+        self.name_text.set_position(
+            (SCREEN_WIDTH - self.name_text.width()) / 2,
+            last_row_y + head_height + NAME_SPACING,
+        );
+    }
+
+    fn update_parallax(&mut self) {
+        self.parallax_frame = self.parallax_frame.wrapping_add(1);
+
+        let distance = self.parallax_frame / PARALLAX_DIVISOR;
+        let bands = PARALLAX_BANDS.map(|(first_line, speed)| ScanlineBand {
+            first_line,
+            value: distance.wrapping_mul(speed),
+        });
+        self.parallax.set_bands(&bands);
+    }
+
+    fn selected_personality(&self) -> CpuPersonality {
+        CPU_PERSONALITIES[self.selection]
+    }
+}
+
+impl<'a> Screen for CharacterSelectScreen<'a> {
+    fn update(&mut self) -> Option<ScreenState> {
+        self.background_scroller.update();
+        self.background_scroller
+            .apply_vertical_to_background(&self.scrolling_background);
+        self.update_parallax();
+
+        self.move_selection();
+        self.cursor_animation_controller.tick();
+
+        if self.gba.key_was_pressed(GbaKey::A) || self.gba.key_was_pressed(GbaKey::START) {
+            SoundEvent::Confirm.play();
+            Some(ScreenState::VsCpuSpinnerScreen(
+                self.series,
+                self.selected_personality(),
+            ))
+        } else if self.gba.key_was_pressed(GbaKey::B) {
+            SoundEvent::Cancel.play();
+            Some(ScreenState::TitleScreen)
+        } else {
+            None
+        }
+    }
+}
+
+/// The top-left corner of the head in the given grid slot, with the grid centred on screen.
+fn get_head_position(index: usize) -> (u16, u16) {
+    let head_width: u16 = CpuSprites::width().try_into().unwrap();
+    let head_height: u16 = CpuSprites::height().try_into().unwrap();
+
+    let grid_columns: u16 = GRID_COLUMNS.try_into().unwrap();
+    let grid_rows: u16 = (NUM_CPU_PERSONALITIES / GRID_COLUMNS).try_into().unwrap();
+
+    let grid_width = grid_columns * (head_width + HEAD_SPACING) - HEAD_SPACING;
+    let grid_height = grid_rows * (head_height + HEAD_SPACING) - HEAD_SPACING;
+
+    let column: u16 = (index % GRID_COLUMNS).try_into().unwrap();
+    let row: u16 = (index / GRID_COLUMNS).try_into().unwrap();
+
+    let x = (SCREEN_WIDTH - grid_width) / 2 + column * (head_width + HEAD_SPACING);
+    let y = (SCREEN_HEIGHT - grid_height) / 2 + row * (head_height + HEAD_SPACING);
+
+    (x, y)
+}
@@ -1,10 +1,17 @@
 use core::cmp::min;
 
+use self::analysis::{GameAnalysis, MoveHistory, MoveRecord};
 use self::cpu_face::CpuFace;
+use self::cpu_personality::CpuEvent;
+use self::hints::{HintOverlay, HintSearch};
+use self::scoreboard::Scoreboard;
 
+use super::match_series::MatchSeries;
+use super::puzzles::{self, Puzzle, PuzzleProgress, PUZZLES};
 use super::{Screen, ScreenState};
 use crate::audio::assets::BOUNCE_NOISE;
-use crate::audio::mixer::{self, AudioSource, AudioVolume};
+use crate::audio::events::SoundEvent;
+use crate::audio::mixer::{self, AudioSource, Pan, PlaybackRate, SfxPriority};
 use crate::graphics::background::{
     BackgroundLayer, LoadedBackground, BOARD_BACKGROUND, CLOUDS_CLOSE_BACKGROUND,
     CLOUDS_FAR_BACKGROUND,
@@ -12,25 +19,38 @@ use crate::graphics::background::{
 use crate::graphics::effects::background_scroller::BackgroundScroller;
 use crate::graphics::effects::blending::BlendController;
 use crate::graphics::effects::blinker::Blinker;
+use crate::graphics::effects::scanline::{ScanlineEffect, ScanlineTarget};
+use crate::graphics::effects::window::{Window, WindowController, WindowLayers, WindowRect};
+use crate::graphics::palette::{self, FadeColor, PaletteKind, Tint};
 use crate::graphics::sprite::{
-    AnimationController, LoadedAnimation, LoadedObjectEntry, LoadedSprite, BOARD_SLOT_SPRITE,
-    CPU_TEXT_SPRITE, DRAW_TEXT_SPRITE, MENU_CURSOR_ANIMATION, MENU_CURSOR_FRAME_0_SPRITE,
-    P1_TEXT_SPRITE, P2_TEXT_SPRITE, QUIT_TEXT_SPRITE, RED_TOKEN_ANIMATION, REMATCH_TEXT_SPRITE,
-    WINS_TEXT_RED_SPRITE, WINS_TEXT_YELLOW_SPRITE, YELLOW_TOKEN_ANIMATION,
+    Animation, AnimationController, LoadedAnimation, LoadedObjectEntry, LoadedSprite, Sprite,
+    BOARD_SLOT_SPRITE, MENU_CURSOR_ANIMATION, RED_TOKEN_ANIMATION, RED_TOKEN_FRAME_0_SPRITE,
+    RED_TOKEN_MARKED_ANIMATION, RED_TOKEN_MARKED_FRAME_0_SPRITE, YELLOW_TOKEN_ANIMATION,
+    YELLOW_TOKEN_FRAME_0_SPRITE, YELLOW_TOKEN_MARKED_ANIMATION, YELLOW_TOKEN_MARKED_FRAME_0_SPRITE,
 };
+use crate::graphics::text::{TextInk, TextSprite};
 use crate::system::constants::{SCREEN_CENTER, SCREEN_WIDTH};
 use crate::system::gba::GbaKey;
+use crate::system::settings::{self, BoardVariant};
+use crate::system::strings::Message;
 use crate::system::{constants::BOARD_SLOTS, gba::GBA};
-use cpu_turn::CpuTurn;
+use crate::ui::menu::{Menu, MenuEntry, MenuEvent, MenuLayout};
+use cpu_turn::{CpuStrategy, CpuTurn};
 use game_board::WinningPositions;
-use gba::video::{BlendControl, ColorEffectMode};
+use gba::video::{BlendControl, Color, ColorEffectMode};
 use player_turn::PlayerTurn;
+use solver::{Position, SearchBudget};
 
+mod analysis;
 pub mod cpu_face;
+pub mod cpu_personality;
 mod cpu_turn;
 mod cursor;
 mod game_board;
+mod hints;
 mod player_turn;
+mod scoreboard;
+mod solver;
 
 const TOKEN_DROP_TOP_SPEED: i16 = 15;
 const TOKEN_DROP_SPEED_GRADIENT: i16 = 1;
@@ -38,15 +58,49 @@ const TOKEN_DROP_STARTING_SPEED: i16 = 1;
 
 const TOKEN_BOUNCE_SPEED_DECAY: i16 = 2;
 
+// Tokens landing at top speed play the bounce noise at its normal pitch, slower impacts lower it.
+const BOUNCE_PITCH_OFFSET: i16 = 24;
+
 const WINNING_TOKEN_BLINK_TIME_ON: u32 = 22;
 const WINNING_TOKEN_BLINK_TIME_OFF: u32 = 8;
 
-const WIN_TEXT_WORD_SPACING: u16 = 4;
 const WIN_TEXT_YPOS: u16 = 5;
 
-const GAME_OVER_MENU_YPOS: u16 = 26;
-const MENU_TEXT_HORIZ_MARGIN: u16 = 10;
-const CURSOR_X_OFFSET: u16 = 10;
+// A win flashes the screen part of the way to white, then fades back.
+const WIN_FLASH_LEVEL: u16 = 10;
+const WIN_FLASH_FRAMES: u16 = 6;
+
+// Enough 32 pixel wide objects for the longest translation of the result.
+const RESULT_TEXT_OBJECTS: usize = 4;
+
+const MENU_YPOS: u16 = 26;
+// The menu entries are spread across this much of the screen, keeping clear of the scoreboards.
+const MENU_WIDTH: u16 = 176;
+const MENU_ENTRIES: usize = 3;
+
+// How strongly the backgrounds are tinted for the later times of day, out of MAX_BLEND.
+const DUSK_TINT_AMOUNT: u16 = 5;
+const NIGHT_TINT_AMOUNT: u16 = 8;
+
+// The sky darkens towards the top of the screen, down from the backdrop colour at the bottom.
+const SKY_TOP_COLOR: Color = Color::from_rgb(6, 12, 26);
+
+// Just below the menu.
+const REVIEW_PROMPT_YPOS: u16 = 38;
+const REVIEW_PROMPT_OBJECTS: usize = 4;
+
+// Enough 32 pixel wide objects for the longest translation of the prompt.
+const PRESS_A_OBJECTS: usize = 4;
+const PRESS_A_BLINK_TIME_ON: u32 = 40;
+const PRESS_A_BLINK_TIME_OFF: u32 = 10;
+
+// How far the board is darkened around a spotlight, in sixteenths.
+const SPOTLIGHT_DIMNESS: u8 = 8;
+// How far the other columns are darkened while a player chooses their move.
+const COLUMN_DIMNESS: u8 = 3;
+
+const REVIEW_BLINK_TIME_ON: u32 = 22;
+const REVIEW_BLINK_TIME_OFF: u32 = 8;
 
 pub enum Agent<'a> {
     Human(PlayerTurn),
@@ -65,22 +119,68 @@ struct Winner {
     blinker: Blinker,
 }
 
+#[derive(Clone)]
+struct Mistake {
+    token_position: usize,
+    blinker: Blinker,
+}
+
 #[derive(Clone)]
 enum GameOutcome {
     Winner(Winner),
     Draw,
+    PuzzleFailed(Mistake),
 }
 
-#[derive(Clone)]
-enum CursorPosition {
+#[derive(Clone, Copy, PartialEq)]
+enum MenuChoice {
     Rematch,
+    NextPuzzle,
+    Resume,
+    Settings,
     Quit,
 }
 
+static GAME_OVER_MENU: [MenuEntry<MenuChoice>; 2] = [
+    MenuEntry::new(Message::Rematch, MenuChoice::Rematch),
+    MenuEntry::new(Message::Quit, MenuChoice::Quit).with_sound(Some(SoundEvent::Cancel)),
+];
+
+// The next puzzle is only enabled once this one has been solved.
+static PUZZLE_OVER_MENU: [MenuEntry<MenuChoice>; MENU_ENTRIES] = [
+    MenuEntry::new(Message::Retry, MenuChoice::Rematch),
+    MenuEntry::new(Message::Next, MenuChoice::NextPuzzle),
+    MenuEntry::new(Message::Quit, MenuChoice::Quit).with_sound(Some(SoundEvent::Cancel)),
+];
+
+// Opened with START during a turn.
+static PAUSE_MENU: [MenuEntry<MenuChoice>; MENU_ENTRIES] = [
+    MenuEntry::new(Message::Resume, MenuChoice::Resume),
+    MenuEntry::new(Message::Settings, MenuChoice::Settings),
+    MenuEntry::new(Message::Quit, MenuChoice::Quit).with_sound(Some(SoundEvent::Cancel)),
+];
+
+/// A game left for the settings screen from the pause menu, to be carried on once it closes.
+static mut SUSPENDED_GAME: Option<SuspendedGame> = None;
+
+struct SuspendedGame {
+    screen_state: ScreenState,
+    moves: MoveHistory,
+}
+
 #[derive(Clone)]
 struct GameOverState {
     outcome: GameOutcome,
-    cursor_position: CursorPosition,
+    analysis: GameAnalysis,
+}
+
+/// Steps through the blunders found by the post-game analysis. The board is shown as it was
+/// just after each blunder, with the blundered token blinking and the better move suggested.
+#[derive(Clone)]
+struct ReviewState {
+    game_over_state: GameOverState,
+    blunder_index: usize,
+    blinker: Blinker,
 }
 
 #[derive(Clone)]
@@ -93,13 +193,24 @@ struct TokenDroppingState {
     obj_index: usize,
     speed: i16,
     num_bounces: i16,
+    creates_threat: bool,
 }
 
 #[derive(Clone)]
 enum GameState {
     TurnState(TokenColor),
+    Paused(TokenColor),
     TokenDropping(TokenDroppingState),
     GameOver(GameOverState),
+    Review(ReviewState),
+}
+
+/// Tracks the player's progress through a puzzle.
+struct PuzzleAttempt {
+    puzzle_index: usize,
+    puzzle: &'static Puzzle,
+    moves_used: u8,
+    solved: bool,
 }
 
 pub struct GameScreen<'a> {
@@ -109,70 +220,51 @@ pub struct GameScreen<'a> {
     _board_slot_objects: [LoadedObjectEntry<'a>; BOARD_SLOTS],
     game_state: GameState,
     game_board: game_board::GameBoard<'a>,
-    _background: LoadedBackground<'a>,
+    background: LoadedBackground<'a>,
     red_agent: Agent<'a>,
     yellow_agent: Agent<'a>,
-    p1_text_object: LoadedObjectEntry<'a>,
-    p2_text_object: LoadedObjectEntry<'a>,
-    cpu_text_object: LoadedObjectEntry<'a>,
-    red_wins_text_object: LoadedObjectEntry<'a>,
-    yellow_wins_text_object: LoadedObjectEntry<'a>,
-    draw_text_object: LoadedObjectEntry<'a>,
+    result_text: TextSprite<'a, RESULT_TEXT_OBJECTS>,
+    review_prompt: TextSprite<'a, REVIEW_PROMPT_OBJECTS>,
     clouds_background_close: LoadedBackground<'a>,
     clouds_background_far: LoadedBackground<'a>,
-    _blend_controller: BlendController,
+    blend_controller: BlendController,
+    sky: ScanlineEffect,
+    window_controller: WindowController,
+    spotlight_masks: [Option<LoadedObjectEntry<'a>>; 4],
+    dimmed_column: Option<usize>,
     cloud_scroller_close: BackgroundScroller,
     cloud_scroller_far: BackgroundScroller,
-    rematch_text_object: LoadedObjectEntry<'a>,
-    quit_text_object: LoadedObjectEntry<'a>,
-    menu_cursor_animation_controller: AnimationController<'a, 5>,
+    menu: Menu<'a, MenuChoice, MENU_ENTRIES>,
+    press_a_text: TextSprite<'a, PRESS_A_OBJECTS>,
+    press_a_blinker: Blinker,
+    starting_color: TokenColor,
+    series: MatchSeries,
+    scoreboard: Option<Scoreboard<'a>>,
+    puzzle: Option<PuzzleAttempt>,
+    hint_overlay: HintOverlay<'a>,
+    move_history: MoveHistory,
+    initial_position: Position,
 }
 
 pub struct GameScreenLoadedData<'a> {
     red_token_animation: LoadedAnimation<'a, 4>,
     yellow_token_animation: LoadedAnimation<'a, 4>,
     board_slot_sprite: LoadedSprite<'a>,
-    p1_text_sprite: LoadedSprite<'a>,
-    p2_text_sprite: LoadedSprite<'a>,
-    cpu_text_sprite: LoadedSprite<'a>,
-    red_wins_text_sprite: LoadedSprite<'a>,
-    yellow_wins_text_sprite: LoadedSprite<'a>,
-    draw_text_sprite: LoadedSprite<'a>,
-    rematch_text_sprite: LoadedSprite<'a>,
-    quit_text_sprite: LoadedSprite<'a>,
     menu_cursor_animation: LoadedAnimation<'a, 5>,
 }
 
 impl<'a> GameScreenLoadedData<'a> {
     pub fn new(gba: &'a GBA) -> Self {
-        let yellow_token_animation = YELLOW_TOKEN_ANIMATION.load(gba);
-        let red_token_animation = RED_TOKEN_ANIMATION.load(gba);
+        let yellow_token_animation = TokenColor::Yellow.animation().load(gba);
+        let red_token_animation = TokenColor::Red.animation().load(gba);
         let board_slot_sprite = BOARD_SLOT_SPRITE.load(gba);
 
-        let p1_text_sprite = P1_TEXT_SPRITE.load(gba);
-        let p2_text_sprite = P2_TEXT_SPRITE.load(gba);
-        let cpu_text_sprite = CPU_TEXT_SPRITE.load(gba);
-        let red_wins_text_sprite = WINS_TEXT_RED_SPRITE.load(gba);
-        let yellow_wins_text_sprite = WINS_TEXT_YELLOW_SPRITE.load(gba);
-
-        let draw_text_sprite = DRAW_TEXT_SPRITE.load(gba);
-
-        let rematch_text_sprite = REMATCH_TEXT_SPRITE.load(gba);
-        let quit_text_sprite = QUIT_TEXT_SPRITE.load(gba);
         let menu_cursor_animation = MENU_CURSOR_ANIMATION.load(gba);
 
         Self {
             yellow_token_animation,
             red_token_animation,
             board_slot_sprite,
-            p1_text_sprite,
-            p2_text_sprite,
-            cpu_text_sprite,
-            red_wins_text_sprite,
-            yellow_wins_text_sprite,
-            draw_text_sprite,
-            rematch_text_sprite,
-            quit_text_sprite,
             menu_cursor_animation,
         }
     }
@@ -187,6 +279,7 @@ impl<'a> Agent<'a> {
         animation_controller: &mut AnimationController<4>,
         game_board: &mut game_board::GameBoard,
         opponent: &mut Agent,
+        hint_overlay: &mut HintOverlay,
     ) -> Option<usize> {
         match self {
             Self::Cpu(ref mut face, ref mut turn) => {
@@ -198,7 +291,14 @@ impl<'a> Agent<'a> {
                     Agent::Cpu(ref mut face, _) => Some(face),
                 };
 
-                turn.update(gba, token_color, animation_controller, game_board, cpu_face)
+                turn.update(
+                    gba,
+                    token_color,
+                    animation_controller,
+                    game_board,
+                    cpu_face,
+                    hint_overlay,
+                )
             }
         }
     }
@@ -208,7 +308,16 @@ impl<'a> Agent<'a> {
     }
 
     pub fn new_cpu_agent(cpu_face: CpuFace<'a>) -> Self {
-        Self::Cpu(cpu_face, CpuTurn::new())
+        let strategy = CpuStrategy::Heuristic(cpu_face.personality());
+        Self::Cpu(cpu_face, CpuTurn::with_strategy(strategy))
+    }
+
+    /// A CPU that never makes a mistake when defending against a forced win of up to `moves` moves.
+    pub fn new_defender_agent(cpu_face: CpuFace<'a>, moves: u8) -> Self {
+        Self::Cpu(
+            cpu_face,
+            CpuTurn::with_strategy(CpuStrategy::Defender(moves)),
+        )
     }
 }
 
@@ -219,6 +328,7 @@ impl<'a> GameScreen<'a> {
         red_agent: Agent<'a>,
         yellow_agent: Agent<'a>,
         starting_color: TokenColor,
+        series: MatchSeries,
     ) -> Self {
         let mut red_token_animation_controller =
             loaded_data.red_token_animation.create_controller(gba);
@@ -241,62 +351,73 @@ impl<'a> GameScreen<'a> {
             loaded_data.yellow_token_animation.get_frame(0),
         );
 
-        let p1_text_object = loaded_data
-            .p1_text_sprite
-            .create_obj_attr_entry(gba)
-            .with_hidden();
-        let p2_text_object = loaded_data
-            .p2_text_sprite
-            .create_obj_attr_entry(gba)
-            .with_hidden();
-        let cpu_text_object = loaded_data
-            .cpu_text_sprite
-            .create_obj_attr_entry(gba)
-            .with_hidden();
-        let red_wins_text_object = loaded_data
-            .red_wins_text_sprite
-            .create_obj_attr_entry(gba)
-            .with_hidden();
-        let yellow_wins_text_object = loaded_data
-            .yellow_wins_text_sprite
-            .create_obj_attr_entry(gba)
-            .with_hidden();
-
-        let draw_text_object = loaded_data
-            .draw_text_sprite
-            .create_obj_attr_entry(gba)
-            .with_hidden();
-
-        let _background = BOARD_BACKGROUND.load(gba, BackgroundLayer::Bg0);
+        let mut result_text = TextSprite::new(gba);
+        result_text.set_hidden();
+
+        let mut review_prompt = TextSprite::new(gba);
+        review_prompt.set_text(Message::Review.get());
+        review_prompt.set_position(
+            (SCREEN_WIDTH - review_prompt.width()) / 2,
+            REVIEW_PROMPT_YPOS,
+        );
+        review_prompt.set_hidden();
+
+        let background = BOARD_BACKGROUND.load(gba, BackgroundLayer::Bg0);
         let clouds_background_far = CLOUDS_FAR_BACKGROUND.load(gba, BackgroundLayer::Bg1);
         let clouds_background_close = CLOUDS_CLOSE_BACKGROUND.load(gba, BackgroundLayer::Bg2);
+
+        // The backgrounds are tinted for the time of day the board is set at.
+        let tint = board_tint(settings::current().board_variant);
+        for background in [
+            &background,
+            &clouds_background_far,
+            &clouds_background_close,
+        ] {
+            palette::set_tint(PaletteKind::Background, background.get_palette_bank(), tint);
+        }
         let mut blend_controller = BlendController::new();
+        apply_cloud_blend(&mut blend_controller);
 
-        // Target 1 is on top of Target 2
-        blend_controller.update(
-            BlendControl::new()
-                .with_mode(ColorEffectMode::AlphaBlend)
-                .with_target2_bg0(true)
-                .with_target1_bg1(true)
-                .with_target1_bg2(true),
-            [25, 7].into(),
-        );
+        let sky = ScanlineEffect::new(ScanlineTarget::Backdrop);
 
         let cloud_scroller_close = BackgroundScroller::new(1, 0).with_divisor(5);
         let cloud_scroller_far = BackgroundScroller::new(1, 0).with_divisor(8);
 
-        let rematch_text_object = loaded_data
-            .rematch_text_sprite
-            .create_obj_attr_entry(gba)
-            .with_hidden();
-        let quit_text_object = loaded_data
-            .quit_text_sprite
-            .create_obj_attr_entry(gba)
-            .with_hidden();
-        let mut menu_cursor_animation_controller =
-            loaded_data.menu_cursor_animation.create_controller(gba);
+        // Spread across the middle of the screen, keeping clear of the scoreboards.
+        let menu = Menu::new(
+            gba,
+            &loaded_data.menu_cursor_animation,
+            &GAME_OVER_MENU,
+            MenuLayout::Horizontal {
+                x: SCREEN_CENTER.0 - MENU_WIDTH / 2,
+                y: MENU_YPOS,
+                width: MENU_WIDTH,
+            },
+        );
 
-        menu_cursor_animation_controller.set_hidden();
+        let mut press_a_text = TextSprite::new(gba);
+        press_a_text.set_text(Message::PressA.get());
+        press_a_text.set_position((SCREEN_WIDTH - press_a_text.width()) / 2, MENU_YPOS);
+        press_a_text.set_hidden();
+
+        // The scoreboard is only needed when more than one game is being played.
+        let scoreboard = if series.is_single_game() {
+            None
+        } else {
+            let mut scoreboard = Scoreboard::new(
+                gba,
+                loaded_data.red_token_animation.get_frame(0),
+                loaded_data.yellow_token_animation.get_frame(0),
+            );
+            scoreboard.update(&series);
+            Some(scoreboard)
+        };
+
+        let hint_overlay = HintOverlay::new(
+            gba,
+            loaded_data.red_token_animation.get_frame(0),
+            loaded_data.yellow_token_animation.get_frame(0),
+        );
 
         Self {
             gba,
@@ -305,26 +426,172 @@ impl<'a> GameScreen<'a> {
             _board_slot_objects,
             game_state,
             game_board,
-            _background,
+            background,
             red_agent,
             yellow_agent,
-            p1_text_object,
-            p2_text_object,
-            cpu_text_object,
-            red_wins_text_object,
-            yellow_wins_text_object,
-            draw_text_object,
+            result_text,
+            review_prompt,
             clouds_background_close,
             clouds_background_far,
             cloud_scroller_close,
             cloud_scroller_far,
-            rematch_text_object,
-            quit_text_object,
-            menu_cursor_animation_controller,
-            _blend_controller: blend_controller,
+            menu,
+            press_a_text,
+            press_a_blinker: Blinker::new(PRESS_A_BLINK_TIME_ON, PRESS_A_BLINK_TIME_OFF, true),
+            starting_color,
+            series,
+            scoreboard,
+            puzzle: None,
+            hint_overlay,
+            move_history: MoveHistory::new(),
+            initial_position: Position::empty(),
+            blend_controller,
+            sky,
+            window_controller: WindowController::new(),
+            spotlight_masks: Default::default(),
+            dimmed_column: None,
         }
     }
 
+    /// Set the board up for a puzzle. The agents and starting color should already match the puzzle.
+    pub fn with_puzzle(
+        mut self,
+        loaded_data: &'a GameScreenLoadedData<'a>,
+        puzzle_index: usize,
+    ) -> Self {
+        let puzzle = &PUZZLES[puzzle_index];
+
+        for (column, tokens) in puzzle.columns.iter().enumerate() {
+            for (row, token_color) in tokens.iter().enumerate() {
+                let obj_index = self.game_board.set_cell(*token_color, column, row);
+                let y_pos = self.game_board.get_token_ypos_for_row(row);
+
+                if let Some(obj) = self.game_board.get_token_obj_entry_mut(obj_index) {
+                    obj.get_obj_attr_data().set_y(y_pos);
+                }
+            }
+        }
+
+        // The scoreboard counts down the player's remaining moves.
+        self.scoreboard = Some(Scoreboard::new(
+            self.gba,
+            loaded_data.red_token_animation.get_frame(0),
+            loaded_data.yellow_token_animation.get_frame(0),
+        ));
+
+        self.puzzle = Some(PuzzleAttempt {
+            puzzle_index,
+            puzzle,
+            moves_used: 0,
+            solved: false,
+        });
+
+        self.initial_position = solver::position_from_board(&self.game_board, puzzle.to_move);
+        self.update_puzzle_scoreboard();
+        self.hint_overlay
+            .set_search(HintSearch::ForcedWin(puzzle.moves));
+
+        self
+    }
+
+    /// Carry on with the game that was left for the settings screen, if there is one.
+    /// The puzzle, if any, should already be set up.
+    pub fn with_suspended_game(mut self) -> Self {
+        let suspended = match unsafe { SUSPENDED_GAME.take() } {
+            Some(suspended) => suspended,
+            None => return self,
+        };
+
+        for i in 0..suspended.moves.num_moves() {
+            let move_record = suspended.moves.get(i).unwrap();
+            self.place_token(move_record.token_color, move_record.column);
+        }
+
+        if let Some(last_move) = suspended.moves.num_moves().checked_sub(1) {
+            let last_color = suspended.moves.get(last_move).unwrap().token_color;
+            self.game_state = GameState::TurnState(last_color.opposite());
+        }
+
+        self
+    }
+
+    /// Put a token straight into the next free row of a column, without dropping it.
+    fn place_token(&mut self, token_color: TokenColor, column: usize) {
+        let row = self
+            .game_board
+            .get_next_free_row(column)
+            .expect("No more rows!");
+        let obj_index = self.game_board.set_cell(token_color, column, row);
+        let y_pos = self.game_board.get_token_ypos_for_row(row);
+
+        if let Some(obj) = self.game_board.get_token_obj_entry_mut(obj_index) {
+            obj.get_obj_attr_data().set_y(y_pos);
+        }
+
+        self.record_puzzle_move(token_color);
+        self.move_history.push(MoveRecord {
+            token_color,
+            column,
+            obj_index,
+        });
+    }
+
+    /// Stop the game with the pause menu over it. The turn carries on where it was once resumed.
+    fn pause(&mut self, token_color: TokenColor) -> GameState {
+        SoundEvent::Confirm.play();
+
+        // The menu covers where the token is held over the board.
+        self.red_token_animation_controller.set_hidden();
+        self.yellow_token_animation_controller.set_hidden();
+        self.hint_overlay.hide();
+
+        self.menu.set_entries(&PAUSE_MENU);
+        self.menu.show();
+
+        GameState::Paused(token_color)
+    }
+
+    /// Returns the next screen if the game is being left. Resuming just hides the menu.
+    fn update_pause_menu(&mut self) -> Option<ScreenState> {
+        if self.gba.key_was_pressed(GbaKey::B) {
+            SoundEvent::Cancel.play();
+            self.menu.hide();
+            return None;
+        }
+
+        match self.menu.update() {
+            Some(MenuEvent::Selected(MenuChoice::Settings)) => Some(self.suspend()),
+            Some(MenuEvent::Selected(MenuChoice::Quit)) => Some(ScreenState::TitleScreen),
+            Some(MenuEvent::Selected(_)) => {
+                self.menu.hide();
+                None
+            }
+            Some(MenuEvent::Cancelled) | None => None,
+        }
+    }
+
+    /// Leave the game for the settings screen, which comes back to it once it closes.
+    fn suspend(&self) -> ScreenState {
+        let screen_state = match (&self.puzzle, &self.yellow_agent) {
+            (Some(puzzle), _) => ScreenState::PuzzleScreen(puzzle.puzzle_index),
+            (None, Agent::Cpu(face, _)) => {
+                ScreenState::VsCpuScreen(self.starting_color, self.series, face.personality())
+            }
+            (None, Agent::Human(_)) => {
+                ScreenState::VsPlayerScreen(self.starting_color, self.series)
+            }
+        };
+
+        unsafe {
+            SUSPENDED_GAME = Some(SuspendedGame {
+                screen_state,
+                moves: self.move_history.clone(),
+            });
+        }
+
+        ScreenState::SettingsScreen
+    }
+
     fn get_state(&self) -> GameState {
         self.game_state.clone()
     }
@@ -349,14 +616,26 @@ impl<'a> GameScreen<'a> {
             animation_controller,
             &mut self.game_board,
             opponent,
+            &mut self.hint_overlay,
         );
 
         if let Some(column) = column {
+            self.record_puzzle_move(token_color);
+
             let row = self.game_board.get_next_free_row(column);
 
             match row {
                 Some(row) => {
+                    let had_threat = self.game_board.player_has_winning_move(token_color);
                     let obj_index = self.game_board.set_cell(token_color, column, row);
+                    let creates_threat =
+                        !had_threat && self.game_board.player_has_winning_move(token_color);
+
+                    self.move_history.push(MoveRecord {
+                        token_color,
+                        column,
+                        obj_index,
+                    });
                     let y_pos = game_board::get_token_y_position();
 
                     let drop_state = TokenDroppingState {
@@ -368,8 +647,11 @@ impl<'a> GameScreen<'a> {
                         speed: TOKEN_DROP_STARTING_SPEED,
                         target_y: self.game_board.get_token_ypos_for_row(row),
                         num_bounces: 0,
+                        creates_threat,
                     };
 
+                    self.clip_drop(column);
+
                     Some(GameState::TokenDropping(drop_state))
                 }
                 None => {
@@ -399,6 +681,8 @@ impl<'a> GameScreen<'a> {
             let bounce_speed = -(state.speed / TOKEN_BOUNCE_SPEED_DECAY);
 
             if bounce_speed.abs() == 1 {
+                self.clear_spotlight();
+
                 // Bouncing animation has
                 // Turn is over now.
                 // Check victory conditions, otherwise move to next player's turn.
@@ -415,17 +699,32 @@ impl<'a> GameScreen<'a> {
                         Some(new_state)
                     }
                     None => {
-                        if self.game_board.is_full() {
+                        if let Some(failed_state) = self.check_puzzle_move(state) {
+                            Some(failed_state)
+                        } else if self.game_board.is_full() {
                             Some(self.get_draw_game_state())
                         } else {
+                            if state.creates_threat {
+                                self.play_player_sound(
+                                    state.token_color,
+                                    SoundEvent::ThreatCreated,
+                                );
+                            }
                             Some(GameState::TurnState(state.token_color.opposite()))
                         }
                     }
                 }
             } else {
                 // noise::play_impact_noise();
-                let sound = AudioSource::new(BOUNCE_NOISE, AudioVolume::new(10), false);
-                mixer::set_channel_2(sound);
+                let impact_speed: u32 = (BOUNCE_PITCH_OFFSET + state.speed).try_into().unwrap();
+                let top_speed: u32 = (BOUNCE_PITCH_OFFSET + TOKEN_DROP_TOP_SPEED)
+                    .try_into()
+                    .unwrap();
+                let token_x = game_board::get_token_x_position(state.column);
+                let sound = AudioSource::from_asset(&BOUNCE_NOISE)
+                    .with_rate(PlaybackRate::from_ratio(impact_speed, top_speed))
+                    .with_pan(Pan::from_screen_x(token_x));
+                mixer::play_sfx(sound, SfxPriority::Normal);
 
                 state.num_bounces += 1;
                 state.speed = bounce_speed;
@@ -449,6 +748,11 @@ impl<'a> GameScreen<'a> {
     }
 
     fn update_game_over(&mut self, game_over_state: &mut GameOverState) -> Option<ScreenState> {
+        // Once the win flash has peaked, fade back from it.
+        if !palette::is_fading() && palette::fade_level() > 0 {
+            palette::fade_in(WIN_FLASH_FRAMES);
+        }
+
         if let GameOutcome::Winner(ref mut winner) = &mut game_over_state.outcome {
             winner.blinker.update();
             for i in winner.token_positions {
@@ -457,40 +761,449 @@ impl<'a> GameScreen<'a> {
             }
         }
 
-        if self.gba.key_was_pressed(GbaKey::A) || self.gba.key_was_pressed(GbaKey::START) {
-            return match game_over_state.cursor_position {
-                CursorPosition::Quit => Some(ScreenState::TitleScreen),
-                CursorPosition::Rematch => match self.yellow_agent {
-                    Agent::Cpu(_, _) => Some(ScreenState::VsCpuSpinnerScreen),
-                    Agent::Human(_) => Some(ScreenState::VsPlayerSpinnerScreen),
-                },
+        if let GameOutcome::PuzzleFailed(ref mut mistake) = &mut game_over_state.outcome {
+            mistake.blinker.update();
+            let token_obj = self
+                .game_board
+                .get_token_obj_entry_mut(mistake.token_position)
+                .as_mut()
+                .unwrap();
+            mistake.blinker.apply_to_object(token_obj);
+        }
+
+        // Once the series has been decided there is no menu, just a prompt to continue.
+        if self.series.winner().is_some() {
+            self.press_a_blinker.update();
+            self.press_a_blinker.apply_to_text(&mut self.press_a_text);
+
+            if self.gba.key_was_pressed(GbaKey::A) || self.gba.key_was_pressed(GbaKey::START) {
+                SoundEvent::Confirm.play();
+                return Some(match self.yellow_agent {
+                    Agent::Cpu(ref face, _) => {
+                        ScreenState::VsCpuSeriesOverScreen(self.series, face.personality())
+                    }
+                    Agent::Human(_) => ScreenState::VsPlayerSeriesOverScreen(self.series),
+                });
+            }
+
+            return None;
+        }
+
+        match self.menu.update() {
+            Some(MenuEvent::Selected(MenuChoice::Rematch)) => {
+                Some(self.get_next_game_screen_state())
+            }
+            Some(MenuEvent::Selected(MenuChoice::NextPuzzle)) => {
+                let puzzle_index = self.puzzle.as_ref()?.puzzle_index;
+                Some(ScreenState::PuzzleScreen(puzzles::next_puzzle_index(
+                    puzzle_index,
+                )))
+            }
+            Some(MenuEvent::Selected(MenuChoice::Quit)) => Some(ScreenState::TitleScreen),
+            // Only in the pause menu.
+            Some(MenuEvent::Selected(MenuChoice::Resume | MenuChoice::Settings)) => None,
+            Some(MenuEvent::Cancelled) | None => None,
+        }
+    }
+
+    fn get_next_game_screen_state(&self) -> ScreenState {
+        // A rematch of a puzzle is another try at it.
+        if let Some(ref puzzle) = self.puzzle {
+            return ScreenState::PuzzleScreen(puzzle.puzzle_index);
+        }
+
+        let series = self.series;
+
+        match (&self.yellow_agent, series.next_starting_color()) {
+            (Agent::Cpu(face, _), Some(color)) => {
+                ScreenState::VsCpuScreen(color, series, face.personality())
+            }
+            (Agent::Cpu(face, _), None) => {
+                ScreenState::VsCpuSpinnerScreen(series, face.personality())
+            }
+            (Agent::Human(_), Some(color)) => ScreenState::VsPlayerScreen(color, series),
+            (Agent::Human(_), None) => ScreenState::VsPlayerSpinnerScreen(series),
+        }
+    }
+
+    fn record_game_result(&mut self, winner: Option<TokenColor>) {
+        if let Some(ref mut puzzle) = self.puzzle {
+            if winner == Some(puzzle.puzzle.to_move) {
+                puzzle.solved = true;
+                PuzzleProgress::load().mark_solved(puzzle.puzzle_index);
+            }
+
+            return;
+        }
+
+        if self.series.is_single_game() {
+            return;
+        }
+
+        self.series.record_result(winner, self.starting_color);
+
+        if let Some(ref mut scoreboard) = self.scoreboard {
+            scoreboard.update(&self.series);
+        }
+    }
+
+    fn update_puzzle_scoreboard(&mut self) {
+        if let (Some(puzzle), Some(scoreboard)) = (&self.puzzle, &mut self.scoreboard) {
+            let remaining_moves = puzzle.puzzle.moves - puzzle.moves_used;
+
+            match puzzle.puzzle.to_move {
+                TokenColor::Red => scoreboard.set_counts(remaining_moves, 0),
+                TokenColor::Yellow => scoreboard.set_counts(0, remaining_moves),
+            }
+        }
+    }
+
+    /// L/R switch puzzles until the player has made their first move.
+    fn update_puzzle_input(&self, token_color: TokenColor) -> Option<ScreenState> {
+        let puzzle = self.puzzle.as_ref()?;
+
+        if token_color != puzzle.puzzle.to_move || puzzle.moves_used > 0 {
+            return None;
+        }
+
+        if self.gba.key_was_pressed(GbaKey::L) {
+            let puzzle_index = puzzles::previous_puzzle_index(puzzle.puzzle_index);
+            Some(ScreenState::PuzzleScreen(puzzle_index))
+        } else if self.gba.key_was_pressed(GbaKey::R) {
+            let puzzle_index = puzzles::next_puzzle_index(puzzle.puzzle_index);
+            Some(ScreenState::PuzzleScreen(puzzle_index))
+        } else {
+            None
+        }
+    }
+
+    fn record_puzzle_move(&mut self, token_color: TokenColor) {
+        if let Some(ref mut puzzle) = self.puzzle {
+            if token_color == puzzle.puzzle.to_move {
+                puzzle.moves_used += 1;
+            }
+
+            let remaining_moves = puzzle.puzzle.moves - puzzle.moves_used;
+            self.hint_overlay
+                .set_search(HintSearch::ForcedWin(remaining_moves));
+        }
+
+        self.update_puzzle_scoreboard();
+    }
+
+    /// After the player's move in a puzzle, make sure they still have a forced win in the moves left.
+    /// If not, the defender can hold out and the attempt has failed.
+    fn check_puzzle_move(&mut self, state: &TokenDroppingState) -> Option<GameState> {
+        let puzzle = self.puzzle.as_ref()?;
+
+        if state.token_color != puzzle.puzzle.to_move {
+            return None;
+        }
+
+        let remaining_moves = puzzle.puzzle.moves - puzzle.moves_used;
+        let position = solver::position_from_board(&self.game_board, state.token_color.opposite());
+        let mut budget = SearchBudget::per_search();
+
+        // The build checks that this fits in the budget for every puzzle, but if it ever ran out,
+        // the player would get the benefit of the doubt.
+        if solver::opponent_is_lost(&position, remaining_moves, &mut budget)
+            || budget.is_exhausted()
+        {
+            None
+        } else {
+            Some(self.get_puzzle_failed_state(state.obj_index))
+        }
+    }
+
+    fn get_puzzle_failed_state(&mut self, token_position: usize) -> GameState {
+        // The defender has escaped, so it gets to gloat.
+        for color in [TokenColor::Yellow, TokenColor::Red] {
+            if let Agent::Cpu(ref mut face, _) = self.get_agent(color) {
+                face.set_emotion(cpu_face::CpuEmotion::Happy)
+            }
+        }
+
+        SoundEvent::Lose.play();
+
+        // Blink the token that let the win slip away.
+        let outcome = GameOutcome::PuzzleFailed(Mistake {
+            token_position,
+            blinker: Blinker::new(
+                WINNING_TOKEN_BLINK_TIME_ON,
+                WINNING_TOKEN_BLINK_TIME_OFF,
+                false,
+            ),
+        });
+
+        let game_over_state = GameOverState {
+            outcome,
+            analysis: self.new_game_analysis(),
+        };
+
+        self.spotlight_outcome(&game_over_state.outcome);
+        self.init_game_over_menu();
+        self.menu.select(MenuChoice::Rematch);
+
+        GameState::GameOver(game_over_state)
+    }
+
+    /// Only the human players' moves are analysed.
+    fn new_game_analysis(&self) -> GameAnalysis {
+        let cpu_color = match (&self.red_agent, &self.yellow_agent) {
+            (Agent::Cpu(_, _), _) => Some(TokenColor::Red),
+            (_, Agent::Cpu(_, _)) => Some(TokenColor::Yellow),
+            _ => None,
+        };
+
+        GameAnalysis::new(self.initial_position, cpu_color)
+    }
+
+    /// Run the post-game analysis a move at a time. Once it has finished,
+    /// SELECT opens the review if any blunders were found, which a prompt points out.
+    fn update_analysis(&mut self, game_over_state: &mut GameOverState) -> Option<GameState> {
+        let analysis = &mut game_over_state.analysis;
+
+        if !analysis.is_finished(&self.move_history) {
+            analysis.analyse_next_move(&self.move_history);
+            return None;
+        }
+
+        if analysis.num_blunders() == 0 {
+            return None;
+        }
+
+        self.review_prompt.set_visible();
+
+        if self.gba.key_was_pressed(GbaKey::SELECT) {
+            let review_state = ReviewState {
+                game_over_state: game_over_state.clone(),
+                blunder_index: 0,
+                blinker: Blinker::new(REVIEW_BLINK_TIME_ON, REVIEW_BLINK_TIME_OFF, false),
             };
+
+            self.enter_review(&review_state);
+
+            Some(GameState::Review(review_state))
+        } else {
+            None
         }
+    }
+
+    fn enter_review(&mut self, review_state: &ReviewState) {
+        self.menu.hide();
+        self.press_a_text.set_hidden();
+        self.review_prompt.set_hidden();
+
+        self.show_review_blunder(review_state);
+    }
+
+    fn update_review(&mut self, review_state: &mut ReviewState) -> Option<GameState> {
+        if self.gba.key_was_pressed(GbaKey::B) || self.gba.key_was_pressed(GbaKey::SELECT) {
+            return Some(self.exit_review(review_state));
+        }
+
+        let num_blunders = review_state.game_over_state.analysis.num_blunders();
 
-        // Update the menu position.
-        if self.gba.key_was_pressed(GbaKey::LEFT) || self.gba.key_was_pressed(GbaKey::RIGHT) {
-            game_over_state.cursor_position = game_over_state.cursor_position.next();
+        if self.gba.key_was_pressed(GbaKey::LEFT) {
+            review_state.blunder_index =
+                (review_state.blunder_index + num_blunders - 1) % num_blunders;
+            self.show_review_blunder(review_state);
+        } else if self.gba.key_was_pressed(GbaKey::RIGHT) {
+            review_state.blunder_index = (review_state.blunder_index + 1) % num_blunders;
+            self.show_review_blunder(review_state);
         }
 
-        self.update_cursor_object(&game_over_state.cursor_position);
+        let blunder = review_state
+            .game_over_state
+            .analysis
+            .get_blunder(review_state.blunder_index)
+            .unwrap();
+        let move_record = self.move_history.get(blunder.move_index).unwrap();
+
+        review_state.blinker.update();
+        let token_obj = self
+            .game_board
+            .get_token_obj_entry_mut(move_record.obj_index)
+            .as_mut()
+            .unwrap();
+        review_state.blinker.apply_to_object(token_obj);
+
+        self.hint_overlay.update();
 
         None
     }
 
-    fn update_cursor_object(&mut self, position: &CursorPosition) {
-        let target_obj = match position {
-            CursorPosition::Rematch => self.rematch_text_object.get_obj_attr_data(),
-            CursorPosition::Quit => self.quit_text_object.get_obj_attr_data(),
+    fn show_review_blunder(&mut self, review_state: &ReviewState) {
+        let blunder = review_state
+            .game_over_state
+            .analysis
+            .get_blunder(review_state.blunder_index)
+            .unwrap();
+
+        self.show_board_after_move(blunder.move_index);
+        self.hint_overlay.show_move(
+            blunder.token_color,
+            &self.game_board,
+            blunder.better_column,
+            blunder.better_row,
+        );
+        self.spotlight(
+            game_board::get_column_rect(blunder.better_column),
+            SPOTLIGHT_DIMNESS,
+        );
+    }
+
+    /// Draw attention to the tokens that decided the game.
+    fn spotlight_outcome(&mut self, outcome: &GameOutcome) {
+        match outcome {
+            GameOutcome::Winner(winner) => self.spotlight_tokens(&winner.token_positions),
+            GameOutcome::PuzzleFailed(mistake) => {
+                self.spotlight_tokens(&[mistake.token_position]);
+            }
+            GameOutcome::Draw => self.clear_spotlight(),
+        }
+    }
+
+    /// Darken the board, apart from the rectangle, which is also cleared of clouds.
+    fn spotlight(&mut self, rect: WindowRect, dimness: u8) {
+        self.darken(dimness);
+
+        let clear_view = WindowLayers::ALL
+            .with_background(BackgroundLayer::Bg1, false)
+            .with_background(BackgroundLayer::Bg2, false)
+            .with_effects(false);
+
+        // Only the board is darkened, leaving the text and scoreboards above and beside it.
+        self.spotlight_masks = Default::default();
+        self.window_controller.clear_obj_window();
+        self.window_controller
+            .set_window(Window::Win0, rect, clear_view);
+        self.window_controller.set_window(
+            Window::Win1,
+            game_board::get_board_rect(),
+            WindowLayers::ALL,
+        );
+        self.window_controller
+            .set_outside(WindowLayers::ALL.with_effects(false));
+    }
+
+    /// Darken everything below the text, apart from the tokens in the cells, which are also cleared of clouds.
+    /// The tokens are cut out by their shape in the OBJ window, so a diagonal line doesn't light up its neighbours.
+    fn spotlight_tokens(&mut self, cell_indices: &[usize]) {
+        self.darken(SPOTLIGHT_DIMNESS);
+
+        let masks = core::array::from_fn(|i| {
+            let cell_index = *cell_indices.get(i)?;
+            self.game_board.create_window_mask(cell_index)
+        });
+        self.spotlight_masks = masks;
+
+        let clear_view = WindowLayers::ALL
+            .with_background(BackgroundLayer::Bg1, false)
+            .with_background(BackgroundLayer::Bg2, false)
+            .with_effects(false);
+
+        // The OBJ window is outranked by the other two, so the board has to be left outside of them,
+        // which darkens the scenery beside it as well.
+        self.window_controller.set_obj_window(clear_view);
+        self.window_controller.set_window(
+            Window::Win0,
+            game_board::get_above_board_rect(),
+            WindowLayers::ALL.with_effects(false),
+        );
+        self.window_controller.clear_window(Window::Win1);
+        self.window_controller.set_outside(WindowLayers::ALL);
+    }
+
+    /// While a player chooses their move, dim all of the board but the column they're pointing at.
+    fn update_column_dimming(&mut self, token_color: TokenColor) {
+        let column = match self.get_agent(token_color) {
+            Agent::Human(player_turn) => Some(player_turn.get_column()),
+            Agent::Cpu(..) => None,
         };
 
-        let target_obj_x = target_obj.1.x();
-        let cursor_x = target_obj_x - CURSOR_X_OFFSET;
+        if column == self.dimmed_column {
+            return;
+        }
+
+        match column {
+            Some(column) => self.spotlight(game_board::get_column_rect(column), COLUMN_DIMNESS),
+            None => self.clear_spotlight(),
+        }
+        self.dimmed_column = column;
+    }
+
+    /// Hide the dropping token until it reaches the top of the board, so it looks like it's dropped in.
+    fn clip_drop(&mut self, column: usize) {
+        self.clear_spotlight();
+        self.window_controller.set_window(
+            Window::Win0,
+            game_board::get_above_column_rect(column),
+            WindowLayers::ALL.with_objects(false),
+        );
+        self.window_controller.set_outside(WindowLayers::ALL);
+    }
+
+    fn darken(&mut self, dimness: u8) {
+        // Fading replaces the clouds' blending until the spotlight is cleared.
+        self.blend_controller.update_fade(
+            BlendControl::new()
+                .with_mode(ColorEffectMode::Darken)
+                .with_target1_bg0(true)
+                .with_target1_bg1(true)
+                .with_target1_bg2(true)
+                .with_target1_obj(true)
+                .with_target1_backdrop(true),
+            dimness,
+        );
+    }
+
+    /// Shade the sky behind the board, following the board's tint and any fade.
+    fn update_sky(&mut self) {
+        let bank = self.background.get_palette_bank();
+        let horizon = palette::get_color(PaletteKind::Background, 0);
+
+        self.sky.set_gradient(
+            palette::apply_effects(PaletteKind::Background, bank, SKY_TOP_COLOR),
+            palette::apply_effects(PaletteKind::Background, bank, horizon),
+        );
+    }
+
+    fn clear_spotlight(&mut self) {
+        self.spotlight_masks = Default::default();
+        self.dimmed_column = None;
+        self.window_controller.clear();
+        apply_cloud_blend(&mut self.blend_controller);
+    }
+
+    fn exit_review(&mut self, review_state: &ReviewState) -> GameState {
+        self.hint_overlay.hide();
+        self.spotlight_outcome(&review_state.game_over_state.outcome);
+        self.show_board_after_move(self.move_history.num_moves());
+        self.init_game_over_menu();
+
+        GameState::GameOver(review_state.game_over_state.clone())
+    }
+
+    /// Show the board as it was after the given move.
+    /// Tokens that were on the board before the first move (in puzzles) are always shown.
+    fn show_board_after_move(&mut self, move_index: usize) {
+        for i in 0..BOARD_SLOTS {
+            if let Some(obj) = self.game_board.get_token_obj_entry_mut(i) {
+                obj.set_visible();
+            }
+        }
+
+        for i in (move_index + 1)..self.move_history.num_moves() {
+            let move_record = self.move_history.get(i).unwrap();
 
-        let cursor_obj = self.menu_cursor_animation_controller.get_obj_attr_entry();
-        cursor_obj.set_visible();
-        let cursor_oa = cursor_obj.get_obj_attr_data();
-        cursor_oa.set_x(cursor_x);
-        self.menu_cursor_animation_controller.tick();
+            if let Some(obj) = self
+                .game_board
+                .get_token_obj_entry_mut(move_record.obj_index)
+            {
+                obj.set_hidden();
+            }
+        }
     }
 
     fn get_agent<'b>(&'b mut self, token_color: TokenColor) -> &'b mut Agent<'a> {
@@ -500,6 +1213,14 @@ impl<'a> GameScreen<'a> {
         }
     }
 
+    /// Play a sound caused by one of the players, from the CPU's head if it was the CPU.
+    fn play_player_sound(&mut self, token_color: TokenColor, event: SoundEvent) {
+        match self.get_agent(token_color) {
+            Agent::Cpu(ref face, _) => event.play_panned(face.pan()),
+            Agent::Human(_) => event.play(),
+        }
+    }
+
     fn get_player_winning_state(
         &mut self,
         winning_color: TokenColor,
@@ -516,131 +1237,163 @@ impl<'a> GameScreen<'a> {
             blinker,
         });
 
-        // If the losing player is a CPU, then he becomes sad :(
+        palette::start_fade(FadeColor::White, WIN_FLASH_LEVEL, WIN_FLASH_FRAMES);
+
+        // Losing to the CPU gets a sadder sound than a player winning.
+        let winning_agent = self.get_agent(winning_color);
+        if let Agent::Cpu(_, _) = winning_agent {
+            self.play_player_sound(winning_color, SoundEvent::Lose);
+        } else {
+            SoundEvent::Win.play();
+        }
+
+        // If the losing player is a CPU, then it reacts to losing.
         let losing_color = winning_color.opposite();
         let losing_agent = self.get_agent(losing_color);
         if let Agent::Cpu(ref mut cpu_face, _) = losing_agent {
-            cpu_face.set_emotion(cpu_face::CpuEmotion::Sad);
+            cpu_face.react(CpuEvent::LostGame);
         }
 
         // Add the "{Player} Wins" banner.
-        let winning_player_obj = if winning_color == TokenColor::Red {
-            &mut self.p1_text_object
+        let winning_player_name = if winning_color == TokenColor::Red {
+            Message::Player1
         } else {
             let winning_agent = self.get_agent(winning_color);
             if let Agent::Human(_) = winning_agent {
-                &mut self.p2_text_object
+                Message::Player2
             } else {
-                &mut self.cpu_text_object
+                Message::Cpu
             }
         };
 
-        let wins_text_obj = match winning_color {
-            TokenColor::Red => &mut self.red_wins_text_object,
-            TokenColor::Yellow => &mut self.yellow_wins_text_object,
-        };
-
-        let player_name_width: u16 = winning_player_obj
-            .loaded_sprite()
-            .sprite()
-            .width()
-            .try_into()
-            .unwrap();
-        let wins_text_width: u16 = wins_text_obj
-            .loaded_sprite()
-            .sprite()
-            .width()
-            .try_into()
-            .unwrap();
-        let total_width = player_name_width + wins_text_width + WIN_TEXT_WORD_SPACING;
-
-        let player_text_xpos = SCREEN_WIDTH / 2 - total_width / 2;
-        let oa = winning_player_obj.get_obj_attr_data();
-        oa.set_x(player_text_xpos);
-        oa.set_y(WIN_TEXT_YPOS);
-        winning_player_obj.set_visible();
-
-        let wins_text_xpos = player_text_xpos + player_name_width + WIN_TEXT_WORD_SPACING;
-        let oa = wins_text_obj.get_obj_attr_data();
-        oa.set_x(wins_text_xpos);
-        oa.set_y(WIN_TEXT_YPOS);
-        wins_text_obj.set_visible();
+        let (before, after) = Message::Wins.split();
+        self.show_result_text(&[
+            (before, TextInk::Normal),
+            (winning_player_name.get(), winning_color.text_ink()),
+            (after, TextInk::Normal),
+        ]);
 
         let game_over_state = GameOverState {
             outcome,
-            cursor_position: CursorPosition::Rematch,
+            analysis: self.new_game_analysis(),
         };
 
+        self.record_game_result(Some(winning_color));
+        self.spotlight_outcome(&game_over_state.outcome);
         self.init_game_over_menu();
 
+        // A solved puzzle moves straight on to the next one.
+        let choice = match self.puzzle {
+            Some(ref puzzle) if puzzle.solved => MenuChoice::NextPuzzle,
+            _ => MenuChoice::Rematch,
+        };
+        self.menu.select(choice);
+
         GameState::GameOver(game_over_state)
     }
 
     fn get_draw_game_state(&mut self) -> GameState {
         let outcome = GameOutcome::Draw;
 
-        let draw_text_width: u16 = self
-            .draw_text_object
-            .loaded_sprite()
-            .sprite()
-            .width()
-            .try_into()
-            .unwrap();
+        SoundEvent::Draw.play();
 
         for color in [TokenColor::Yellow, TokenColor::Red] {
             let agent = self.get_agent(color);
             if let Agent::Cpu(ref mut face, _) = agent {
-                face.set_emotion(cpu_face::CpuEmotion::Surprised)
+                face.react(CpuEvent::DrewGame)
             }
         }
 
-        let x_pos = (SCREEN_WIDTH - draw_text_width) / 2;
-        let oa = self.draw_text_object.get_obj_attr_data();
-        oa.set_x(x_pos);
-        oa.set_y(WIN_TEXT_YPOS);
-        self.draw_text_object.set_visible();
+        self.show_result_text(&[(Message::Draw.get(), TextInk::Normal)]);
 
         let game_over_state = GameOverState {
             outcome,
-            cursor_position: CursorPosition::Quit,
+            analysis: self.new_game_analysis(),
         };
 
+        self.record_game_result(None);
         self.init_game_over_menu();
+        self.menu.select(MenuChoice::Quit);
 
         GameState::GameOver(game_over_state)
     }
 
     fn init_game_over_menu(&mut self) {
-        let rematch_oa = self.rematch_text_object.get_obj_attr_data();
-        let rematch_text_width: u16 = REMATCH_TEXT_SPRITE.width().try_into().unwrap();
-        let rematch_text_height: u16 = REMATCH_TEXT_SPRITE.height().try_into().unwrap();
-        let rematch_text_xpos = SCREEN_CENTER.0 - rematch_text_width - MENU_TEXT_HORIZ_MARGIN;
+        if self.series.winner().is_some() {
+            self.init_series_over_prompt();
+            return;
+        }
+
+        // The menu may have been the pause menu, so its entries are put back first.
+        let highlighted = self.menu.highlighted();
+        let entries: &'static [MenuEntry<MenuChoice>] = match self.puzzle {
+            Some(_) => &PUZZLE_OVER_MENU,
+            None => &GAME_OVER_MENU,
+        };
+        self.menu.set_entries(entries);
+
+        if let Some(choice) = highlighted {
+            self.menu.select(choice);
+        }
 
-        rematch_oa.set_x(rematch_text_xpos);
-        rematch_oa.set_y(GAME_OVER_MENU_YPOS);
-        self.rematch_text_object.set_visible();
+        if let Some(ref puzzle) = self.puzzle {
+            self.menu.set_enabled(MenuChoice::NextPuzzle, puzzle.solved);
+        }
 
-        let rematch_x_center = rematch_text_xpos + (rematch_text_width / 2);
-        let rematch_center_offset = SCREEN_CENTER.0 - rematch_x_center;
+        self.menu.show();
+    }
 
-        let quit_oa = self.quit_text_object.get_obj_attr_data();
-        let quit_text_width: u16 = QUIT_TEXT_SPRITE.width().try_into().unwrap();
-        let quit_text_xpos = (SCREEN_CENTER.0 + rematch_center_offset) - (quit_text_width / 2);
+    /// Show the outcome of the game, centred above the board.
+    fn show_result_text(&mut self, result: &[(&str, TextInk)]) {
+        self.result_text.set_text_parts(result);
+        self.result_text
+            .set_position((SCREEN_WIDTH - self.result_text.width()) / 2, WIN_TEXT_YPOS);
+        self.result_text.set_visible();
+    }
 
-        quit_oa.set_x(quit_text_xpos);
-        quit_oa.set_y(GAME_OVER_MENU_YPOS);
-        self.quit_text_object.set_visible();
+    fn init_series_over_prompt(&mut self) {
+        self.press_a_blinker = Blinker::new(PRESS_A_BLINK_TIME_ON, PRESS_A_BLINK_TIME_OFF, true);
+        self.press_a_text.set_visible();
+    }
+}
 
-        let cursor_height: u16 = MENU_CURSOR_FRAME_0_SPRITE.height().try_into().unwrap();
-        let menu_y_center = GAME_OVER_MENU_YPOS + (rematch_text_height / 2);
-        let cursor_ypos = menu_y_center - (cursor_height / 2);
+/// The game waiting for the settings screen to close, if it was opened from the pause menu.
+pub fn suspended_game_screen() -> Option<ScreenState> {
+    unsafe {
+        SUSPENDED_GAME
+            .as_ref()
+            .map(|suspended| suspended.screen_state)
+    }
+}
 
-        let cursor_obj = self.menu_cursor_animation_controller.get_obj_attr_entry();
-        let cursor_oa = cursor_obj.get_obj_attr_data();
-        cursor_oa.set_y(cursor_ypos);
+/// How the backgrounds are tinted for each time of day.
+fn board_tint(board_variant: BoardVariant) -> Option<Tint> {
+    match board_variant {
+        BoardVariant::Day => None,
+        BoardVariant::Dusk => Some(Tint {
+            color: Color::from_rgb(31, 12, 4),
+            amount: DUSK_TINT_AMOUNT,
+        }),
+        BoardVariant::Night => Some(Tint {
+            color: Color::from_rgb(2, 3, 12),
+            amount: NIGHT_TINT_AMOUNT,
+        }),
     }
 }
 
+/// Blend the clouds over the board.
+fn apply_cloud_blend(blend_controller: &mut BlendController) {
+    // Target 1 is on top of Target 2
+    blend_controller.update(
+        BlendControl::new()
+            .with_mode(ColorEffectMode::AlphaBlend)
+            .with_target2_bg0(true)
+            .with_target1_bg1(true)
+            .with_target1_bg2(true),
+        [25, 7].into(),
+    );
+}
+
 impl TokenColor {
     pub fn opposite(&self) -> TokenColor {
         match self {
@@ -648,6 +1401,48 @@ impl TokenColor {
             TokenColor::Yellow => TokenColor::Red,
         }
     }
+
+    /// The colour to write the player's name in.
+    pub fn text_ink(&self) -> TextInk {
+        match self {
+            TokenColor::Red => TextInk::Red,
+            TokenColor::Yellow => TextInk::Yellow,
+        }
+    }
+
+    /// The token's animation, with a shape drawn on when the settings ask for shape markers.
+    pub fn animation(&self) -> &'static Animation<4> {
+        let shape_markers = settings::current().shape_markers;
+
+        match (self, shape_markers) {
+            (TokenColor::Red, false) => &RED_TOKEN_ANIMATION,
+            (TokenColor::Red, true) => &RED_TOKEN_MARKED_ANIMATION,
+            (TokenColor::Yellow, false) => &YELLOW_TOKEN_ANIMATION,
+            (TokenColor::Yellow, true) => &YELLOW_TOKEN_MARKED_ANIMATION,
+        }
+    }
+
+    /// A still image of the token, with its shape marker if enabled.
+    pub fn sprite(&self) -> &'static Sprite {
+        self.sprite_with_markers(settings::current().shape_markers)
+    }
+
+    /// A still image of the token, for screens where the shape marker setting can change.
+    pub fn sprite_with_markers(&self, shape_markers: bool) -> &'static Sprite {
+        match (self, shape_markers) {
+            (TokenColor::Red, false) => &RED_TOKEN_FRAME_0_SPRITE,
+            (TokenColor::Red, true) => &RED_TOKEN_MARKED_FRAME_0_SPRITE,
+            (TokenColor::Yellow, false) => &YELLOW_TOKEN_FRAME_0_SPRITE,
+            (TokenColor::Yellow, true) => &YELLOW_TOKEN_MARKED_FRAME_0_SPRITE,
+        }
+    }
+}
+
+impl<'a> Drop for GameScreen<'a> {
+    fn drop(&mut self) {
+        // Don't leave the win flash behind for the next screen.
+        palette::start_fade(FadeColor::White, 0, 0);
+    }
 }
 
 impl<'a> Screen for GameScreen<'a> {
@@ -660,10 +1455,30 @@ impl<'a> Screen for GameScreen<'a> {
         self.cloud_scroller_far
             .apply_to_background(&self.clouds_background_far);
 
+        self.update_sky();
+
         let mut state = self.get_state();
 
         let new_state = match state {
-            GameState::TurnState(token_color) => self.update_turn(token_color),
+            GameState::TurnState(token_color) => {
+                if self.gba.key_was_pressed(GbaKey::START) {
+                    Some(self.pause(token_color))
+                } else {
+                    let next_screen = self.update_puzzle_input(token_color);
+                    if next_screen.is_some() {
+                        return next_screen;
+                    }
+                    self.update_column_dimming(token_color);
+                    self.update_turn(token_color)
+                }
+            }
+            GameState::Paused(token_color) => {
+                let next_screen = self.update_pause_menu();
+                if next_screen.is_some() {
+                    return next_screen;
+                }
+                (!self.menu.is_visible()).then_some(GameState::TurnState(token_color))
+            }
             GameState::TokenDropping(ref mut token_state) => {
                 self.update_token_dropping(token_state)
             }
@@ -672,8 +1487,9 @@ impl<'a> Screen for GameScreen<'a> {
                 if next_screen.is_some() {
                     return next_screen;
                 }
-                None
+                self.update_analysis(game_over_state)
             }
+            GameState::Review(ref mut review_state) => self.update_review(review_state),
         };
 
         if let Some(new_state) = new_state {
@@ -685,12 +1501,3 @@ impl<'a> Screen for GameScreen<'a> {
         None
     }
 }
-
-impl CursorPosition {
-    pub fn next(&self) -> Self {
-        match self {
-            Self::Quit => Self::Rematch,
-            Self::Rematch => Self::Quit,
-        }
-    }
-}
@@ -0,0 +1,142 @@
+use crate::system::constants::BOARD_SLOTS;
+
+use super::solver::{self, Position, SearchBudget};
+use super::TokenColor;
+
+// How many moves ahead to look for forced wins when checking each move.
+const ANALYSIS_DEPTH: u8 = 3;
+
+#[derive(Clone, Copy)]
+pub struct MoveRecord {
+    pub token_color: TokenColor,
+    pub column: usize,
+    /// The index of the token's object in the game board.
+    pub obj_index: usize,
+}
+
+/// Every move made in a game, in order.
+#[derive(Clone)]
+pub struct MoveHistory {
+    moves: [Option<MoveRecord>; BOARD_SLOTS],
+    len: usize,
+}
+
+/// A move that turned a won or drawn position into a loss.
+#[derive(Clone, Copy)]
+pub struct Blunder {
+    pub move_index: usize,
+    pub token_color: TokenColor,
+    /// A column that would have kept the win, or at least avoided the loss.
+    pub better_column: usize,
+    pub better_row: usize,
+}
+
+/// Re-evaluates each move of a finished game looking for blunders.
+/// The searches are too slow to run all at once, so the analysis is done one move per call.
+#[derive(Clone)]
+pub struct GameAnalysis {
+    position: Position,
+    next_move: usize,
+    skip_color: Option<TokenColor>,
+    blunders: [Option<Blunder>; BOARD_SLOTS],
+    num_blunders: usize,
+}
+
+impl MoveHistory {
+    pub fn new() -> Self {
+        Self {
+            moves: [None; BOARD_SLOTS],
+            len: 0,
+        }
+    }
+
+    pub fn push(&mut self, move_record: MoveRecord) {
+        self.moves[self.len] = Some(move_record);
+        self.len += 1;
+    }
+
+    pub fn num_moves(&self) -> usize {
+        self.len
+    }
+
+    pub fn get(&self, index: usize) -> Option<MoveRecord> {
+        self.moves.get(index).copied().flatten()
+    }
+}
+
+impl Default for MoveHistory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GameAnalysis {
+    /// `initial_position` is the board before the first recorded move.
+    /// Moves made by `skip_color` are not checked, which is used to skip the CPU's moves.
+    pub fn new(initial_position: Position, skip_color: Option<TokenColor>) -> Self {
+        Self {
+            position: initial_position,
+            next_move: 0,
+            skip_color,
+            blunders: [None; BOARD_SLOTS],
+            num_blunders: 0,
+        }
+    }
+
+    pub fn is_finished(&self, history: &MoveHistory) -> bool {
+        self.next_move >= history.num_moves()
+    }
+
+    pub fn analyse_next_move(&mut self, history: &MoveHistory) {
+        let move_record = match history.get(self.next_move) {
+            Some(move_record) => move_record,
+            None => return,
+        };
+
+        if self.skip_color != Some(move_record.token_color) {
+            if let Some((better_column, better_row)) =
+                find_better_move(&self.position, move_record.column)
+            {
+                self.blunders[self.num_blunders] = Some(Blunder {
+                    move_index: self.next_move,
+                    token_color: move_record.token_color,
+                    better_column,
+                    better_row,
+                });
+                self.num_blunders += 1;
+            }
+        }
+
+        self.position = self.position.play(move_record.column);
+        self.next_move += 1;
+    }
+
+    pub fn num_blunders(&self) -> usize {
+        self.num_blunders
+    }
+
+    pub fn get_blunder(&self, index: usize) -> Option<Blunder> {
+        self.blunders.get(index).copied().flatten()
+    }
+}
+
+/// If playing `column` gives the opponent a forced win when there was a way to avoid it,
+/// return the column and row of a better move.
+/// A move that can't be judged within the search budget isn't counted as a blunder.
+fn find_better_move(position: &Position, column: usize) -> Option<(usize, usize)> {
+    if position.is_winning_move(column) {
+        return None;
+    }
+
+    let mut budget = SearchBudget::per_search();
+
+    if !solver::can_force_win(&position.play(column), ANALYSIS_DEPTH, &mut budget) {
+        return None;
+    }
+
+    // Keeping a forced win is best, otherwise any move that doesn't lose will do.
+    let better_column = solver::find_winning_column(position, ANALYSIS_DEPTH, &mut budget)
+        .or_else(|| solver::find_safe_column(position, ANALYSIS_DEPTH, &mut budget))?;
+
+    Some((better_column, position.next_free_row(better_column)))
+}
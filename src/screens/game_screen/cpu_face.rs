@@ -1,3 +1,4 @@
+use crate::audio::mixer::Pan;
 use crate::system::constants::{SCREEN_HEIGHT, SCREEN_WIDTH};
 use crate::system::gba::GBA;
 
@@ -6,9 +7,17 @@ use crate::graphics::sprite::{
     CPU_FACE_NEUTRAL_SPRITE, CPU_FACE_SAD_SPRITE, CPU_FACE_SURPRISED_SPRITE, CPU_HEAD_SPRITE,
 };
 
+use super::cpu_personality::{CpuEvent, CpuPersonality};
+
 pub struct CpuSprites<'a> {
     _gba: &'a GBA,
+    personality: CpuPersonality,
     cpu_head_sprite: LoadedSprite<'a>,
+    face_sprites: CpuFaceSprites<'a>,
+}
+
+/// Every personality pulls the same faces, so these can be shared between several heads.
+pub struct CpuFaceSprites<'a> {
     cpu_neutral_face_sprite: LoadedSprite<'a>,
     cpu_happy_face_sprite: LoadedSprite<'a>,
     cpu_mad_face_sprite: LoadedSprite<'a>,
@@ -20,7 +29,9 @@ pub struct CpuFace<'a> {
     _gba: &'a GBA,
     cpu_head_obj: LoadedObjectEntry<'a>,
     cpu_face_obj: LoadedObjectEntry<'a>,
-    cpu_sprites: &'a CpuSprites<'a>,
+    face_sprites: &'a CpuFaceSprites<'a>,
+    personality: CpuPersonality,
+    x: u16,
 }
 
 #[derive(Clone, Copy)]
@@ -33,27 +44,36 @@ pub enum CpuEmotion {
 }
 
 impl<'a> CpuSprites<'a> {
-    pub fn new(gba: &'a GBA) -> Self {
-        let cpu_head_sprite = CPU_HEAD_SPRITE.load(gba);
-        let cpu_neutral_face_sprite = CPU_FACE_NEUTRAL_SPRITE.load(gba);
-        let cpu_happy_face_sprite = CPU_FACE_HAPPY_SPRITE.load(gba);
-        let cpu_mad_face_sprite = CPU_FACE_MAD_SPRITE.load(gba);
-        let cpu_surprised_face_sprite = CPU_FACE_SURPRISED_SPRITE.load(gba);
-        let cpu_sad_face_sprite = CPU_FACE_SAD_SPRITE.load(gba);
+    pub fn new(gba: &'a GBA, personality: CpuPersonality) -> Self {
+        let cpu_head_sprite = personality.head_sprite().load(gba);
+        let face_sprites = CpuFaceSprites::new(gba);
 
         Self {
             cpu_head_sprite,
-            cpu_neutral_face_sprite,
-            cpu_happy_face_sprite,
-            cpu_mad_face_sprite,
-            cpu_surprised_face_sprite,
-            cpu_sad_face_sprite,
+            face_sprites,
+            personality,
             _gba: gba,
         }
     }
 
-    fn get_head_sprite(&'a self) -> &'a LoadedSprite<'a> {
-        &self.cpu_head_sprite
+    pub fn height() -> usize {
+        CPU_HEAD_SPRITE.height()
+    }
+
+    pub fn width() -> usize {
+        CPU_HEAD_SPRITE.width()
+    }
+}
+
+impl<'a> CpuFaceSprites<'a> {
+    pub fn new(gba: &'a GBA) -> Self {
+        Self {
+            cpu_neutral_face_sprite: CPU_FACE_NEUTRAL_SPRITE.load(gba),
+            cpu_happy_face_sprite: CPU_FACE_HAPPY_SPRITE.load(gba),
+            cpu_mad_face_sprite: CPU_FACE_MAD_SPRITE.load(gba),
+            cpu_surprised_face_sprite: CPU_FACE_SURPRISED_SPRITE.load(gba),
+            cpu_sad_face_sprite: CPU_FACE_SAD_SPRITE.load(gba),
+        }
     }
 
     fn get_face_sprite(&'a self, emotion: CpuEmotion) -> &'a LoadedSprite<'a> {
@@ -65,19 +85,27 @@ impl<'a> CpuSprites<'a> {
             CpuEmotion::Sad => &self.cpu_sad_face_sprite,
         }
     }
-
-    pub fn height() -> usize {
-        CPU_HEAD_SPRITE.height()
-    }
-
-    pub fn width() -> usize {
-        CPU_HEAD_SPRITE.width()
-    }
 }
 
 impl<'a> CpuFace<'a> {
     pub fn new(gba: &'a GBA, cpu_sprites: &'a CpuSprites<'a>) -> Self {
-        let mut cpu_head_obj = cpu_sprites.get_head_sprite().create_obj_attr_entry(gba);
+        Self::with_head(
+            gba,
+            cpu_sprites.personality,
+            &cpu_sprites.cpu_head_sprite,
+            &cpu_sprites.face_sprites,
+        )
+    }
+
+    /// A face for the personality, drawn with a head and faces that are loaded separately.
+    /// The head must be the personality's own.
+    pub fn with_head(
+        gba: &'a GBA,
+        personality: CpuPersonality,
+        head_sprite: &'a LoadedSprite<'a>,
+        face_sprites: &'a CpuFaceSprites<'a>,
+    ) -> Self {
+        let mut cpu_head_obj = head_sprite.create_obj_attr_entry(gba);
 
         let cpu_head_height: u16 = CpuSprites::height().try_into().unwrap();
         let cpu_head_width: u16 = CpuSprites::width().try_into().unwrap();
@@ -85,7 +113,7 @@ impl<'a> CpuFace<'a> {
         let y_pos = SCREEN_HEIGHT - cpu_head_height;
         let x_pos = SCREEN_WIDTH - cpu_head_width - 5;
 
-        let mut cpu_face_obj = cpu_sprites
+        let mut cpu_face_obj = face_sprites
             .get_face_sprite(CpuEmotion::Neutral)
             .create_obj_attr_entry(gba);
 
@@ -97,18 +125,39 @@ impl<'a> CpuFace<'a> {
 
         Self {
             cpu_face_obj,
-            cpu_sprites,
+            face_sprites,
             cpu_head_obj,
+            personality,
+            x: x_pos,
             _gba: gba,
         }
     }
 
+    pub fn personality(&self) -> CpuPersonality {
+        self.personality
+    }
+
     pub fn set_emotion(&mut self, emotion: CpuEmotion) {
-        let face_sprite = self.cpu_sprites.get_face_sprite(emotion);
+        let face_sprite = self.face_sprites.get_face_sprite(emotion);
         face_sprite.store_in_obj_entry(&mut self.cpu_face_obj);
     }
 
+    /// Show this personality's reaction to the event, if it has one.
+    pub fn react(&mut self, event: CpuEvent) {
+        if let Some(emotion) = self.personality.reaction(event) {
+            self.set_emotion(emotion);
+        }
+    }
+
+    /// Pan the CPU's sounds towards where its head is on screen.
+    pub fn pan(&self) -> Pan {
+        let head_width: u16 = CpuSprites::width().try_into().unwrap();
+        Pan::from_screen_x(self.x + head_width / 2)
+    }
+
     pub fn set_x(&mut self, x: u16) {
+        self.x = x;
+
         for obj in [&mut self.cpu_face_obj, &mut self.cpu_head_obj] {
             let oa = obj.get_obj_attr_data();
             oa.set_x(x);
@@ -0,0 +1,144 @@
+use crate::graphics::sprite::{
+    Sprite, CPU_HEAD_AGGRESSIVE_SPRITE, CPU_HEAD_CENTER_OBSESSED_SPRITE, CPU_HEAD_SPRITE,
+    CPU_HEAD_TRICKSTER_SPRITE,
+};
+
+use crate::system::strings::Message;
+
+use super::cpu_face::CpuEmotion;
+
+/// The CPU opponents that can be picked on the character select screen.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CpuPersonality {
+    Aggressive,
+    Defensive,
+    Trickster,
+    CenterObsessed,
+}
+
+pub const NUM_CPU_PERSONALITIES: usize = 4;
+
+pub const CPU_PERSONALITIES: [CpuPersonality; NUM_CPU_PERSONALITIES] = [
+    CpuPersonality::Aggressive,
+    CpuPersonality::Defensive,
+    CpuPersonality::Trickster,
+    CpuPersonality::CenterObsessed,
+];
+
+/// How a personality scores the columns that aren't decided by the win/block rules.
+pub struct PlayStyle {
+    /// Multiplier for the length of the CPU's own line that a move extends.
+    pub attack_weight: i32,
+    /// Multiplier for the length of the opponent's line that a move blocks.
+    pub defence_weight: i32,
+    /// Bonus for each column closer to the centre.
+    pub centre_weight: i32,
+    /// Up to this much is added to each column's score at random.
+    pub randomness: u32,
+}
+
+/// Moments during a game that the CPU reacts to.
+#[derive(Clone, Copy)]
+pub enum CpuEvent {
+    FoundWinningMove,
+    BlockedWinningMove,
+    SetUpWinningMove,
+    WasBlocked,
+    MadeMove,
+    TookCentre,
+    LostGame,
+    DrewGame,
+}
+
+impl CpuPersonality {
+    pub fn play_style(&self) -> PlayStyle {
+        match self {
+            // Builds its own lines and mostly ignores the player's.
+            Self::Aggressive => PlayStyle {
+                attack_weight: 3,
+                defence_weight: 1,
+                centre_weight: 0,
+                randomness: 1,
+            },
+            // The original CPU, which cares twice as much about blocking as attacking.
+            Self::Defensive => PlayStyle {
+                attack_weight: 1,
+                defence_weight: 2,
+                centre_weight: 0,
+                randomness: 0,
+            },
+            // Balanced, but hard to read.
+            Self::Trickster => PlayStyle {
+                attack_weight: 2,
+                defence_weight: 2,
+                centre_weight: 0,
+                randomness: 4,
+            },
+            // Will happily pass up a good move to stay in the middle of the board.
+            Self::CenterObsessed => PlayStyle {
+                attack_weight: 1,
+                defence_weight: 1,
+                centre_weight: 2,
+                randomness: 0,
+            },
+        }
+    }
+
+    /// The emotion shown in response to an event, if this personality reacts to it.
+    pub fn reaction(&self, event: CpuEvent) -> Option<CpuEmotion> {
+        match (self, event) {
+            (_, CpuEvent::FoundWinningMove) => Some(CpuEmotion::Happy),
+            (_, CpuEvent::MadeMove) => Some(CpuEmotion::Neutral),
+
+            (Self::Aggressive, CpuEvent::BlockedWinningMove) => Some(CpuEmotion::Mad),
+            (_, CpuEvent::BlockedWinningMove) => Some(CpuEmotion::Surprised),
+
+            (Self::Aggressive | Self::Trickster, CpuEvent::SetUpWinningMove) => {
+                Some(CpuEmotion::Happy)
+            }
+            (_, CpuEvent::SetUpWinningMove) => None,
+
+            (Self::Trickster, CpuEvent::WasBlocked) => Some(CpuEmotion::Surprised),
+            (_, CpuEvent::WasBlocked) => Some(CpuEmotion::Mad),
+
+            (Self::CenterObsessed, CpuEvent::TookCentre) => Some(CpuEmotion::Happy),
+            (_, CpuEvent::TookCentre) => Some(CpuEmotion::Neutral),
+
+            (Self::Aggressive, CpuEvent::LostGame) => Some(CpuEmotion::Mad),
+            (_, CpuEvent::LostGame) => Some(CpuEmotion::Sad),
+
+            (Self::Aggressive, CpuEvent::DrewGame) => Some(CpuEmotion::Mad),
+            (Self::Trickster, CpuEvent::DrewGame) => Some(CpuEmotion::Happy),
+            (_, CpuEvent::DrewGame) => Some(CpuEmotion::Surprised),
+        }
+    }
+
+    /// The name shown on the character select screen.
+    pub fn name(&self) -> Message {
+        match self {
+            Self::Aggressive => Message::CpuAggressive,
+            Self::Defensive => Message::CpuDefensive,
+            Self::Trickster => Message::CpuTrickster,
+            Self::CenterObsessed => Message::CpuCentrist,
+        }
+    }
+
+    /// The face shown on the character select screen.
+    pub fn signature_emotion(&self) -> CpuEmotion {
+        match self {
+            Self::Aggressive => CpuEmotion::Mad,
+            Self::Defensive => CpuEmotion::Neutral,
+            Self::Trickster => CpuEmotion::Happy,
+            Self::CenterObsessed => CpuEmotion::Surprised,
+        }
+    }
+
+    pub fn head_sprite(&self) -> &'static Sprite {
+        match self {
+            Self::Aggressive => &CPU_HEAD_AGGRESSIVE_SPRITE,
+            Self::Defensive => &CPU_HEAD_SPRITE,
+            Self::Trickster => &CPU_HEAD_TRICKSTER_SPRITE,
+            Self::CenterObsessed => &CPU_HEAD_CENTER_OBSESSED_SPRITE,
+        }
+    }
+}
@@ -4,15 +4,21 @@ use gba::prelude::TIMER3_COUNT;
 use gba::random::{Gen32, Lcg32};
 
 use crate::system::constants::BOARD_COLUMNS;
+use crate::system::settings::{self, CpuDelay};
 
-use super::cpu_face::{CpuEmotion, CpuFace};
+use super::cpu_face::CpuFace;
+use super::cpu_personality::{CpuEvent, CpuPersonality, PlayStyle};
 use super::cursor::Cursor;
 use super::game_board;
+use super::solver::{self, SearchBudget};
 use super::TokenColor;
 use crate::graphics::sprite::AnimationController;
 
 const NUM_COLUMNS: usize = BOARD_COLUMNS as usize;
+const CENTRE_COLUMN: usize = NUM_COLUMNS / 2;
 const MOVEMENT_DELAY: u32 = 6;
+const MOVEMENT_DELAY_SHORT: u32 = 3;
+const MOVEMENT_DELAY_LONG: u32 = 12;
 
 #[derive(Clone)]
 struct DecidingState {
@@ -24,6 +30,7 @@ struct DecidingState {
 struct MovingState {
     target_column: usize,
     move_delay_timer: u32,
+    move_delay: u32,
 }
 
 #[derive(Clone)]
@@ -32,15 +39,26 @@ enum CpuState {
     Moving(MovingState),
 }
 
+#[derive(Clone, Copy)]
+pub enum CpuStrategy {
+    /// The normal opponent, which scores each column with some simple rules of thumb,
+    /// weighted according to its personality.
+    Heuristic(CpuPersonality),
+    /// Searches for the reply that holds off a forced win the longest,
+    /// looking ahead the given number of the opponent's moves. Used in puzzles.
+    Defender(u8),
+}
+
 #[derive(Clone)]
 pub struct CpuTurn {
     state: CpuState,
     cursor: Cursor,
     rng: Lcg32,
+    strategy: CpuStrategy,
 }
 
 impl CpuTurn {
-    pub fn new() -> Self {
+    pub fn with_strategy(strategy: CpuStrategy) -> Self {
         let deciding_state = DecidingState::new();
         // Seed with the timer value as a somewhat "random" source.
         let seed: u32 = TIMER3_COUNT.read().into();
@@ -50,6 +68,7 @@ impl CpuTurn {
             state: CpuState::Deciding(deciding_state),
             cursor: Cursor::new(),
             rng,
+            strategy,
         }
     }
 
@@ -68,7 +87,13 @@ impl CpuTurn {
                     let moving_state = MovingState::new(best_column);
                     self.state = CpuState::Moving(moving_state);
                 } else {
-                    deciding.score_next_column(token_color, game_board, cpu_face);
+                    deciding.score_next_column(
+                        token_color,
+                        game_board,
+                        cpu_face,
+                        self.strategy,
+                        &mut self.rng,
+                    );
                 }
             }
             CpuState::Moving(ref mut moving) => {
@@ -80,7 +105,11 @@ impl CpuTurn {
 
                     if let Some(row) = row {
                         if !game_board.is_winning_token(column, row, token_color) {
-                            cpu_face.set_emotion(CpuEmotion::Neutral);
+                            cpu_face.react(if column == CENTRE_COLUMN {
+                                CpuEvent::TookCentre
+                            } else {
+                                CpuEvent::MadeMove
+                            });
                         }
 
                         animation_controller.set_hidden();
@@ -106,12 +135,6 @@ impl CpuTurn {
     }
 }
 
-impl Default for CpuTurn {
-    fn default() -> Self {
-        Self::new()
-    }
-}
-
 impl DecidingState {
     pub fn new() -> Self {
         Self {
@@ -125,8 +148,36 @@ impl DecidingState {
         token_color: TokenColor,
         game_board: &mut game_board::GameBoard,
         cpu_face: &mut CpuFace,
+        strategy: CpuStrategy,
+        rng: &mut Lcg32,
     ) {
-        let score = self.score_column(token_color, game_board, self.scored_columns, cpu_face);
+        let score = match strategy {
+            CpuStrategy::Heuristic(personality) => {
+                let play_style = personality.play_style();
+
+                let jitter = if play_style.randomness > 0 {
+                    (rng.next_u32() % (play_style.randomness + 1)) as i32
+                } else {
+                    0
+                };
+
+                self.score_column(
+                    token_color,
+                    game_board,
+                    self.scored_columns,
+                    &play_style,
+                    jitter,
+                    Some(cpu_face),
+                )
+            }
+            CpuStrategy::Defender(max_moves) => self.score_defending_column(
+                token_color,
+                game_board,
+                self.scored_columns,
+                cpu_face,
+                max_moves,
+            ),
+        };
 
         self.col_scores[self.scored_columns] = Some(score);
         self.scored_columns += 1;
@@ -166,9 +217,11 @@ impl DecidingState {
     fn score_column(
         &self,
         token_color: TokenColor,
-        game_board: &mut game_board::GameBoard,
+        game_board: &game_board::GameBoard,
         column_number: usize,
-        cpu_face: &mut CpuFace,
+        play_style: &PlayStyle,
+        jitter: i32,
+        cpu_face: Option<&mut CpuFace>,
     ) -> i32 {
         let row = game_board.get_next_free_row(column_number);
 
@@ -185,32 +238,43 @@ impl DecidingState {
 
         // First priority is to choose a winning move.
         if game_board.player_can_win(column_number, token_color) {
-            cpu_face.set_emotion(CpuEmotion::Happy);
+            if let Some(cpu_face) = cpu_face {
+                cpu_face.react(CpuEvent::FoundWinningMove);
+            }
             return i32::MAX;
         };
 
         // Next priority is to block opponent's winning move.
         if game_board.player_can_win(column_number, opponent_color) {
-            cpu_face.set_emotion(CpuEmotion::Surprised);
+            if let Some(cpu_face) = cpu_face {
+                cpu_face.react(CpuEvent::BlockedWinningMove);
+            }
             return i32::MAX - 1;
         }
 
         // Don't make a move that sets up a winning move for the opponent.
-        if self.player_has_winning_move(&candidate_board, opponent_color) {
+        if candidate_board.player_has_winning_move(opponent_color) {
             // + 1 makes sure this is chosen over a move that is not allowed.
             return i32::MIN + 1;
         }
 
         // Then see if you can set up a winning move.
-        if self.player_has_winning_move(&candidate_board, token_color) {
+        if candidate_board.player_has_winning_move(token_color) {
+            if let Some(cpu_face) = cpu_face {
+                cpu_face.react(CpuEvent::SetUpWinningMove);
+            }
             return i32::MAX - 2;
         }
 
         // Otherwise fall back to heuristic:
         //  Go through each neighbour, atwiend check for any lines it contributes to.
-        //  Lines of the opponent's color are worth twice as much as lines of the player's color.
-        //  The final score will be the  maximum weighted line from all possible directions.
-        game_board::DIRECTIONS
+        //  Lines of each color are weighted according to the play style.
+        //  The final score will be the  maximum weighted line from all possible directions,
+        //  plus any bonus for being near the centre and a little randomness.
+        let centre_distance: i32 = column_number.abs_diff(CENTRE_COLUMN).try_into().unwrap();
+        let centre_bonus = play_style.centre_weight * (CENTRE_COLUMN as i32 - centre_distance);
+
+        let line_score = game_board::DIRECTIONS
             .iter()
             .map(|direction| {
                 let my_color_length: i32 = game_board
@@ -225,26 +289,79 @@ impl DecidingState {
                     .try_into()
                     .unwrap();
 
-                max(my_color_length, 2 * opposite_color_length)
+                max(
+                    play_style.attack_weight * my_color_length,
+                    play_style.defence_weight * opposite_color_length,
+                )
             })
             .max()
-            .unwrap()
+            .unwrap();
+
+        line_score + centre_bonus + jitter
     }
 
-    fn player_has_winning_move(
+    fn score_defending_column(
         &self,
-        game_board: &game_board::GameBoard,
         token_color: TokenColor,
-    ) -> bool {
-        (0..NUM_COLUMNS).any(|column| game_board.player_can_win(column, token_color))
+        game_board: &game_board::GameBoard,
+        column_number: usize,
+        cpu_face: &mut CpuFace,
+        max_moves: u8,
+    ) -> i32 {
+        let position = solver::position_from_board(game_board, token_color);
+
+        if !position.can_play(column_number) {
+            return i32::MIN;
+        }
+
+        if position.is_winning_move(column_number) {
+            cpu_face.react(CpuEvent::FoundWinningMove);
+            return i32::MAX;
+        }
+
+        // Make the opponent work as hard as possible for their win.
+        let mut budget = SearchBudget::per_search();
+        solver::moves_until_forced_loss(&position, column_number, max_moves, &mut budget).into()
     }
 }
 
+/// Run the CPU's evaluation on behalf of the given player, to suggest a move to a human.
+/// This uses the defensive play style, without any randomness. Ties go to the column closest
+/// to the centre, so that asking for the same hint twice gives the same answer.
+pub fn suggest_column(
+    token_color: TokenColor,
+    game_board: &game_board::GameBoard,
+) -> Option<usize> {
+    let deciding_state = DecidingState::new();
+    let play_style = CpuPersonality::Defensive.play_style();
+    let centre_column: i32 = CENTRE_COLUMN.try_into().unwrap();
+
+    (0..NUM_COLUMNS)
+        .map(|column| {
+            let score =
+                deciding_state.score_column(token_color, game_board, column, &play_style, 0, None);
+            (column, score)
+        })
+        .filter(|(_, score)| *score != i32::MIN)
+        .max_by_key(|(column, score)| {
+            let column: i32 = (*column).try_into().unwrap();
+            (*score, -(column - centre_column).abs())
+        })
+        .map(|(column, _)| column)
+}
+
 impl MovingState {
     pub fn new(target_column: usize) -> Self {
+        let move_delay = match settings::current().cpu_delay {
+            CpuDelay::Short => MOVEMENT_DELAY_SHORT,
+            CpuDelay::Normal => MOVEMENT_DELAY,
+            CpuDelay::Long => MOVEMENT_DELAY_LONG,
+        };
+
         Self {
             target_column,
-            move_delay_timer: MOVEMENT_DELAY,
+            move_delay_timer: move_delay,
+            move_delay,
         }
     }
 
@@ -272,7 +389,7 @@ impl MovingState {
         self.move_delay_timer -= 1;
 
         if self.move_delay_timer == 0 {
-            self.move_delay_timer = MOVEMENT_DELAY;
+            self.move_delay_timer = self.move_delay;
             true
         } else {
             false
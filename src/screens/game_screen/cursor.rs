@@ -1,10 +1,18 @@
 use core::cmp::{max, min};
 
 use super::game_board::{self, get_token_x_position};
-use crate::{graphics::sprite::AnimationController, system::constants::BOARD_COLUMNS};
+use crate::{
+    graphics::sprite::AnimationController,
+    system::{
+        constants::BOARD_COLUMNS,
+        settings::{self, CursorSpeed},
+    },
+};
 
 const CURSOR_MOVEMENT_SPEED: u16 = 5;
 const CURSOR_MOVEMENT_SPEED_FAST: u16 = 20;
+const CURSOR_MOVEMENT_SPEED_SLOW_SETTING: u16 = 3;
+const CURSOR_MOVEMENT_SPEED_FAST_SETTING: u16 = 8;
 
 #[derive(Clone)]
 pub struct Cursor {
@@ -13,18 +21,27 @@ pub struct Cursor {
     target_x_position: u16,
     moving: bool,
     speed: u16,
+    // The usual speed, from the settings. Wrapping around the board always moves fast.
+    base_speed: u16,
 }
 
 impl Cursor {
     pub fn new() -> Self {
         let column: usize = 0;
         let x_position = get_token_x_position(column);
+        let base_speed = match settings::current().cursor_speed {
+            CursorSpeed::Slow => CURSOR_MOVEMENT_SPEED_SLOW_SETTING,
+            CursorSpeed::Normal => CURSOR_MOVEMENT_SPEED,
+            CursorSpeed::Fast => CURSOR_MOVEMENT_SPEED_FAST_SETTING,
+        };
+
         Self {
             column,
             x_position,
             target_x_position: x_position,
             moving: false,
-            speed: CURSOR_MOVEMENT_SPEED,
+            speed: base_speed,
+            base_speed,
         }
     }
 
@@ -55,7 +72,7 @@ impl Cursor {
 
             // Reset the speed once the movement is done.
             if !self.moving {
-                self.speed = CURSOR_MOVEMENT_SPEED;
+                self.speed = self.base_speed;
             }
         }
     }
@@ -1,12 +1,15 @@
 use super::TokenColor;
 
+use gba::prelude::ObjEffectMode;
+
 use crate::system::{
     constants::{BOARD_COLUMNS, BOARD_ROWS, BOARD_SLOTS, SCREEN_HEIGHT, SCREEN_WIDTH},
     gba::GBA,
 };
 
-use crate::graphics::sprite::{
-    LoadedObjectEntry, LoadedSprite, BOARD_SLOT_SPRITE, RED_TOKEN_FRAME_0_SPRITE,
+use crate::graphics::{
+    effects::window::WindowRect,
+    sprite::{LoadedObjectEntry, LoadedSprite, BOARD_SLOT_SPRITE, RED_TOKEN_FRAME_0_SPRITE},
 };
 
 pub type WinningPositions = [usize; 4];
@@ -117,6 +120,25 @@ impl<'a> GameBoard<'a> {
         }
     }
 
+    pub fn player_has_winning_move(&self, token_color: TokenColor) -> bool {
+        let num_columns: usize = BOARD_COLUMNS.into();
+        (0..num_columns).any(|column| self.player_can_win(column, token_color))
+    }
+
+    /// Columns where dropping a token would give the opponent a winning move in the slot above it.
+    pub fn get_losing_columns(&self, token_color: TokenColor) -> impl Iterator<Item = usize> + '_ {
+        let num_columns: usize = BOARD_COLUMNS.into();
+        let opponent_color = token_color.opposite();
+
+        (0..num_columns).filter(move |column| {
+            self.get_next_free_row(*column).is_some()
+                && !self.player_can_win(*column, token_color)
+                && self
+                    .get_board_after_move(*column, token_color)
+                    .player_can_win(*column, opponent_color)
+        })
+    }
+
     pub fn get_board_after_move(&self, column: usize, token_color: TokenColor) -> Self {
         let mut new_matrix = self.matrix;
 
@@ -190,6 +212,29 @@ impl<'a> GameBoard<'a> {
         cell_index
     }
 
+    /// An invisible copy of the token in a cell, which adds the token's shape to the OBJ window.
+    pub fn create_window_mask(&self, cell_index: usize) -> Option<LoadedObjectEntry<'a>> {
+        let sprite = match self.matrix[cell_index]? {
+            TokenColor::Red => self.red_token_sprite,
+            TokenColor::Yellow => self.yellow_token_sprite,
+        };
+
+        let num_rows: usize = BOARD_ROWS.into();
+        let col = cell_index / num_rows;
+        let row = cell_index % num_rows;
+
+        let mut obj = sprite.create_obj_attr_entry(self.gba);
+
+        let attr = obj.get_obj_attr_data();
+        attr.1 = attr.1.with_x(get_token_x_position(col));
+        attr.0 = attr
+            .0
+            .with_y(self.get_token_ypos_for_row(row))
+            .with_mode(ObjEffectMode::Window);
+
+        Some(obj)
+    }
+
     fn move_index_in_direction(
         &self,
         current_column: usize,
@@ -264,7 +309,7 @@ impl<'a> GameBoard<'a> {
         }
     }
 
-    fn check_token(&self, column: usize, row: usize) -> Option<TokenColor> {
+    pub fn check_token(&self, column: usize, row: usize) -> Option<TokenColor> {
         let num_rows: usize = BOARD_ROWS.into();
         let index = column * num_rows + row;
 
@@ -411,6 +456,62 @@ pub fn board_top_left_corner() -> (u16, u16) {
     (start_x, start_y)
 }
 
+/// The area of the screen covered by the whole board.
+pub fn get_board_rect() -> WindowRect {
+    let (start_x, start_y) = board_top_left_corner();
+    let board_slot_width: u16 = BOARD_SLOT_SPRITE.width().try_into().unwrap();
+    let board_slot_height: u16 = BOARD_SLOT_SPRITE.height().try_into().unwrap();
+
+    let columns: u16 = BOARD_COLUMNS.into();
+    let rows: u16 = BOARD_ROWS.into();
+
+    WindowRect::new(
+        start_x,
+        start_y,
+        board_slot_width * columns,
+        board_slot_height * rows,
+    )
+}
+
+/// The area of the screen covered by one column of the board.
+pub fn get_column_rect(column_number: usize) -> WindowRect {
+    let (start_x, start_y) = board_top_left_corner();
+    let board_slot_width: u16 = BOARD_SLOT_SPRITE.width().try_into().unwrap();
+    let board_slot_height: u16 = BOARD_SLOT_SPRITE.height().try_into().unwrap();
+
+    let column_number: u16 = column_number.try_into().unwrap();
+    let rows: u16 = BOARD_ROWS.into();
+
+    WindowRect::new(
+        start_x + column_number * board_slot_width,
+        start_y,
+        board_slot_width,
+        board_slot_height * rows,
+    )
+}
+
+/// The area of the screen above one column of the board, which a token drops through.
+pub fn get_above_column_rect(column_number: usize) -> WindowRect {
+    let (start_x, start_y) = board_top_left_corner();
+    let board_slot_width: u16 = BOARD_SLOT_SPRITE.width().try_into().unwrap();
+
+    let column_number: u16 = column_number.try_into().unwrap();
+
+    WindowRect::new(
+        start_x + column_number * board_slot_width,
+        0,
+        board_slot_width,
+        start_y,
+    )
+}
+
+/// The area of the screen above the board.
+pub fn get_above_board_rect() -> WindowRect {
+    let (_, start_y) = board_top_left_corner();
+
+    WindowRect::new(0, 0, SCREEN_WIDTH, start_y)
+}
+
 impl Direction {
     pub fn opposite(&self) -> Self {
         match self {
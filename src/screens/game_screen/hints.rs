@@ -0,0 +1,157 @@
+use crate::graphics::effects::blinker::Blinker;
+use crate::graphics::sprite::{LoadedObjectEntry, LoadedSprite};
+use crate::system::constants::{BOARD_COLUMNS, BOARD_ROWS};
+use crate::system::gba::GBA;
+
+use super::cpu_turn;
+use super::game_board::{self, GameBoard};
+use super::solver::{self, SearchBudget};
+use super::TokenColor;
+
+const NUM_COLUMNS: usize = BOARD_COLUMNS as usize;
+const NUM_ROWS: usize = BOARD_ROWS as usize;
+
+const HINT_BLINK_TIME_ON: u32 = 12;
+const HINT_BLINK_TIME_OFF: u32 = 12;
+
+/// How the suggested column is chosen.
+#[derive(Clone, Copy)]
+pub enum HintSearch {
+    /// Use the same evaluation as the CPU player.
+    Heuristic,
+    /// Find a forced win within the given number of moves, as needed for puzzles.
+    ForcedWin(u8),
+}
+
+/// Shows the human player a suggested move, as a blinking token where it would land.
+/// Columns that would hand the opponent a win are marked with a blinking opponent token in the
+/// slot that the move would open up for them.
+///
+/// The objects are only claimed while the hint is on screen, to leave OAM free the rest of the time.
+pub struct HintOverlay<'a> {
+    gba: &'a GBA,
+    red_token_sprite: &'a LoadedSprite<'a>,
+    yellow_token_sprite: &'a LoadedSprite<'a>,
+    suggestion_object: Option<LoadedObjectEntry<'a>>,
+    threat_objects: [Option<LoadedObjectEntry<'a>>; NUM_COLUMNS],
+    blinker: Blinker,
+    search: HintSearch,
+}
+
+impl<'a> HintOverlay<'a> {
+    pub fn new(
+        gba: &'a GBA,
+        red_token_sprite: &'a LoadedSprite<'a>,
+        yellow_token_sprite: &'a LoadedSprite<'a>,
+    ) -> Self {
+        Self {
+            gba,
+            red_token_sprite,
+            yellow_token_sprite,
+            suggestion_object: None,
+            threat_objects: core::array::from_fn(|_| None),
+            blinker: Blinker::new(HINT_BLINK_TIME_ON, HINT_BLINK_TIME_OFF, true),
+            search: HintSearch::Heuristic,
+        }
+    }
+
+    pub fn set_search(&mut self, search: HintSearch) {
+        self.search = search;
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.suggestion_object.is_some() || self.threat_objects.iter().any(|o| o.is_some())
+    }
+
+    /// Work out the hint for the given player and put it on screen.
+    pub fn show(&mut self, token_color: TokenColor, game_board: &GameBoard) {
+        self.hide();
+
+        if let Some(column) = self.suggest_column(token_color, game_board) {
+            if let Some(row) = game_board.get_next_free_row(column) {
+                self.show_move(token_color, game_board, column, row);
+            }
+        }
+
+        let opponent_color = token_color.opposite();
+
+        for column in game_board.get_losing_columns(token_color) {
+            // The opponent's winning slot is directly above where this token would land.
+            if let Some(row) = game_board.get_next_free_row(column) {
+                if row + 1 < NUM_ROWS {
+                    let obj = self.create_token_object(opponent_color, game_board, column, row + 1);
+                    self.threat_objects[column] = Some(obj);
+                }
+            }
+        }
+    }
+
+    /// Suggest a specific move, replacing anything currently shown.
+    pub fn show_move(
+        &mut self,
+        token_color: TokenColor,
+        game_board: &GameBoard,
+        column: usize,
+        row: usize,
+    ) {
+        self.hide();
+        self.blinker = Blinker::new(HINT_BLINK_TIME_ON, HINT_BLINK_TIME_OFF, true);
+
+        let obj = self.create_token_object(token_color, game_board, column, row);
+        self.suggestion_object = Some(obj);
+    }
+
+    pub fn hide(&mut self) {
+        self.suggestion_object = None;
+        self.threat_objects = core::array::from_fn(|_| None);
+    }
+
+    pub fn update(&mut self) {
+        if !self.is_visible() {
+            return;
+        }
+
+        self.blinker.update();
+
+        let objects = self
+            .threat_objects
+            .iter_mut()
+            .chain(core::iter::once(&mut self.suggestion_object));
+
+        for obj in objects.flatten() {
+            self.blinker.apply_to_object(obj);
+        }
+    }
+
+    fn suggest_column(&self, token_color: TokenColor, game_board: &GameBoard) -> Option<usize> {
+        let forced_win = match self.search {
+            HintSearch::Heuristic => None,
+            HintSearch::ForcedWin(moves) => {
+                let position = solver::position_from_board(game_board, token_color);
+                solver::find_winning_column(&position, moves, &mut SearchBudget::per_search())
+            }
+        };
+
+        forced_win.or_else(|| cpu_turn::suggest_column(token_color, game_board))
+    }
+
+    fn create_token_object(
+        &self,
+        token_color: TokenColor,
+        game_board: &GameBoard,
+        column: usize,
+        row: usize,
+    ) -> LoadedObjectEntry<'a> {
+        let sprite = match token_color {
+            TokenColor::Red => self.red_token_sprite,
+            TokenColor::Yellow => self.yellow_token_sprite,
+        };
+
+        let mut obj = sprite.create_obj_attr_entry(self.gba);
+        let oa = obj.get_obj_attr_data();
+        oa.set_x(game_board::get_token_x_position(column));
+        oa.set_y(game_board.get_token_ypos_for_row(row));
+
+        obj
+    }
+}
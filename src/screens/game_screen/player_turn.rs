@@ -1,6 +1,8 @@
-use super::cpu_face::{CpuEmotion, CpuFace};
+use super::cpu_face::CpuFace;
+use super::cpu_personality::CpuEvent;
 use super::cursor::Cursor;
 use super::game_board;
+use super::hints::HintOverlay;
 use super::TokenColor;
 use crate::graphics::sprite::AnimationController;
 use crate::system::gba::{GbaKey, GBA};
@@ -23,7 +25,19 @@ impl PlayerTurn {
         anim_controller: &mut AnimationController<4>,
         game_board: &mut game_board::GameBoard,
         cpu_face: Option<&mut CpuFace>,
+        hint_overlay: &mut HintOverlay,
     ) -> Option<usize> {
+        // SELECT toggles a hint for the current turn.
+        if gba.key_was_pressed(GbaKey::SELECT) {
+            if hint_overlay.is_visible() {
+                hint_overlay.hide();
+            } else {
+                hint_overlay.show(token_color, game_board);
+            }
+        }
+
+        hint_overlay.update();
+
         if self.cursor.is_moving() {
             self.cursor.update_movement();
         } else if gba.key_was_pressed(GbaKey::LEFT) {
@@ -36,13 +50,14 @@ impl PlayerTurn {
             anim_controller.set_hidden();
 
             if let Some(row) = row {
-                // If the player blocks the CPU, then he should be angry.
+                // If the player blocks the CPU, then it reacts.
                 if game_board.is_winning_token(col, row, token_color.opposite()) {
                     if let Some(cpu_face) = cpu_face {
-                        cpu_face.set_emotion(CpuEmotion::Mad);
+                        cpu_face.react(CpuEvent::WasBlocked);
                     }
                 }
 
+                hint_overlay.hide();
                 self.reset();
 
                 return Some(col);
@@ -54,6 +69,10 @@ impl PlayerTurn {
         None
     }
 
+    pub fn get_column(&self) -> usize {
+        self.cursor.get_column()
+    }
+
     fn reset(&mut self) {
         self.cursor = Cursor::new();
     }
@@ -0,0 +1,83 @@
+use super::TokenColor;
+use crate::graphics::sprite::{LoadedObjectEntry, LoadedSprite};
+use crate::screens::match_series::{MatchSeries, MAX_SERIES_WINS};
+use crate::system::constants::SCREEN_WIDTH;
+use crate::system::gba::GBA;
+
+const SCOREBOARD_YPOS: u16 = 4;
+const SCOREBOARD_EDGE_MARGIN: u16 = 4;
+const SCOREBOARD_SPACING: u16 = 2;
+
+/// Draws the number of games each player has won in a series, as a column of tokens
+/// down each side of the board.
+pub struct Scoreboard<'a> {
+    red_tokens: [LoadedObjectEntry<'a>; MAX_SERIES_WINS],
+    yellow_tokens: [LoadedObjectEntry<'a>; MAX_SERIES_WINS],
+}
+
+impl<'a> Scoreboard<'a> {
+    pub fn new(
+        gba: &'a GBA,
+        red_token_sprite: &'a LoadedSprite<'a>,
+        yellow_token_sprite: &'a LoadedSprite<'a>,
+    ) -> Self {
+        let token_width: u16 = red_token_sprite.sprite().width().try_into().unwrap();
+
+        let red_tokens = create_token_column(gba, red_token_sprite, SCOREBOARD_EDGE_MARGIN);
+        let yellow_tokens = create_token_column(
+            gba,
+            yellow_token_sprite,
+            SCREEN_WIDTH - token_width - SCOREBOARD_EDGE_MARGIN,
+        );
+
+        Self {
+            red_tokens,
+            yellow_tokens,
+        }
+    }
+
+    /// Show one token for each game won by each player.
+    pub fn update(&mut self, series: &MatchSeries) {
+        self.set_counts(
+            series.wins(TokenColor::Red),
+            series.wins(TokenColor::Yellow),
+        );
+    }
+
+    /// Show the given number of tokens on each side, clamped to the height of the scoreboard.
+    pub fn set_counts(&mut self, red_count: u8, yellow_count: u8) {
+        for (count, tokens) in [
+            (red_count, &mut self.red_tokens),
+            (yellow_count, &mut self.yellow_tokens),
+        ] {
+            let count: usize = count.into();
+
+            for (i, token) in tokens.iter_mut().enumerate() {
+                if i < count {
+                    token.set_visible();
+                } else {
+                    token.set_hidden();
+                }
+            }
+        }
+    }
+}
+
+fn create_token_column<'a>(
+    gba: &'a GBA,
+    token_sprite: &'a LoadedSprite<'a>,
+    x_pos: u16,
+) -> [LoadedObjectEntry<'a>; MAX_SERIES_WINS] {
+    let token_height: u16 = token_sprite.sprite().height().try_into().unwrap();
+
+    core::array::from_fn(|i| {
+        let mut obj = token_sprite.create_obj_attr_entry(gba).with_hidden();
+
+        let i: u16 = i.try_into().unwrap();
+        let oa = obj.get_obj_attr_data();
+        oa.set_x(x_pos);
+        oa.set_y(SCOREBOARD_YPOS + i * (token_height + SCOREBOARD_SPACING));
+
+        obj
+    })
+}
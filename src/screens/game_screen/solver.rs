@@ -0,0 +1,16 @@
+pub use solver_core::{
+    can_force_win, find_safe_column, find_winning_column, moves_until_forced_loss,
+    opponent_is_lost, Position, SearchBudget,
+};
+
+use super::game_board::GameBoard;
+use super::TokenColor;
+
+/// A copy of the board for searching ahead, with `to_move` as the player to move.
+pub fn position_from_board(game_board: &GameBoard, to_move: TokenColor) -> Position {
+    Position::from_slots(|column, row| {
+        game_board
+            .check_token(column, row)
+            .map(|token_color| token_color == to_move)
+    })
+}
@@ -0,0 +1,120 @@
+use super::game_screen::TokenColor;
+
+/// The longest series that can be played. Four wins are needed to take a best of 7.
+pub const MAX_SERIES_LENGTH: u8 = 7;
+pub const MAX_SERIES_WINS: usize = (MAX_SERIES_LENGTH / 2 + 1) as usize;
+
+/// How the starting player is chosen for every game after the first one in a series.
+#[derive(Clone, Copy, PartialEq)]
+pub enum NextGameStart {
+    /// Every game begins with the spinner.
+    Spinner,
+    /// The loser of the previous game goes first and the spinner is skipped.
+    /// After a draw, whoever went second in that game goes first.
+    LoserStarts,
+}
+
+impl NextGameStart {
+    pub const ALL: [Self; 2] = [Self::Spinner, Self::LoserStarts];
+}
+
+/// Tracks the results of a best-of-N series of games.
+/// A series of length 1 is a single game, which behaves like a normal rematch.
+#[derive(Clone, Copy)]
+pub struct MatchSeries {
+    best_of: u8,
+    next_game_start: NextGameStart,
+    red_wins: u8,
+    yellow_wins: u8,
+    next_starting_color: Option<TokenColor>,
+}
+
+impl MatchSeries {
+    pub fn new(best_of: u8, next_game_start: NextGameStart) -> Self {
+        assert!(best_of % 2 == 1, "Series must have an odd number of games.");
+        assert!(best_of <= MAX_SERIES_LENGTH, "Series is too long.");
+
+        Self {
+            best_of,
+            next_game_start,
+            red_wins: 0,
+            yellow_wins: 0,
+            next_starting_color: None,
+        }
+    }
+
+    pub fn best_of(&self) -> u8 {
+        self.best_of
+    }
+
+    pub fn is_single_game(&self) -> bool {
+        self.best_of == 1
+    }
+
+    pub fn wins_required(&self) -> u8 {
+        self.best_of / 2 + 1
+    }
+
+    pub fn wins(&self, token_color: TokenColor) -> u8 {
+        match token_color {
+            TokenColor::Red => self.red_wins,
+            TokenColor::Yellow => self.yellow_wins,
+        }
+    }
+
+    /// Record the result of a game. `winner` is None if the game was a draw.
+    pub fn record_result(&mut self, winner: Option<TokenColor>, starting_color: TokenColor) {
+        match winner {
+            Some(TokenColor::Red) => self.red_wins += 1,
+            Some(TokenColor::Yellow) => self.yellow_wins += 1,
+            None => {}
+        }
+
+        self.next_starting_color = match self.next_game_start {
+            NextGameStart::Spinner => None,
+            NextGameStart::LoserStarts => match winner {
+                Some(winning_color) => Some(winning_color.opposite()),
+                None => Some(starting_color.opposite()),
+            },
+        };
+    }
+
+    /// The player that has won the series, if it has been decided.
+    pub fn winner(&self) -> Option<TokenColor> {
+        let wins_required = self.wins_required();
+
+        if self.red_wins >= wins_required {
+            Some(TokenColor::Red)
+        } else if self.yellow_wins >= wins_required {
+            Some(TokenColor::Yellow)
+        } else {
+            None
+        }
+    }
+
+    /// The color that should start the next game, or None if the spinner should decide.
+    pub fn next_starting_color(&self) -> Option<TokenColor> {
+        self.next_starting_color
+    }
+
+    /// Cycle through the series lengths (1, 3, 5, 7), keeping the format otherwise unchanged.
+    pub fn with_next_length(&self) -> Self {
+        let best_of = if self.best_of >= MAX_SERIES_LENGTH {
+            1
+        } else {
+            self.best_of + 2
+        };
+
+        Self::new(best_of, self.next_game_start)
+    }
+
+    pub fn with_previous_length(&self) -> Self {
+        let best_of = if self.best_of <= 1 {
+            MAX_SERIES_LENGTH
+        } else {
+            self.best_of - 2
+        };
+
+        Self::new(best_of, self.next_game_start)
+    }
+}
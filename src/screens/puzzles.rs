@@ -0,0 +1,60 @@
+use crate::system::{
+    constants::BOARD_COLUMNS,
+    save::{self, PUZZLE_PROGRESS_OFFSET, PUZZLE_PROGRESS_SIZE},
+};
+
+use super::game_screen::TokenColor;
+
+/// A "win in N moves" challenge. The player to move must find a forced win against a
+/// perfect defender, using no more than `moves` of their own moves.
+pub struct Puzzle {
+    pub to_move: TokenColor,
+    pub moves: u8,
+    /// The tokens in each column, from the bottom up.
+    pub columns: [&'static [TokenColor]; BOARD_COLUMNS as usize],
+}
+
+/// Which puzzles have been solved, stored as one bit per puzzle in SRAM.
+pub struct PuzzleProgress {
+    solved: u64,
+}
+
+impl PuzzleProgress {
+    pub fn load() -> Self {
+        let mut bytes = [0; PUZZLE_PROGRESS_SIZE];
+        save::read_bytes(PUZZLE_PROGRESS_OFFSET, &mut bytes);
+
+        Self {
+            solved: u64::from_le_bytes(bytes),
+        }
+    }
+
+    pub fn is_solved(&self, puzzle_index: usize) -> bool {
+        self.solved & (1 << puzzle_index) != 0
+    }
+
+    pub fn mark_solved(&mut self, puzzle_index: usize) {
+        if !self.is_solved(puzzle_index) {
+            self.solved |= 1 << puzzle_index;
+            save::write_bytes(PUZZLE_PROGRESS_OFFSET, &self.solved.to_le_bytes());
+        }
+    }
+
+    /// The puzzle to start on when entering puzzle mode.
+    /// Once everything has been solved this goes back to the first puzzle.
+    pub fn first_unsolved(&self) -> usize {
+        (0..PUZZLES.len())
+            .find(|i| !self.is_solved(*i))
+            .unwrap_or(0)
+    }
+}
+
+pub fn next_puzzle_index(puzzle_index: usize) -> usize {
+    (puzzle_index + 1) % PUZZLES.len()
+}
+
+pub fn previous_puzzle_index(puzzle_index: usize) -> usize {
+    (puzzle_index + PUZZLES.len() - 1) % PUZZLES.len()
+}
+
+include!(concat!(env!("OUT_DIR"), "/puzzle_data.rs"));
@@ -0,0 +1,195 @@
+use crate::{
+    graphics::{
+        background::{BackgroundLayer, LoadedBackground, SCROLLER_BACKGROUND},
+        effects::{background_scroller::BackgroundScroller, blinker::Blinker},
+        sprite::{LoadedObjectEntry, LoadedSprite},
+        text::{TextInk, TextSprite, FONT},
+    },
+    system::{
+        constants::{SCREEN_HEIGHT, SCREEN_WIDTH},
+        gba::{GbaKey, GBA},
+        strings::Message,
+    },
+};
+
+use super::{
+    game_screen::{
+        cpu_face::{CpuEmotion, CpuFace, CpuSprites},
+        cpu_personality::CpuPersonality,
+        TokenColor,
+    },
+    match_series::{MatchSeries, MAX_SERIES_WINS},
+    Screen, ScreenState,
+};
+
+const WIN_TEXT_YPOS: u16 = 40;
+// Enough 32 pixel wide objects for the longest translation of the banner.
+const WIN_TEXT_OBJECTS: usize = 4;
+const SCORE_ROW_YPOS: u16 = 72;
+const SCORE_ROW_SPACING: u16 = 4;
+const SCORE_TOKEN_SPACING: u16 = 2;
+const PRESS_A_OFFSET: u16 = 4;
+// Enough 32 pixel wide objects for the longest translation of the prompt.
+const PRESS_A_OBJECTS: usize = 4;
+const BLINK_TIME_ON: u32 = 40;
+const BLINK_TIME_OFF: u32 = 10;
+
+pub enum SeriesOverMode {
+    VsCpu(CpuPersonality),
+    VsPlayer,
+}
+
+pub struct SeriesOverScreenLoadedData<'a> {
+    winner_name: Message,
+    red_token_sprite: LoadedSprite<'a>,
+    yellow_token_sprite: LoadedSprite<'a>,
+    cpu_sprites: Option<CpuSprites<'a>>,
+    winner: TokenColor,
+}
+
+/// Shown once a best-of-N series has been decided.
+pub struct SeriesOverScreen<'a> {
+    gba: &'a GBA,
+    background_scroller: BackgroundScroller,
+    scrolling_background: LoadedBackground<'a>,
+    win_text: TextSprite<'a, WIN_TEXT_OBJECTS>,
+    _score_token_objects: [[Option<LoadedObjectEntry<'a>>; MAX_SERIES_WINS]; 2],
+    _press_a_text: TextSprite<'a, PRESS_A_OBJECTS>,
+    _cpu_face: Option<CpuFace<'a>>,
+    blinker: Blinker,
+}
+
+impl<'a> SeriesOverScreenLoadedData<'a> {
+    pub fn new(gba: &'a GBA, mode: &SeriesOverMode, series: &MatchSeries) -> Self {
+        let winner = series.winner().expect("Series has not been decided.");
+
+        let winner_name = match (winner, mode) {
+            (TokenColor::Red, _) => Message::Player1,
+            (TokenColor::Yellow, SeriesOverMode::VsCpu(_)) => Message::Cpu,
+            (TokenColor::Yellow, SeriesOverMode::VsPlayer) => Message::Player2,
+        };
+
+        let cpu_sprites = match mode {
+            SeriesOverMode::VsCpu(personality) => Some(CpuSprites::new(gba, *personality)),
+            SeriesOverMode::VsPlayer => None,
+        };
+
+        Self {
+            winner_name,
+            red_token_sprite: TokenColor::Red.sprite().load(gba),
+            yellow_token_sprite: TokenColor::Yellow.sprite().load(gba),
+            cpu_sprites,
+            winner,
+        }
+    }
+}
+
+impl<'a> SeriesOverScreen<'a> {
+    pub fn new(
+        gba: &'a GBA,
+        loaded_data: &'a SeriesOverScreenLoadedData<'a>,
+        series: MatchSeries,
+    ) -> Self {
+        let scrolling_background = SCROLLER_BACKGROUND.load(gba, BackgroundLayer::Bg0);
+        let background_scroller = BackgroundScroller::new(0, 1).with_divisor(2);
+
+        // "{Player} Wins" banner, centred horizontally.
+        let (before, after) = Message::Wins.split();
+        let mut win_text = TextSprite::new(gba);
+        win_text.set_text_parts(&[
+            (before, TextInk::Normal),
+            (loaded_data.winner_name.get(), loaded_data.winner.text_ink()),
+            (after, TextInk::Normal),
+        ]);
+        win_text.set_position((SCREEN_WIDTH - win_text.width()) / 2, WIN_TEXT_YPOS);
+
+        // One row of tokens per player showing the final score.
+        let token_height: u16 = loaded_data
+            .red_token_sprite
+            .sprite()
+            .height()
+            .try_into()
+            .unwrap();
+
+        let score_token_objects = [
+            (TokenColor::Red, &loaded_data.red_token_sprite, 0),
+            (TokenColor::Yellow, &loaded_data.yellow_token_sprite, 1),
+        ]
+        .map(|(color, sprite, row)| {
+            let y_pos = SCORE_ROW_YPOS + row * (token_height + SCORE_ROW_SPACING);
+            create_score_row(gba, sprite, series.wins(color), y_pos)
+        });
+
+        let mut press_a_text = TextSprite::new(gba);
+        press_a_text.set_text(Message::PressA.get());
+        press_a_text.set_position(
+            (SCREEN_WIDTH - press_a_text.width()) / 2,
+            SCREEN_HEIGHT - FONT.line_height() - PRESS_A_OFFSET,
+        );
+
+        let cpu_face = loaded_data.cpu_sprites.as_ref().map(|cpu_sprites| {
+            let mut cpu_face = CpuFace::new(gba, cpu_sprites);
+
+            cpu_face.set_emotion(match loaded_data.winner {
+                TokenColor::Red => CpuEmotion::Sad,
+                TokenColor::Yellow => CpuEmotion::Happy,
+            });
+
+            cpu_face
+        });
+
+        Self {
+            gba,
+            background_scroller,
+            scrolling_background,
+            win_text,
+            _press_a_text: press_a_text,
+            _score_token_objects: score_token_objects,
+            _cpu_face: cpu_face,
+            blinker: Blinker::new(BLINK_TIME_ON, BLINK_TIME_OFF, true),
+        }
+    }
+}
+
+impl<'a> Screen for SeriesOverScreen<'a> {
+    fn update(&mut self) -> Option<ScreenState> {
+        self.background_scroller.update();
+        self.background_scroller
+            .apply_to_background(&self.scrolling_background);
+
+        self.blinker.update();
+        self.blinker.apply_to_text(&mut self.win_text);
+
+        if self.gba.key_was_pressed(GbaKey::A) || self.gba.key_was_pressed(GbaKey::START) {
+            Some(ScreenState::TitleScreen)
+        } else {
+            None
+        }
+    }
+}
+
+fn create_score_row<'a>(
+    gba: &'a GBA,
+    token_sprite: &'a LoadedSprite<'a>,
+    wins: u8,
+    y_pos: u16,
+) -> [Option<LoadedObjectEntry<'a>>; MAX_SERIES_WINS] {
+    let token_width: u16 = token_sprite.sprite().width().try_into().unwrap();
+    let wins_count: u16 = wins.into();
+    let row_width = wins_count * (token_width + SCORE_TOKEN_SPACING);
+    let start_x = SCREEN_WIDTH / 2 - row_width / 2;
+
+    core::array::from_fn(|i| {
+        let i: u16 = i.try_into().unwrap();
+
+        if i < wins_count {
+            let mut obj = token_sprite.create_obj_attr_entry(gba);
+            let oa = obj.get_obj_attr_data();
+            oa.set_x(start_x + i * (token_width + SCORE_TOKEN_SPACING));
+            oa.set_y(y_pos);
+            Some(obj)
+        } else {
+            None
+        }
+    })
+}
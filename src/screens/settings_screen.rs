@@ -0,0 +1,379 @@
+use crate::{
+    audio::{events::SoundEvent, noise::play_menu_move_noise},
+    graphics::{
+        background::{BackgroundLayer, LoadedBackground, SCROLLER_BACKGROUND},
+        effects::background_scroller::BackgroundScroller,
+        sprite::{
+            LoadedAnimation, LoadedObjectEntry, LoadedSprite, BOARD_SLOT_SPRITE,
+            MENU_CURSOR_ANIMATION,
+        },
+        text::{TextSprite, FONT},
+    },
+    system::{
+        constants::SCREEN_WIDTH,
+        gba::{GbaKey, GBA},
+        settings::{self, BoardVariant, CpuDelay, CursorSpeed, Settings, MAX_VOLUME},
+        strings::{Language, Message},
+    },
+    ui::menu::{Menu, MenuEntry, MenuEvent, MenuLayout},
+};
+
+use super::{
+    game_screen::{self, TokenColor},
+    match_series::NextGameStart,
+    Screen, ScreenState,
+};
+
+const NUM_ROWS: usize = 9;
+const MAX_PIPS: usize = MAX_VOLUME as usize;
+
+// The rows that show the name of their value, rather than a row of tokens.
+const NUM_NAMED_ROWS: usize = 3;
+
+const FIRST_ROW_YPOS: u16 = 12;
+const ROW_SPACING: u16 = 16;
+const PIP_SPACING: u16 = 2;
+const PIPS_RIGHT_MARGIN: u16 = 8;
+// Where the menu's cursor sits, in front of the labels.
+const LABELS_X: u16 = 14;
+
+// Enough 32 pixel wide objects for the longest language name, or any other value's name.
+const VALUE_NAME_OBJECTS: usize = 3;
+
+/// One line of the settings screen.
+#[derive(Clone, Copy, PartialEq)]
+enum SettingsRow {
+    MusicVolume,
+    SfxVolume,
+    CursorSpeed,
+    CpuDelay,
+    ColourBlind,
+    ShapeMarkers,
+    Language,
+    NextGameStart,
+    BoardVariant,
+}
+
+const ROWS: [SettingsRow; NUM_ROWS] = [
+    SettingsRow::MusicVolume,
+    SettingsRow::SfxVolume,
+    SettingsRow::CursorSpeed,
+    SettingsRow::CpuDelay,
+    SettingsRow::ColourBlind,
+    SettingsRow::ShapeMarkers,
+    SettingsRow::Language,
+    SettingsRow::NextGameStart,
+    SettingsRow::BoardVariant,
+];
+
+// A and the arrows change the highlighted setting, so choosing a row plays no sound of its own.
+static ROW_ENTRIES: [MenuEntry<SettingsRow>; NUM_ROWS] = [
+    MenuEntry::new(Message::SettingMusic, SettingsRow::MusicVolume).with_sound(None),
+    MenuEntry::new(Message::SettingSound, SettingsRow::SfxVolume).with_sound(None),
+    MenuEntry::new(Message::SettingCursor, SettingsRow::CursorSpeed).with_sound(None),
+    MenuEntry::new(Message::SettingCpuWait, SettingsRow::CpuDelay).with_sound(None),
+    MenuEntry::new(Message::SettingColours, SettingsRow::ColourBlind).with_sound(None),
+    MenuEntry::new(Message::SettingShapes, SettingsRow::ShapeMarkers).with_sound(None),
+    MenuEntry::new(Message::SettingLanguage, SettingsRow::Language).with_sound(None),
+    MenuEntry::new(Message::SettingNextGame, SettingsRow::NextGameStart).with_sound(None),
+    MenuEntry::new(Message::SettingBoard, SettingsRow::BoardVariant).with_sound(None),
+];
+
+pub struct SettingsScreenLoadedData<'a> {
+    filled_pip_sprite: LoadedSprite<'a>,
+    marked_pip_sprite: LoadedSprite<'a>,
+    empty_pip_sprite: LoadedSprite<'a>,
+    cursor_animation: LoadedAnimation<'a, 5>,
+}
+
+/// Lets the player change the settings, which take effect as soon as they are changed.
+/// Each setting is drawn as a row of tokens, filled in up to its current value,
+/// apart from the language and who starts the next game, which are shown by name.
+pub struct SettingsScreen<'a> {
+    gba: &'a GBA,
+    loaded_data: &'a SettingsScreenLoadedData<'a>,
+    background_scroller: BackgroundScroller,
+    scrolling_background: LoadedBackground<'a>,
+    rows: Menu<'a, SettingsRow, NUM_ROWS>,
+    value_names: [TextSprite<'a, VALUE_NAME_OBJECTS>; NUM_NAMED_ROWS],
+    pips: [[LoadedObjectEntry<'a>; MAX_PIPS]; NUM_ROWS],
+    settings: Settings,
+}
+
+impl SettingsRow {
+    /// How many values the setting can take, not counting zero.
+    fn num_pips(&self) -> u8 {
+        match self {
+            SettingsRow::MusicVolume | SettingsRow::SfxVolume => MAX_VOLUME,
+            SettingsRow::CursorSpeed => CursorSpeed::ALL.len().try_into().unwrap(),
+            SettingsRow::CpuDelay => CpuDelay::ALL.len().try_into().unwrap(),
+            SettingsRow::ColourBlind | SettingsRow::ShapeMarkers => 1,
+            SettingsRow::Language => Language::count().try_into().unwrap(),
+            SettingsRow::NextGameStart => NextGameStart::ALL.len().try_into().unwrap(),
+            SettingsRow::BoardVariant => BoardVariant::ALL.len().try_into().unwrap(),
+        }
+    }
+
+    fn shows_pips(&self) -> bool {
+        !matches!(
+            self,
+            SettingsRow::Language | SettingsRow::NextGameStart | SettingsRow::BoardVariant
+        )
+    }
+
+    /// The name shown in place of the tokens, for the rows without any.
+    fn value_name(&self, settings: &Settings) -> &'static str {
+        match self {
+            SettingsRow::Language => settings.language.name(),
+            SettingsRow::NextGameStart => match settings.next_game_start {
+                NextGameStart::Spinner => Message::NextGameSpinner.get(),
+                NextGameStart::LoserStarts => Message::NextGameLoser.get(),
+            },
+            SettingsRow::BoardVariant => match settings.board_variant {
+                BoardVariant::Day => Message::BoardDay.get(),
+                BoardVariant::Dusk => Message::BoardDusk.get(),
+                BoardVariant::Night => Message::BoardNight.get(),
+            },
+            _ => "",
+        }
+    }
+
+    /// The lowest value the setting can take. Volumes can be turned down to nothing,
+    /// but the speeds always show at least one token.
+    fn min_value(&self) -> u8 {
+        match self {
+            SettingsRow::CursorSpeed
+            | SettingsRow::CpuDelay
+            | SettingsRow::Language
+            | SettingsRow::NextGameStart
+            | SettingsRow::BoardVariant => 1,
+            _ => 0,
+        }
+    }
+
+    fn get_value(&self, settings: &Settings) -> u8 {
+        match self {
+            SettingsRow::MusicVolume => settings.music_volume,
+            SettingsRow::SfxVolume => settings.sfx_volume,
+            SettingsRow::CursorSpeed => settings.cursor_speed as u8 + 1,
+            SettingsRow::CpuDelay => settings.cpu_delay as u8 + 1,
+            SettingsRow::ColourBlind => settings.colour_blind.into(),
+            SettingsRow::ShapeMarkers => settings.shape_markers.into(),
+            SettingsRow::Language => settings.language.index() + 1,
+            SettingsRow::NextGameStart => settings.next_game_start as u8 + 1,
+            SettingsRow::BoardVariant => settings.board_variant as u8 + 1,
+        }
+    }
+
+    fn set_value(&self, settings: &mut Settings, value: u8) {
+        let index = usize::from(value.saturating_sub(1));
+
+        match self {
+            SettingsRow::MusicVolume => settings.music_volume = value,
+            SettingsRow::SfxVolume => settings.sfx_volume = value,
+            SettingsRow::CursorSpeed => settings.cursor_speed = CursorSpeed::ALL[index],
+            SettingsRow::CpuDelay => settings.cpu_delay = CpuDelay::ALL[index],
+            SettingsRow::ColourBlind => settings.colour_blind = value == 1,
+            SettingsRow::ShapeMarkers => settings.shape_markers = value == 1,
+            SettingsRow::Language => {
+                settings.language = Language::from_index(index.try_into().unwrap()).unwrap()
+            }
+            SettingsRow::NextGameStart => settings.next_game_start = NextGameStart::ALL[index],
+            SettingsRow::BoardVariant => settings.board_variant = BoardVariant::ALL[index],
+        }
+    }
+}
+
+impl<'a> SettingsScreenLoadedData<'a> {
+    pub fn new(gba: &'a GBA) -> Self {
+        Self {
+            filled_pip_sprite: TokenColor::Red.sprite_with_markers(false).load(gba),
+            marked_pip_sprite: TokenColor::Red.sprite_with_markers(true).load(gba),
+            empty_pip_sprite: BOARD_SLOT_SPRITE.load(gba),
+            cursor_animation: MENU_CURSOR_ANIMATION.load(gba),
+        }
+    }
+}
+
+impl<'a> SettingsScreen<'a> {
+    pub fn new(gba: &'a GBA, loaded_data: &'a SettingsScreenLoadedData<'a>) -> Self {
+        let scrolling_background = SCROLLER_BACKGROUND.load(gba, BackgroundLayer::Bg0);
+        let background_scroller = BackgroundScroller::new(0, 1).with_divisor(2);
+
+        // The labels line up with the tokens in their rows.
+        let mut rows = Menu::new(
+            gba,
+            &loaded_data.cursor_animation,
+            &ROW_ENTRIES,
+            MenuLayout::Vertical {
+                x: LABELS_X,
+                y: FIRST_ROW_YPOS + get_label_offset(),
+                spacing: ROW_SPACING,
+            },
+        )
+        .with_wraparound();
+        rows.show();
+
+        let pips = core::array::from_fn(|row| {
+            core::array::from_fn(|i| {
+                let mut obj = loaded_data.empty_pip_sprite.create_obj_attr_entry(gba);
+                let (x, y) = get_pip_position(row, i);
+                let oa = obj.get_obj_attr_data();
+                oa.set_x(x);
+                oa.set_y(y);
+
+                if ROWS[row].shows_pips() && i < ROWS[row].num_pips().into() {
+                    obj.with_visible()
+                } else {
+                    obj.with_hidden()
+                }
+            })
+        });
+
+        let mut screen = Self {
+            gba,
+            loaded_data,
+            background_scroller,
+            scrolling_background,
+            rows,
+            value_names: core::array::from_fn(|_| TextSprite::new(gba)),
+            pips,
+            settings: settings::current(),
+        };
+
+        for row in 0..NUM_ROWS {
+            screen.update_pips(row);
+        }
+        screen.update_value_names();
+
+        screen
+    }
+
+    /// The menu moves between the rows, and picking one with A steps through its values.
+    /// Left and right change the value of whichever row is highlighted.
+    fn change_value(&mut self, event: Option<MenuEvent<SettingsRow>>) {
+        let row = match self.rows.highlighted() {
+            Some(row) => row,
+            None => return,
+        };
+        let value = row.get_value(&self.settings);
+
+        let new_value = if self.gba.key_was_pressed(GbaKey::LEFT) {
+            value.saturating_sub(1).max(row.min_value())
+        } else if self.gba.key_was_pressed(GbaKey::RIGHT) {
+            (value + 1).min(row.num_pips())
+        } else if matches!(event, Some(MenuEvent::Selected(_))) {
+            // Step through the values, wrapping back round from the highest.
+            if value == row.num_pips() {
+                row.min_value()
+            } else {
+                value + 1
+            }
+        } else {
+            return;
+        };
+
+        row.set_value(&mut self.settings, new_value);
+        self.settings.apply();
+
+        let row_index = ROWS.iter().position(|r| *r == row).unwrap();
+
+        match row {
+            // The filled pips on every row change to show the new setting.
+            SettingsRow::ShapeMarkers => (0..NUM_ROWS).for_each(|row| self.update_pips(row)),
+            SettingsRow::Language => {
+                self.rows.refresh();
+                self.update_value_names();
+            }
+            SettingsRow::NextGameStart | SettingsRow::BoardVariant => self.update_value_names(),
+            _ => self.update_pips(row_index),
+        }
+
+        // Played after applying, so that it is heard at the new sound effect volume.
+        play_menu_move_noise();
+    }
+
+    fn update_pips(&mut self, row: usize) {
+        if !ROWS[row].shows_pips() {
+            return;
+        }
+
+        let value: usize = ROWS[row].get_value(&self.settings).into();
+        let filled_pip_sprite = if self.settings.shape_markers {
+            &self.loaded_data.marked_pip_sprite
+        } else {
+            &self.loaded_data.filled_pip_sprite
+        };
+
+        for (i, pip) in self.pips[row]
+            .iter_mut()
+            .take(ROWS[row].num_pips().into())
+            .enumerate()
+        {
+            if i < value {
+                filled_pip_sprite.store_in_obj_entry(pip);
+            } else {
+                self.loaded_data.empty_pip_sprite.store_in_obj_entry(pip);
+            }
+        }
+    }
+
+    /// Write out the value names in the current language.
+    fn update_value_names(&mut self) {
+        // The names take the place of the tokens, lined up with their right edge.
+        let named_rows = ROWS
+            .iter()
+            .enumerate()
+            .filter(|(_, setting)| !setting.shows_pips());
+
+        for ((row, setting), name) in named_rows.zip(self.value_names.iter_mut()) {
+            let (_, y) = get_pip_position(row, 0);
+
+            name.set_text(setting.value_name(&self.settings));
+            let x = SCREEN_WIDTH - PIPS_RIGHT_MARGIN - name.width();
+            name.set_position(x, y + get_label_offset());
+        }
+    }
+}
+
+impl<'a> Screen for SettingsScreen<'a> {
+    fn update(&mut self) -> Option<ScreenState> {
+        self.background_scroller.update();
+        self.background_scroller
+            .apply_to_background(&self.scrolling_background);
+
+        // START would also pick the highlighted row, so leaving is checked first.
+        if self.gba.key_was_pressed(GbaKey::B) || self.gba.key_was_pressed(GbaKey::START) {
+            self.settings.save();
+            SoundEvent::Confirm.play();
+
+            // Opened from the pause menu, the game carries on where it was left.
+            Some(game_screen::suspended_game_screen().unwrap_or(ScreenState::TitleScreen))
+        } else {
+            let event = self.rows.update();
+            self.change_value(event);
+            None
+        }
+    }
+}
+
+/// The top-left corner of a token in the given row, with the widest row against the right edge.
+fn get_pip_position(row: usize, index: usize) -> (u16, u16) {
+    let pip_width: u16 = BOARD_SLOT_SPRITE.width().try_into().unwrap();
+    let max_pips: u16 = MAX_PIPS.try_into().unwrap();
+    let row_width = max_pips * (pip_width + PIP_SPACING) - PIP_SPACING;
+
+    let row: u16 = row.try_into().unwrap();
+    let index: u16 = index.try_into().unwrap();
+
+    let x = SCREEN_WIDTH - row_width - PIPS_RIGHT_MARGIN + index * (pip_width + PIP_SPACING);
+    let y = FIRST_ROW_YPOS + row * ROW_SPACING;
+
+    (x, y)
+}
+
+/// How far down a label sits from the top of its row, so that it lines up with the tokens.
+fn get_label_offset() -> u16 {
+    let pip_height: u16 = BOARD_SLOT_SPRITE.height().try_into().unwrap();
+    pip_height.saturating_sub(FONT.line_height()) / 2
+}
@@ -1,24 +1,26 @@
 use gba::{prelude::TIMER3_COUNT, random::Lcg32};
 
 use crate::{
-    audio::drum_roll::DrumRoll,
+    audio::{drum_roll::DrumRoll, events::SoundEvent, mixer::PlaybackRate},
     graphics::effects::spinner::Spinner,
     graphics::{
         background::{BackgroundLayer, LoadedBackground, SCROLLER_BACKGROUND, SPINNER_BACKGROUND},
         effects::{background_scroller::BackgroundScroller, blinker::Blinker},
-        sprite::{
-            AffineLoadedObjectEntry, AnimationController, LoadedAnimation, LoadedObjectEntry,
-            LoadedSprite, CPU_TEXT_SPRITE, P1_TEXT_SPRITE, P2_TEXT_SPRITE, PRESS_A_ANIMATION,
-            PRESS_A_FRAME_0_SPRITE, SPINNER_ARROW_SPRITE,
-        },
+        sprite::{AffineLoadedObjectEntry, LoadedSprite, SPINNER_ARROW_SPRITE},
+        text::{TextSprite, FONT},
     },
     system::{
         constants::{SCREEN_HEIGHT, SCREEN_WIDTH},
         gba::{GbaKey, GBA},
+        strings::Message,
     },
 };
 
-use super::{game_screen::TokenColor, Screen, ScreenState};
+use super::{
+    game_screen::{cpu_personality::CpuPersonality, TokenColor},
+    match_series::MatchSeries,
+    Screen, ScreenState,
+};
 
 const ARROW_POSITION: (u16, u16) = (56, 32);
 const PRESS_A_OFFSET: u16 = 4;
@@ -27,9 +29,15 @@ const PLAYER_TEXT_Y_POSITION: u16 = 85;
 const FINISHED_STATE_TIME: u32 = 100;
 const BLINK_TIME_ON: u32 = 22;
 const BLINK_TIME_OFF: u32 = 8;
+const PRESS_A_BLINK_TIME_ON: u32 = 40;
+const PRESS_A_BLINK_TIME_OFF: u32 = 10;
+
+// Enough 32 pixel wide objects for the longest translation of each piece of text.
+const PLAYER_TEXT_OBJECTS: usize = 2;
+const PRESS_A_OBJECTS: usize = 4;
 
 pub enum SpinnerMode {
-    VsCpu,
+    VsCpu(CpuPersonality),
     VsPlayer,
 }
 
@@ -48,9 +56,6 @@ enum SpinnerScreenState {
 
 pub struct SpinnerScreenLoadedData<'a> {
     loaded_sprite: LoadedSprite<'a>,
-    press_a_animation: LoadedAnimation<'a, 2>,
-    red_player_icon: LoadedSprite<'a>,
-    yellow_player_icon: LoadedSprite<'a>,
 }
 
 pub struct SpinnerScreen<'a> {
@@ -59,34 +64,23 @@ pub struct SpinnerScreen<'a> {
     _spinner_background: LoadedBackground<'a>,
     scrolling_background: LoadedBackground<'a>,
     state: SpinnerScreenState,
-    press_a_animation_controller: AnimationController<'a, 2>,
+    press_a_text: TextSprite<'a, PRESS_A_OBJECTS>,
+    press_a_blinker: Blinker,
     mode: SpinnerMode,
+    series: MatchSeries,
     gba: &'a GBA,
-    red_player_obj: LoadedObjectEntry<'a>,
-    yellow_player_obj: LoadedObjectEntry<'a>,
+    red_player_text: TextSprite<'a, PLAYER_TEXT_OBJECTS>,
+    yellow_player_text: TextSprite<'a, PLAYER_TEXT_OBJECTS>,
     blinker: Blinker,
     background_scroller: BackgroundScroller,
     drum_roll: DrumRoll,
 }
 
 impl<'a> SpinnerScreenLoadedData<'a> {
-    pub fn new(gba: &'a GBA, mode: &SpinnerMode) -> Self {
+    pub fn new(gba: &'a GBA) -> Self {
         let loaded_sprite = SPINNER_ARROW_SPRITE.load(gba);
-        let press_a_animation = PRESS_A_ANIMATION.load(gba);
-
-        let red_player_icon = P1_TEXT_SPRITE.load(gba);
-        let yellow_player_icon = match mode {
-            SpinnerMode::VsCpu => &CPU_TEXT_SPRITE,
-            SpinnerMode::VsPlayer => &P2_TEXT_SPRITE,
-        }
-        .load(gba);
 
-        Self {
-            loaded_sprite,
-            press_a_animation,
-            red_player_icon,
-            yellow_player_icon,
-        }
+        Self { loaded_sprite }
     }
 }
 
@@ -114,7 +108,12 @@ impl<'a> Screen for SpinnerScreen<'a> {
 }
 
 impl<'a> SpinnerScreen<'a> {
-    pub fn new(gba: &'a GBA, loaded_data: &'a SpinnerScreenLoadedData, mode: SpinnerMode) -> Self {
+    pub fn new(
+        gba: &'a GBA,
+        loaded_data: &'a SpinnerScreenLoadedData,
+        mode: SpinnerMode,
+        series: MatchSeries,
+    ) -> Self {
         let mut arrow_sprite = loaded_data
             .loaded_sprite
             .create_obj_attr_entry(gba)
@@ -131,32 +130,33 @@ impl<'a> SpinnerScreen<'a> {
         let spinner_background = SPINNER_BACKGROUND.load(gba, BackgroundLayer::Bg1);
         let scrolling_background = SCROLLER_BACKGROUND.load(gba, BackgroundLayer::Bg0);
 
-        let mut press_a_animation_controller = loaded_data.press_a_animation.create_controller(gba);
-        let press_a_height: u16 = PRESS_A_FRAME_0_SPRITE.height().try_into().unwrap();
-        let press_a_width: u16 = PRESS_A_FRAME_0_SPRITE.width().try_into().unwrap();
-
-        let press_a_obj = press_a_animation_controller.get_obj_attr_entry();
-        let press_a_oa = press_a_obj.get_obj_attr_data();
-        press_a_oa.set_x(SCREEN_WIDTH - press_a_width - PRESS_A_OFFSET);
-        press_a_oa.set_y(SCREEN_HEIGHT - press_a_height - PRESS_A_OFFSET);
-
-        let mut red_player_obj = loaded_data.red_player_icon.create_obj_attr_entry(gba);
-        let red_player_text_width: u16 = red_player_obj
-            .loaded_sprite()
-            .sprite()
-            .width()
-            .try_into()
-            .unwrap();
-
-        let red_player_oa = red_player_obj.get_obj_attr_data();
-        red_player_oa.set_x((SCREEN_WIDTH / 2) - PLAYER_TEXT_X_OFFSET - red_player_text_width);
-        red_player_oa.set_y(PLAYER_TEXT_Y_POSITION);
-
-        let mut yellow_player_obj = loaded_data.yellow_player_icon.create_obj_attr_entry(gba);
-        let yellow_player_oa = yellow_player_obj.get_obj_attr_data();
+        let mut press_a_text = TextSprite::new(gba);
+        press_a_text.set_text(Message::PressA.get());
+        press_a_text.set_position(
+            SCREEN_WIDTH - press_a_text.width() - PRESS_A_OFFSET,
+            SCREEN_HEIGHT - FONT.line_height() - PRESS_A_OFFSET,
+        );
+
+        let mut red_player_text = TextSprite::new(gba);
+        red_player_text.set_ink(TokenColor::Red.text_ink());
+        red_player_text.set_text(Message::Player1.get());
+        red_player_text.set_position(
+            (SCREEN_WIDTH / 2) - PLAYER_TEXT_X_OFFSET - red_player_text.width(),
+            PLAYER_TEXT_Y_POSITION,
+        );
+
+        let yellow_player_name = match mode {
+            SpinnerMode::VsCpu(_) => Message::Cpu,
+            SpinnerMode::VsPlayer => Message::Player2,
+        };
 
-        yellow_player_oa.set_x((SCREEN_WIDTH / 2) + PLAYER_TEXT_X_OFFSET);
-        yellow_player_oa.set_y(PLAYER_TEXT_Y_POSITION);
+        let mut yellow_player_text = TextSprite::new(gba);
+        yellow_player_text.set_ink(TokenColor::Yellow.text_ink());
+        yellow_player_text.set_text(yellow_player_name.get());
+        yellow_player_text.set_position(
+            (SCREEN_WIDTH / 2) + PLAYER_TEXT_X_OFFSET,
+            PLAYER_TEXT_Y_POSITION,
+        );
 
         let blinker = Blinker::new(BLINK_TIME_ON, BLINK_TIME_OFF, false);
         let background_scroller = BackgroundScroller::new(0, 1).with_divisor(2);
@@ -167,10 +167,12 @@ impl<'a> SpinnerScreen<'a> {
             gba,
             arrow_sprite,
             spinner,
-            press_a_animation_controller,
+            press_a_text,
+            press_a_blinker: Blinker::new(PRESS_A_BLINK_TIME_ON, PRESS_A_BLINK_TIME_OFF, true),
             mode,
-            red_player_obj,
-            yellow_player_obj,
+            series,
+            red_player_text,
+            yellow_player_text,
             blinker,
             scrolling_background,
             background_scroller,
@@ -181,15 +183,18 @@ impl<'a> SpinnerScreen<'a> {
     }
 
     fn update_press_a(&mut self) {
-        self.press_a_animation_controller.tick();
+        self.press_a_blinker.update();
+        self.press_a_blinker.apply_to_text(&mut self.press_a_text);
+
         if self.gba.key_was_pressed(GbaKey::A) {
+            SoundEvent::Confirm.play();
             self.enter_spinning_state();
         }
     }
 
     fn enter_spinning_state(&mut self) {
         // Hide the press a indicator.
-        self.press_a_animation_controller.set_hidden();
+        self.press_a_text.set_hidden();
 
         let seed: u32 = TIMER3_COUNT.read().into();
         let mut rng = Lcg32::new(seed);
@@ -213,6 +218,11 @@ impl<'a> SpinnerScreen<'a> {
 
         self.drum_roll.set_delay(drum_delay.into());
 
+        // The drums also get higher pitched the faster the spinner goes, up to double speed.
+        let speed: u32 = self.spinner.speed().into();
+        self.drum_roll
+            .set_rate(PlaybackRate::from_ratio(0x1000 + speed, 0x1000));
+
         if self.spinner.finished() {
             self.enter_finished_state();
         }
@@ -229,6 +239,8 @@ impl<'a> SpinnerScreen<'a> {
             TokenColor::Yellow
         };
 
+        SoundEvent::SpinnerStop.play();
+
         let state = FinishedState {
             timer: FINISHED_STATE_TIME,
             color,
@@ -241,13 +253,13 @@ impl<'a> SpinnerScreen<'a> {
         state.timer -= 1;
 
         // apply blinker.
-        let target_obj = match state.color {
-            TokenColor::Red => &mut self.red_player_obj,
-            TokenColor::Yellow => &mut self.yellow_player_obj,
+        let target_text = match state.color {
+            TokenColor::Red => &mut self.red_player_text,
+            TokenColor::Yellow => &mut self.yellow_player_text,
         };
 
         self.blinker.update();
-        self.blinker.apply_to_object(target_obj);
+        self.blinker.apply_to_text(target_text);
 
         let should_transition = state.timer == 0;
 
@@ -257,8 +269,10 @@ impl<'a> SpinnerScreen<'a> {
 
         if should_transition {
             let next_screen = match self.mode {
-                SpinnerMode::VsCpu => ScreenState::VsCpuScreen(starting_color),
-                SpinnerMode::VsPlayer => ScreenState::VsPlayerScreen(starting_color),
+                SpinnerMode::VsCpu(personality) => {
+                    ScreenState::VsCpuScreen(starting_color, self.series, personality)
+                }
+                SpinnerMode::VsPlayer => ScreenState::VsPlayerScreen(starting_color, self.series),
             };
             Some(next_screen)
         } else {
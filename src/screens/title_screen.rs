@@ -1,45 +1,91 @@
-use gba::prelude::ObjDisplayStyle;
-
 use crate::{
-    audio::noise::play_menu_move_noise,
+    audio::{events::SoundEvent, noise::play_menu_move_noise},
     graphics::{
         background::{
             BackgroundLayer, LoadedBackground, SCROLLER_BACKGROUND, TITLE_SCREEN_BACKGROUND,
         },
-        effects::{background_scroller::BackgroundScroller, blinker::Blinker},
+        effects::{
+            background_scroller::BackgroundScroller,
+            blinker::Blinker,
+            scanline::{ScanlineEffect, ScanlineTarget},
+        },
         sprite::{
-            AnimationController, LoadedAnimation, LoadedObjectEntry, LoadedSprite,
-            MENU_CURSOR_ANIMATION, PRESS_TEXT_SPRITE, START_TEXT_SPRITE, VS_CPU_TEXT_SPRITE,
-            VS_PLAYER_TEXT_SPRITE,
+            LoadedAnimation, LoadedObjectEntry, LoadedSprite, MENU_CURSOR_ANIMATION,
+            RED_TOKEN_FRAME_0_SPRITE,
         },
+        palette::{self, PaletteKind},
+        text::{TextInk, TextSprite, FONT},
     },
     system::{
         constants::SCREEN_WIDTH,
         gba::{GbaKey, GBA},
+        settings,
+        strings::Message,
     },
+    ui::menu::{Menu, MenuEntry, MenuEvent, MenuLayout},
 };
 
 use super::{
-    game_screen::cpu_face::{CpuEmotion, CpuFace, CpuSprites},
+    game_screen::{
+        cpu_face::{CpuEmotion, CpuFace, CpuSprites},
+        cpu_personality::CpuPersonality,
+        TokenColor,
+    },
+    match_series::{MatchSeries, MAX_SERIES_LENGTH},
+    puzzles::PuzzleProgress,
     Screen, ScreenState,
 };
 
 const MENU_TEXT_Y: u16 = 140;
-const MENU_TEXT_HORIZ_MARGIN: u16 = 12;
-const CURSOR_X_OFFSET: u16 = 10;
+const MENU_ENTRIES: usize = 3;
+
+// Enough 32 pixel wide objects for the longest translation of the text.
+const PRESS_START_OBJECTS: usize = 4;
 
 const BLINK_TIME_ON: u32 = 40;
 const BLINK_TIME_OFF: u32 = 10;
+// How long the prompt shows each of the font's glow colours.
+const PRESS_START_GLOW_FRAMES: u16 = 6;
 
 const CPU_HEAD_POS: (u16, u16) = (140, 52);
 const GAME_TRANSITION_TIME: u16 = 40;
 
-#[derive(Clone, Debug)]
-enum MenuEntry {
+// The series length is shown as a row of tokens above the menu, one per game.
+const SERIES_LENGTH_Y: u16 = 120;
+const SERIES_LENGTH_TOKEN_SPACING: u16 = 2;
+const NUM_SERIES_LENGTH_OBJECTS: usize = MAX_SERIES_LENGTH as usize;
+
+// The logo ripples gently from side to side, a line at a time.
+const LOGO_WAVE_AMPLITUDE: i16 = 2;
+const LOGO_WAVE_LENGTH: u16 = 64;
+// How far the wave moves along each frame, where 0x10000 is a full wave.
+const LOGO_WAVE_SPEED: u16 = 0x200;
+
+#[derive(Clone, Copy, PartialEq)]
+enum MenuChoice {
     VsCpu,
     VsPlayer,
+    Puzzles,
+    Settings,
 }
 
+#[derive(Clone, Copy)]
+enum GameMode {
+    VsCpu,
+    VsPlayer,
+}
+
+static MORE_MENU: [MenuEntry<MenuChoice>; 2] = [
+    MenuEntry::new(Message::Puzzles, MenuChoice::Puzzles),
+    MenuEntry::new(Message::Settings, MenuChoice::Settings),
+];
+
+static MAIN_MENU: [MenuEntry<MenuChoice>; MENU_ENTRIES] = [
+    MenuEntry::new(Message::VsCpu, MenuChoice::VsCpu),
+    MenuEntry::new(Message::VsPlayer, MenuChoice::VsPlayer),
+    MenuEntry::submenu(Message::More, &MORE_MENU),
+];
+
 #[derive(Clone)]
 struct PressStartState {
     blinker: Blinker,
@@ -47,12 +93,13 @@ struct PressStartState {
 
 #[derive(Clone)]
 struct MenuState {
-    cursor_position: MenuEntry,
+    series: MatchSeries,
 }
 
 #[derive(Clone)]
 struct TransitionState {
-    game_mode: MenuEntry,
+    game_mode: GameMode,
+    series: MatchSeries,
     timer: u16,
 }
 
@@ -65,53 +112,37 @@ enum TitleScreenState {
 
 pub struct TitleScreen<'a> {
     gba: &'a GBA,
-    press_text_object: LoadedObjectEntry<'a>,
-    start_text_object: LoadedObjectEntry<'a>,
-    vs_cpu_text_object: LoadedObjectEntry<'a>,
-    vs_player_text_object: LoadedObjectEntry<'a>,
-    cursor_animation_controller: AnimationController<'a, 5>,
+    press_start_text: TextSprite<'a, PRESS_START_OBJECTS>,
+    menu: Menu<'a, MenuChoice, MENU_ENTRIES>,
+    series_length_objects: [LoadedObjectEntry<'a>; NUM_SERIES_LENGTH_OBJECTS],
     scrolling_background: LoadedBackground<'a>,
     _logo_background: LoadedBackground<'a>,
     state: TitleScreenState,
     cpu_face: CpuFace<'a>,
     background_scroller: BackgroundScroller,
+    logo_wave: ScanlineEffect,
+    logo_wave_phase: u16,
 }
 
 pub struct TitleScreenLoadedData<'a> {
-    press_text_sprite: LoadedSprite<'a>,
-    start_text_sprite: LoadedSprite<'a>,
-    vs_cpu_text_sprite: LoadedSprite<'a>,
-    vs_player_text_sprite: LoadedSprite<'a>,
     cursor_animation: LoadedAnimation<'a, 5>,
     cpu_sprites: CpuSprites<'a>,
-}
-
-impl MenuEntry {
-    fn next(&self) -> Self {
-        match self {
-            Self::VsCpu => Self::VsPlayer,
-            Self::VsPlayer => Self::VsCpu,
-        }
-    }
+    red_token_sprite: LoadedSprite<'a>,
+    yellow_token_sprite: LoadedSprite<'a>,
 }
 
 impl<'a> TitleScreenLoadedData<'a> {
     pub fn new(gba: &'a GBA) -> Self {
-        let press_text_sprite = PRESS_TEXT_SPRITE.load(gba);
-        let start_text_sprite = START_TEXT_SPRITE.load(gba);
-
-        let vs_player_text_sprite = VS_PLAYER_TEXT_SPRITE.load(gba);
-        let vs_cpu_text_sprite = VS_CPU_TEXT_SPRITE.load(gba);
         let cursor_animation = MENU_CURSOR_ANIMATION.load(gba);
-        let cpu_sprites = CpuSprites::new(gba);
+        let cpu_sprites = CpuSprites::new(gba, CpuPersonality::Defensive);
+        let red_token_sprite = TokenColor::Red.sprite().load(gba);
+        let yellow_token_sprite = TokenColor::Yellow.sprite().load(gba);
 
         Self {
-            press_text_sprite,
-            start_text_sprite,
-            vs_player_text_sprite,
-            vs_cpu_text_sprite,
             cursor_animation,
             cpu_sprites,
+            red_token_sprite,
+            yellow_token_sprite,
         }
     }
 }
@@ -121,52 +152,36 @@ impl<'a> TitleScreen<'a> {
         let logo_background = TITLE_SCREEN_BACKGROUND.load(gba, BackgroundLayer::Bg1);
         let scrolling_background = SCROLLER_BACKGROUND.load(gba, BackgroundLayer::Bg0);
 
-        let mut press_text_object = loaded_data.press_text_sprite.create_obj_attr_entry(gba);
-
-        let press_oa = press_text_object.get_obj_attr_data();
-        press_oa.set_x(85);
-        press_oa.set_y(MENU_TEXT_Y);
-
-        let mut start_text_object = loaded_data.start_text_sprite.create_obj_attr_entry(gba);
-
-        let start_oa = start_text_object.get_obj_attr_data();
-        start_oa.set_x(120);
-        start_oa.set_y(MENU_TEXT_Y);
-
-        let mut vs_cpu_text_object = loaded_data.vs_cpu_text_sprite.create_obj_attr_entry(gba);
-        let vs_cpu_sprite_width: u16 = loaded_data
-            .vs_cpu_text_sprite
-            .sprite()
-            .width()
-            .try_into()
-            .unwrap();
-
-        let vs_cpu_oa = vs_cpu_text_object.get_obj_attr_data();
-        vs_cpu_oa.set_style(ObjDisplayStyle::NotDisplayed);
-        vs_cpu_oa.set_x(SCREEN_WIDTH / 4 - vs_cpu_sprite_width / 2 + MENU_TEXT_HORIZ_MARGIN);
-        vs_cpu_oa.set_y(MENU_TEXT_Y);
-
-        let mut vs_player_text_object =
-            loaded_data.vs_player_text_sprite.create_obj_attr_entry(gba);
-        let vs_player_sprite_width: u16 = loaded_data
-            .vs_player_text_sprite
-            .sprite()
-            .width()
-            .try_into()
-            .unwrap();
-        let vs_player_oa = vs_player_text_object.get_obj_attr_data();
-        vs_player_oa.set_style(ObjDisplayStyle::NotDisplayed);
-        vs_player_oa.set_x(
-            SCREEN_WIDTH / 4 - vs_player_sprite_width / 2 + SCREEN_WIDTH / 2
-                - MENU_TEXT_HORIZ_MARGIN,
-        );
-        vs_player_oa.set_y(MENU_TEXT_Y);
+        let mut press_start_text = TextSprite::new(gba);
+        press_start_text.set_ink(TextInk::Glow);
+        press_start_text.set_text(Message::PressStart.get());
+        palette::start_cycle(FONT.glow_cycle(PRESS_START_GLOW_FRAMES));
+        press_start_text.set_position((SCREEN_WIDTH - press_start_text.width()) / 2, MENU_TEXT_Y);
 
-        let mut cursor_animation_controller = loaded_data.cursor_animation.create_controller(gba);
-        cursor_animation_controller.set_hidden();
-        let cursor_obj = cursor_animation_controller.get_obj_attr_entry();
-        let cursor_oa = cursor_obj.get_obj_attr_data();
-        cursor_oa.set_y(MENU_TEXT_Y + 2);
+        let menu = Menu::new(
+            gba,
+            &loaded_data.cursor_animation,
+            &MAIN_MENU,
+            MenuLayout::Horizontal {
+                x: 0,
+                y: MENU_TEXT_Y,
+                width: SCREEN_WIDTH,
+            },
+        )
+        .with_cancel();
+
+        // Alternate the token colours so the number of games is easy to count.
+        let series_length_objects = core::array::from_fn(|i| {
+            let sprite = if i % 2 == 0 {
+                &loaded_data.red_token_sprite
+            } else {
+                &loaded_data.yellow_token_sprite
+            };
+
+            let mut obj = sprite.create_obj_attr_entry(gba).with_hidden();
+            obj.get_obj_attr_data().set_y(SERIES_LENGTH_Y);
+            obj
+        });
 
         let state = TitleScreenState::PressStart(PressStartState {
             blinker: Blinker::new(BLINK_TIME_ON, BLINK_TIME_OFF, true),
@@ -177,19 +192,20 @@ impl<'a> TitleScreen<'a> {
         cpu_face.set_y(CPU_HEAD_POS.1);
 
         let background_scroller = BackgroundScroller::new(0, 1).with_divisor(2);
+        let logo_wave = ScanlineEffect::new(ScanlineTarget::HorizontalScroll(BackgroundLayer::Bg1));
 
         Self {
             gba,
-            press_text_object,
-            start_text_object,
+            press_start_text,
             state,
-            vs_cpu_text_object,
-            vs_player_text_object,
-            cursor_animation_controller,
+            menu,
+            series_length_objects,
             cpu_face,
             scrolling_background,
             background_scroller,
             _logo_background: logo_background,
+            logo_wave,
+            logo_wave_phase: 0,
         }
     }
 
@@ -197,11 +213,7 @@ impl<'a> TitleScreen<'a> {
         press_start_state.blinker.update();
         press_start_state
             .blinker
-            .apply_to_object(&mut self.press_text_object);
-
-        press_start_state
-            .blinker
-            .apply_to_object(&mut self.start_text_object);
+            .apply_to_text(&mut self.press_start_text);
 
         self.state = TitleScreenState::PressStart(press_start_state);
 
@@ -210,30 +222,60 @@ impl<'a> TitleScreen<'a> {
         }
     }
 
-    fn update_menu(&mut self, mut menu_state: MenuState) {
-        if self.gba.key_was_pressed(GbaKey::LEFT) || self.gba.key_was_pressed(GbaKey::RIGHT) {
+    fn update_menu(&mut self, mut menu_state: MenuState) -> Option<ScreenState> {
+        if self.gba.key_was_pressed(GbaKey::UP) {
             play_menu_move_noise();
-            menu_state.cursor_position = menu_state.cursor_position.next();
+            menu_state.series = menu_state.series.with_next_length();
+        } else if self.gba.key_was_pressed(GbaKey::DOWN) {
+            play_menu_move_noise();
+            menu_state.series = menu_state.series.with_previous_length();
+        }
+
+        // R and SELECT are shortcuts to the entries in the "More" menu.
+        let event = if self.gba.key_was_pressed(GbaKey::R) {
+            SoundEvent::Confirm.play();
+            Some(MenuEvent::Selected(MenuChoice::Settings))
+        } else if self.gba.key_was_pressed(GbaKey::SELECT) {
+            SoundEvent::Confirm.play();
+            Some(MenuEvent::Selected(MenuChoice::Puzzles))
+        } else {
+            self.menu.update()
         };
 
-        self.update_cursor_object(&menu_state);
-        self.update_cpu_expression(&menu_state);
+        self.update_series_length_objects(&menu_state);
+        self.update_cpu_expression();
 
-        if self.gba.key_was_pressed(GbaKey::START) || self.gba.key_was_pressed(GbaKey::A) {
-            play_menu_move_noise();
-            self.enter_transition(menu_state.cursor_position);
-        } else {
-            self.state = TitleScreenState::Menu(menu_state);
+        match event {
+            Some(MenuEvent::Selected(MenuChoice::VsCpu)) => {
+                self.enter_transition(GameMode::VsCpu, menu_state.series);
+            }
+            Some(MenuEvent::Selected(MenuChoice::VsPlayer)) => {
+                self.enter_transition(GameMode::VsPlayer, menu_state.series);
+            }
+            // Puzzle mode starts from the first puzzle that hasn't been solved.
+            Some(MenuEvent::Selected(MenuChoice::Puzzles)) => {
+                let puzzle_index = PuzzleProgress::load().first_unsolved();
+                return Some(ScreenState::PuzzleScreen(puzzle_index));
+            }
+            Some(MenuEvent::Selected(MenuChoice::Settings)) => {
+                return Some(ScreenState::SettingsScreen);
+            }
+            Some(MenuEvent::Cancelled) => self.leave_menu(),
+            None => self.state = TitleScreenState::Menu(menu_state),
         }
+
+        None
     }
 
     fn update_transition(&mut self, mut transition_state: TransitionState) -> Option<ScreenState> {
         transition_state.timer -= 1;
 
         if transition_state.timer == 0 {
+            let series = transition_state.series;
+
             match transition_state.game_mode {
-                MenuEntry::VsCpu => Some(ScreenState::VsCpuSpinnerScreen),
-                MenuEntry::VsPlayer => Some(ScreenState::VsPlayerSpinnerScreen),
+                GameMode::VsCpu => Some(ScreenState::CharacterSelectScreen(series)),
+                GameMode::VsPlayer => Some(ScreenState::VsPlayerSpinnerScreen(series)),
             }
         } else {
             self.state = TitleScreenState::GameTransition(transition_state);
@@ -244,77 +286,100 @@ impl<'a> TitleScreen<'a> {
     fn enter_menu(&mut self) {
         self.hide_press_start_text();
 
-        play_menu_move_noise();
+        SoundEvent::Confirm.play();
 
         let menu_state = MenuState {
-            cursor_position: MenuEntry::VsCpu,
+            series: MatchSeries::new(1, settings::current().next_game_start),
         };
 
-        self.update_cursor_object(&menu_state);
+        self.update_series_length_objects(&menu_state);
 
-        // Show menu items and cursor.
-        for obj in [
-            &mut self.vs_cpu_text_object,
-            &mut self.vs_player_text_object,
-            &mut self.cursor_animation_controller.get_obj_attr_entry(),
-        ] {
-            let oa = obj.get_obj_attr_data();
-            oa.set_style(ObjDisplayStyle::Normal);
-        }
+        self.menu.set_entries(&MAIN_MENU);
+        self.menu.show();
 
         self.state = TitleScreenState::Menu(menu_state)
     }
 
-    fn enter_transition(&mut self, game_mode: MenuEntry) {
-        // Hide cursor.
-        let cursor_obj = self.cursor_animation_controller.get_obj_attr_entry();
-        let cursor_oa = cursor_obj.get_obj_attr_data();
-        cursor_oa.set_style(ObjDisplayStyle::NotDisplayed);
-        self.cursor_animation_controller.tick();
+    /// B on the main menu goes back to the "Press Start" prompt.
+    fn leave_menu(&mut self) {
+        self.menu.hide();
+
+        for obj in self.series_length_objects.iter_mut() {
+            obj.set_hidden();
+        }
+
+        self.cpu_face.set_emotion(CpuEmotion::Neutral);
+        self.press_start_text.set_visible();
+
+        self.state = TitleScreenState::PressStart(PressStartState {
+            blinker: Blinker::new(BLINK_TIME_ON, BLINK_TIME_OFF, true),
+        });
+    }
+
+    fn enter_transition(&mut self, game_mode: GameMode, series: MatchSeries) {
+        self.menu.hide_cursor();
+
+        for obj in self.series_length_objects.iter_mut() {
+            obj.set_hidden();
+        }
 
         // Set CPU emotion.
         let cpu_emotion = match game_mode {
-            MenuEntry::VsCpu => CpuEmotion::Surprised,
-            MenuEntry::VsPlayer => CpuEmotion::Sad,
+            GameMode::VsCpu => CpuEmotion::Surprised,
+            GameMode::VsPlayer => CpuEmotion::Sad,
         };
 
         self.cpu_face.set_emotion(cpu_emotion);
 
         let transition_state = TransitionState {
             game_mode,
+            series,
             timer: GAME_TRANSITION_TIME,
         };
         self.state = TitleScreenState::GameTransition(transition_state);
     }
 
-    fn update_cursor_object(&mut self, menu_state: &MenuState) {
-        let target_obj = match menu_state.cursor_position {
-            MenuEntry::VsCpu => self.vs_cpu_text_object.get_obj_attr_data(),
-            MenuEntry::VsPlayer => self.vs_player_text_object.get_obj_attr_data(),
-        };
-        let target_obj_x = target_obj.1.x();
-        let cursor_x = target_obj_x - CURSOR_X_OFFSET;
-
-        let cursor_obj = self.cursor_animation_controller.get_obj_attr_entry();
-        let cursor_oa = cursor_obj.get_obj_attr_data();
-        cursor_oa.set_x(cursor_x);
-        self.cursor_animation_controller.tick();
+    fn update_series_length_objects(&mut self, menu_state: &MenuState) {
+        let token_width: u16 = RED_TOKEN_FRAME_0_SPRITE.width().try_into().unwrap();
+        let num_games: u16 = menu_state.series.best_of().into();
+        let row_width = num_games * (token_width + SERIES_LENGTH_TOKEN_SPACING);
+        let start_x = SCREEN_WIDTH / 2 - row_width / 2;
+
+        for (i, obj) in self.series_length_objects.iter_mut().enumerate() {
+            let i: u16 = i.try_into().unwrap();
+
+            if i < num_games {
+                let oa = obj.get_obj_attr_data();
+                oa.set_x(start_x + i * (token_width + SERIES_LENGTH_TOKEN_SPACING));
+                obj.set_visible();
+            } else {
+                obj.set_hidden();
+            }
+        }
     }
 
-    fn update_cpu_expression(&mut self, menu_state: &MenuState) {
-        let cpu_emotion = match menu_state.cursor_position {
-            MenuEntry::VsCpu => CpuEmotion::Happy,
-            MenuEntry::VsPlayer => CpuEmotion::Mad,
+    fn update_cpu_expression(&mut self) {
+        let cpu_emotion = match self.menu.highlighted() {
+            Some(MenuChoice::VsCpu) => CpuEmotion::Happy,
+            Some(MenuChoice::VsPlayer) => CpuEmotion::Mad,
+            _ => CpuEmotion::Neutral,
         };
 
         self.cpu_face.set_emotion(cpu_emotion);
     }
 
+    fn update_logo_wave(&mut self) {
+        self.logo_wave_phase = self.logo_wave_phase.wrapping_add(LOGO_WAVE_SPEED);
+        self.logo_wave.set_wave(
+            0,
+            LOGO_WAVE_AMPLITUDE,
+            LOGO_WAVE_LENGTH,
+            self.logo_wave_phase,
+        );
+    }
+
     fn hide_press_start_text(&mut self) {
-        for obj in [&mut self.press_text_object, &mut self.start_text_object] {
-            let oa = obj.get_obj_attr_data();
-            oa.set_style(ObjDisplayStyle::NotDisplayed);
-        }
+        self.press_start_text.set_hidden();
     }
 
     fn get_state(&self) -> TitleScreenState {
@@ -322,6 +387,12 @@ impl<'a> TitleScreen<'a> {
     }
 }
 
+impl<'a> Drop for TitleScreen<'a> {
+    fn drop(&mut self) {
+        palette::stop_cycles(PaletteKind::Object);
+    }
+}
+
 impl<'a> Screen for TitleScreen<'a> {
     fn update(&mut self) -> Option<ScreenState> {
         self.background_scroller.update();
@@ -329,15 +400,14 @@ impl<'a> Screen for TitleScreen<'a> {
         self.background_scroller
             .apply_to_background(&self.scrolling_background);
 
+        self.update_logo_wave();
+
         match self.get_state() {
             TitleScreenState::PressStart(state) => {
                 self.update_press_start(state);
                 None
             }
-            TitleScreenState::Menu(state) => {
-                self.update_menu(state);
-                None
-            }
+            TitleScreenState::Menu(state) => self.update_menu(state),
             TitleScreenState::GameTransition(state) => self.update_transition(state),
         }
     }
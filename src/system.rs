@@ -2,3 +2,6 @@ pub mod constants;
 pub mod gba;
 pub mod irq;
 mod memory;
+pub mod save;
+pub mod settings;
+pub mod strings;
@@ -3,11 +3,13 @@ use core::mem::size_of;
 use crate::audio::mixer;
 use crate::audio::noise::enable_noise;
 
-use super::irq::init_irq;
+use super::irq::{init_irq, LAST_VBLANK_LINE};
 use super::memory::block::MemoryBlockManager;
 use super::memory::series::MemorySeriesManager;
 use super::memory::shadow_oam::ShadowOAM;
 use super::memory::strided_grid::MemoryStridedGridManager;
+use super::save::init_save;
+use super::settings::init_settings;
 use gba::prelude::*;
 use voladdress::Safe;
 
@@ -28,6 +30,7 @@ pub type AffineObjectMatrixMemory =
 static GBA_TAKEN: GbaCell<bool> = GbaCell::new(false);
 static PREV_INPUT_STATE: GbaCell<KeyInput> = GbaCell::new(KeyInput::new());
 static CURRENT_INPUT_STATE: GbaCell<KeyInput> = GbaCell::new(KeyInput::new());
+static INPUT_ENABLED: GbaCell<bool> = GbaCell::new(true);
 
 pub const CHARBLOCK_BASE: u16 = 3;
 
@@ -93,15 +96,29 @@ impl GBA {
     }
 
     pub fn input_state(&self) -> KeyInput {
-        CURRENT_INPUT_STATE.read()
+        if INPUT_ENABLED.read() {
+            CURRENT_INPUT_STATE.read()
+        } else {
+            KeyInput::new()
+        }
     }
 
     pub fn key_was_pressed(&self, key: GbaKey) -> bool {
-        read_key(&key, CURRENT_INPUT_STATE.read()) && !read_key(&key, PREV_INPUT_STATE.read())
+        INPUT_ENABLED.read()
+            && read_key(&key, CURRENT_INPUT_STATE.read())
+            && !read_key(&key, PREV_INPUT_STATE.read())
     }
 
     pub fn key_was_released(&self, key: GbaKey) -> bool {
-        read_key(&key, PREV_INPUT_STATE.read()) && !read_key(&key, CURRENT_INPUT_STATE.read())
+        INPUT_ENABLED.read()
+            && read_key(&key, PREV_INPUT_STATE.read())
+            && !read_key(&key, CURRENT_INPUT_STATE.read())
+    }
+
+    /// While disabled, every key reads as up. The input is still tracked, so nothing is seen as
+    /// pressed or released just because it's enabled again.
+    pub fn set_input_enabled(&self, enabled: bool) {
+        INPUT_ENABLED.write(enabled);
     }
 
     fn set_display_mode(&mut self, display_mode: DisplayControl) {
@@ -117,8 +134,13 @@ impl GBA {
                 .with_show_obj(true),
         );
 
-        // Set up the VBLANK IRQ
-        DISPSTAT.write(DisplayStatus::new().with_irq_vblank(true));
+        // Set up the VBLANK IRQ, and the VCOUNT IRQ that restarts the scanline effects each frame.
+        DISPSTAT.write(
+            DisplayStatus::new()
+                .with_irq_vblank(true)
+                .with_irq_vcount(true)
+                .with_vcount_setting(LAST_VBLANK_LINE),
+        );
 
         // We will start TIMER 3 to be used only for seeding RNG
         TIMER3_CONTROL.write(TimerControl::new().with_enabled(true));
@@ -130,6 +152,9 @@ impl GBA {
 
         mixer::init_mixer();
 
+        init_save();
+        init_settings();
+
         init_irq();
     }
 
@@ -1,5 +1,10 @@
 use gba::prelude::*;
 
+use crate::graphics::effects::scanline;
+
+/// The last line of vblank, just before the first line of the next frame is drawn.
+pub const LAST_VBLANK_LINE: u16 = 227;
+
 extern "C" fn irq_handler(irq: IrqBits) {
     let mut handled_interrupts = IrqBits::new();
 
@@ -9,11 +14,17 @@ extern "C" fn irq_handler(irq: IrqBits) {
         handled_interrupts = handled_interrupts.with_vblank(true);
     }
 
+    // Fires on LAST_VBLANK_LINE.
+    if irq.vcounter() {
+        scanline::restart();
+        handled_interrupts = handled_interrupts.with_vcounter(true);
+    }
+
     IF.write(handled_interrupts);
 }
 
 pub fn init_irq() {
-    IE.write(IrqBits::new().with_vblank(true));
+    IE.write(IrqBits::new().with_vblank(true).with_vcounter(true));
 
     IME.write(true);
 
@@ -1,4 +1,4 @@
-use core::cell::UnsafeCell;
+use core::cell::{Cell, UnsafeCell};
 
 use gba::prelude::*;
 
@@ -12,6 +12,8 @@ const OAM_SIZE: usize = 128;
 pub struct ShadowOAM {
     mem: [UnsafeCell<ObjAttr>; OAM_SIZE],
     tracker: MemorySlotTracker<OAM_SIZE>,
+    // Applied to every object as it's synced, for screen transitions.
+    mosaic: Cell<bool>,
 }
 
 pub struct OAMEntry<'a> {
@@ -42,6 +44,7 @@ impl ShadowOAM {
         Self {
             mem,
             tracker: MemorySlotTracker::new(),
+            mosaic: Cell::new(false),
         }
     }
 
@@ -59,14 +62,22 @@ impl ShadowOAM {
         }
     }
 
+    /// Turn the mosaic effect on or off for every object.
+    pub fn set_mosaic(&self, enabled: bool) {
+        self.mosaic.set(enabled);
+    }
+
     /// Sync the shadow OAM to the real OAM
     ///
     /// # Safety
     /// Must be called during VBLANK
     pub unsafe fn sync(&self) {
+        let mosaic = self.mosaic.get();
+
         for i in 0..OAM_SIZE {
-            let oa = &*self.mem[i].get();
-            OBJ_ATTR_ALL.index(i).write(*oa);
+            let mut oa = *self.mem[i].get();
+            oa.0 = oa.0.with_mosaic(mosaic);
+            OBJ_ATTR_ALL.index(i).write(oa);
         }
     }
 }
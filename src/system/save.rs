@@ -0,0 +1,54 @@
+use voladdress::{Safe, VolBlock};
+
+const SRAM_SIZE: usize = 0x8000;
+
+// Cartridge SRAM is on an 8-bit bus, so it must only ever be accessed a byte at a time.
+const SRAM: VolBlock<u8, Safe, Safe, SRAM_SIZE> = unsafe { VolBlock::new(0x0E00_0000) };
+
+// Written at the start of SRAM, so that uninitialised or foreign save data can be detected.
+const SAVE_MAGIC: [u8; 4] = *b"J4T1";
+const SAVE_MAGIC_OFFSET: usize = 0;
+
+pub const PUZZLE_PROGRESS_OFFSET: usize = 4;
+pub const PUZZLE_PROGRESS_SIZE: usize = 8;
+
+pub const SETTINGS_OFFSET: usize = PUZZLE_PROGRESS_OFFSET + PUZZLE_PROGRESS_SIZE;
+pub const SETTINGS_SIZE: usize = 10;
+
+const SAVE_DATA_SIZE: usize = SETTINGS_OFFSET + SETTINGS_SIZE;
+
+// Emulators and flashcarts scan the ROM for this string to work out which kind of save memory
+// the game expects. It needs to be word aligned and padded to a multiple of 4 bytes.
+#[repr(C, align(4))]
+struct SaveTypeId([u8; 12]);
+
+static SAVE_TYPE_ID: SaveTypeId = SaveTypeId(*b"SRAM_V113\0\0\0");
+
+/// Make sure SRAM holds valid save data, clearing it if it doesn't.
+pub fn init_save() {
+    // Reference the save type string so that the linker can't discard it.
+    let _ = unsafe { core::ptr::read_volatile(&SAVE_TYPE_ID.0[0]) };
+
+    let mut magic = [0; SAVE_MAGIC.len()];
+    read_bytes(SAVE_MAGIC_OFFSET, &mut magic);
+
+    if magic != SAVE_MAGIC {
+        for i in 0..SAVE_DATA_SIZE {
+            SRAM.index(i).write(0);
+        }
+
+        write_bytes(SAVE_MAGIC_OFFSET, &SAVE_MAGIC);
+    }
+}
+
+pub fn read_bytes(offset: usize, buffer: &mut [u8]) {
+    for (i, byte) in buffer.iter_mut().enumerate() {
+        *byte = SRAM.index(offset + i).read();
+    }
+}
+
+pub fn write_bytes(offset: usize, data: &[u8]) {
+    for (i, byte) in data.iter().enumerate() {
+        SRAM.index(offset + i).write(*byte);
+    }
+}
@@ -0,0 +1,168 @@
+use crate::{
+    audio::{
+        mixer::{self, UNITY_GAIN},
+        noise::{self, MAX_PSG_VOLUME},
+    },
+    graphics::sprite::set_colour_blind_palette,
+    screens::match_series::NextGameStart,
+};
+
+use super::{
+    save::{self, SETTINGS_OFFSET, SETTINGS_SIZE},
+    strings::Language,
+};
+
+static mut SETTINGS: Settings = Settings::DEFAULT;
+
+// Stored ahead of the settings, so that save data from before settings existed isn't misread.
+const SETTINGS_VERSION: u8 = 1;
+
+pub const MAX_VOLUME: u8 = 8;
+
+/// How quickly the cursor slides between columns.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum CursorSpeed {
+    Slow,
+    Normal,
+    Fast,
+}
+
+/// How long the CPU pauses between each step of its cursor, so its thinking can be followed.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum CpuDelay {
+    Short,
+    Normal,
+    Long,
+}
+
+/// The time of day the game is played at, which tints the board and the sky behind it.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum BoardVariant {
+    Day,
+    Dusk,
+    Night,
+}
+
+/// The player's options, which are kept in SRAM.
+#[derive(Clone, Copy)]
+pub struct Settings {
+    pub music_volume: u8,
+    pub sfx_volume: u8,
+    pub cursor_speed: CursorSpeed,
+    pub cpu_delay: CpuDelay,
+    pub colour_blind: bool,
+    /// Draw a circle or a cross on each token, as well as colouring them.
+    pub shape_markers: bool,
+    pub language: Language,
+    /// Who goes first in each game of a series after the first.
+    pub next_game_start: NextGameStart,
+    pub board_variant: BoardVariant,
+}
+
+impl CursorSpeed {
+    pub const ALL: [Self; 3] = [Self::Slow, Self::Normal, Self::Fast];
+}
+
+impl CpuDelay {
+    pub const ALL: [Self; 3] = [Self::Short, Self::Normal, Self::Long];
+}
+
+impl BoardVariant {
+    pub const ALL: [Self; 3] = [Self::Day, Self::Dusk, Self::Night];
+}
+
+impl Settings {
+    pub const DEFAULT: Self = Self {
+        music_volume: MAX_VOLUME,
+        sfx_volume: MAX_VOLUME,
+        cursor_speed: CursorSpeed::Normal,
+        cpu_delay: CpuDelay::Normal,
+        colour_blind: false,
+        shape_markers: false,
+        language: Language::DEFAULT,
+        next_game_start: NextGameStart::LoserStarts,
+        board_variant: BoardVariant::Day,
+    };
+
+    /// Read the settings from SRAM. Anything missing or invalid falls back to its default.
+    pub fn load() -> Self {
+        let mut bytes = [0; SETTINGS_SIZE];
+        save::read_bytes(SETTINGS_OFFSET, &mut bytes);
+
+        if bytes[0] != SETTINGS_VERSION {
+            return Self::DEFAULT;
+        }
+
+        let default = Self::DEFAULT;
+
+        Self {
+            music_volume: Some(bytes[1])
+                .filter(|v| *v <= MAX_VOLUME)
+                .unwrap_or(default.music_volume),
+            sfx_volume: Some(bytes[2])
+                .filter(|v| *v <= MAX_VOLUME)
+                .unwrap_or(default.sfx_volume),
+            cursor_speed: CursorSpeed::ALL
+                .get(usize::from(bytes[3]))
+                .copied()
+                .unwrap_or(default.cursor_speed),
+            cpu_delay: CpuDelay::ALL
+                .get(usize::from(bytes[4]))
+                .copied()
+                .unwrap_or(default.cpu_delay),
+            colour_blind: bytes[5] == 1,
+            shape_markers: bytes[6] == 1,
+            language: Language::from_index(bytes[7]).unwrap_or(default.language),
+            next_game_start: NextGameStart::ALL
+                .get(usize::from(bytes[8]))
+                .copied()
+                .unwrap_or(default.next_game_start),
+            board_variant: BoardVariant::ALL
+                .get(usize::from(bytes[9]))
+                .copied()
+                .unwrap_or(default.board_variant),
+        }
+    }
+
+    pub fn save(&self) {
+        let mut bytes = [0; SETTINGS_SIZE];
+
+        bytes[0] = SETTINGS_VERSION;
+        bytes[1] = self.music_volume;
+        bytes[2] = self.sfx_volume;
+        bytes[3] = self.cursor_speed as u8;
+        bytes[4] = self.cpu_delay as u8;
+        bytes[5] = self.colour_blind.into();
+        bytes[6] = self.shape_markers.into();
+        bytes[7] = self.language.index();
+        bytes[8] = self.next_game_start as u8;
+        bytes[9] = self.board_variant as u8;
+
+        save::write_bytes(SETTINGS_OFFSET, &bytes);
+    }
+
+    /// Make these the current settings, taking effect straight away.
+    pub fn apply(&self) {
+        unsafe {
+            SETTINGS = *self;
+        }
+
+        mixer::set_master_gains(volume_gain(self.music_volume), volume_gain(self.sfx_volume));
+        noise::set_psg_volume(u16::from(self.sfx_volume) * MAX_PSG_VOLUME / u16::from(MAX_VOLUME));
+        set_colour_blind_palette(self.colour_blind);
+    }
+}
+
+fn volume_gain(volume: u8) -> u16 {
+    u16::from(volume) * UNITY_GAIN / u16::from(MAX_VOLUME)
+}
+
+/// Load the saved settings and apply them. SRAM must have been initialised first.
+pub fn init_settings() {
+    Settings::load().apply();
+}
+
+/// The settings currently in effect.
+pub fn current() -> Settings {
+    unsafe { SETTINGS }
+}
@@ -0,0 +1,61 @@
+use super::settings;
+
+// Marks where a value is filled in to a message.
+const PLACEHOLDER: &str = "{}";
+
+/// One language's translation of every message.
+pub struct StringTable {
+    /// The name of the language, written in that language.
+    name: &'static str,
+    messages: [&'static str; NUM_MESSAGES],
+}
+
+/// One of the languages built from the string files.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Language(u8);
+
+impl Language {
+    /// The language the messages were first written in.
+    pub const DEFAULT: Self = Self(0);
+
+    pub fn from_index(index: u8) -> Option<Self> {
+        (usize::from(index) < LANGUAGES.len()).then_some(Self(index))
+    }
+
+    pub fn index(&self) -> u8 {
+        self.0
+    }
+
+    pub fn count() -> usize {
+        LANGUAGES.len()
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.table().name
+    }
+
+    fn table(&self) -> &'static StringTable {
+        &LANGUAGES[usize::from(self.0)]
+    }
+}
+
+impl Message {
+    /// The message in the language picked in the settings.
+    pub fn get(&self) -> &'static str {
+        self.get_in(settings::current().language)
+    }
+
+    pub fn get_in(&self, language: Language) -> &'static str {
+        language.table().messages[*self as usize]
+    }
+
+    /// The message either side of its `{}`, so that the value can be drawn in between,
+    /// such as a player's name in their colour.
+    pub fn split(&self) -> (&'static str, &'static str) {
+        let message = self.get();
+        message.split_once(PLACEHOLDER).unwrap_or((message, ""))
+    }
+}
+
+// Insert the string tables generated by the build script.
+include!(concat!(env!("OUT_DIR"), "/string_data.rs"));
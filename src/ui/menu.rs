@@ -0,0 +1,412 @@
+use crate::{
+    audio::{events::SoundEvent, noise::play_menu_move_noise},
+    graphics::{
+        sprite::{AnimationController, LoadedAnimation, MENU_CURSOR_FRAME_0_SPRITE},
+        text::{self, TextSprite, FONT},
+    },
+    system::{
+        gba::{GbaKey, GBA},
+        strings::Message,
+    },
+};
+
+// Enough 32 pixel wide objects for the longest translation of any menu entry.
+const ENTRY_TEXT_OBJECTS: usize = 3;
+
+// How far the cursor sits to the left of the entry it points at.
+const CURSOR_X_OFFSET: u16 = 10;
+
+const MAX_MENU_DEPTH: usize = 4;
+const MAX_DISABLED_ENTRIES: usize = 4;
+
+/// Where a menu's entries are drawn. Entries are measured from the cursor in front of them.
+#[derive(Clone, Copy)]
+pub enum MenuLayout {
+    /// Side by side, spread evenly across `width` pixels. Moved through with left and right.
+    Horizontal { x: u16, y: u16, width: u16 },
+    /// One above the other, `spacing` pixels apart. Moved through with up and down.
+    Vertical { x: u16, y: u16, spacing: u16 },
+}
+
+#[derive(Clone, Copy)]
+pub enum MenuAction<T: 'static> {
+    /// Hand the value back to the screen.
+    Select(T),
+    /// Swap the entries for a sub-menu. B goes back to the menu it was opened from.
+    Open(&'static [MenuEntry<T>]),
+}
+
+/// One line of a menu. Menus are declared as static arrays of these.
+#[derive(Clone, Copy)]
+pub struct MenuEntry<T: 'static> {
+    label: Message,
+    action: MenuAction<T>,
+    sound: Option<SoundEvent>,
+}
+
+pub enum MenuEvent<T> {
+    Selected(T),
+    /// B was pressed at the top level of a menu created `with_cancel`.
+    Cancelled,
+}
+
+#[derive(Clone, Copy)]
+struct ParentMenu<T: 'static> {
+    entries: &'static [MenuEntry<T>],
+    highlighted: usize,
+}
+
+/// A list of entries with an animated cursor, which handles its own input and sounds.
+/// `N` is the most entries shown at once, across the menu and all of its sub-menus.
+pub struct Menu<'a, T: Copy + PartialEq + 'static, const N: usize> {
+    gba: &'a GBA,
+    entries: &'static [MenuEntry<T>],
+    parents: [Option<ParentMenu<T>>; MAX_MENU_DEPTH],
+    texts: [TextSprite<'a, ENTRY_TEXT_OBJECTS>; N],
+    cursor_animation_controller: AnimationController<'a, 5>,
+    layout: MenuLayout,
+    highlighted: usize,
+    disabled: [Option<T>; MAX_DISABLED_ENTRIES],
+    wraparound: bool,
+    cancellable: bool,
+    visible: bool,
+}
+
+impl<T: Copy + 'static> MenuEntry<T> {
+    pub const fn new(label: Message, value: T) -> Self {
+        Self {
+            label,
+            action: MenuAction::Select(value),
+            sound: Some(SoundEvent::Confirm),
+        }
+    }
+
+    pub const fn submenu(label: Message, entries: &'static [MenuEntry<T>]) -> Self {
+        Self {
+            label,
+            action: MenuAction::Open(entries),
+            sound: Some(SoundEvent::Confirm),
+        }
+    }
+
+    /// Play a different sound when the entry is picked, or none at all.
+    pub const fn with_sound(self, sound: Option<SoundEvent>) -> Self {
+        Self {
+            label: self.label,
+            action: self.action,
+            sound,
+        }
+    }
+
+    fn value(&self) -> Option<T> {
+        match self.action {
+            MenuAction::Select(value) => Some(value),
+            MenuAction::Open(_) => None,
+        }
+    }
+}
+
+impl<'a, T: Copy + PartialEq + 'static, const N: usize> Menu<'a, T, N> {
+    /// The menu starts hidden, with the first entry highlighted.
+    pub fn new(
+        gba: &'a GBA,
+        cursor_animation: &'a LoadedAnimation<'a, 5>,
+        entries: &'static [MenuEntry<T>],
+        layout: MenuLayout,
+    ) -> Self {
+        let mut cursor_animation_controller = cursor_animation.create_controller(gba);
+        cursor_animation_controller.set_hidden();
+
+        let mut menu = Self {
+            gba,
+            entries,
+            parents: [None; MAX_MENU_DEPTH],
+            texts: core::array::from_fn(|_| TextSprite::new(gba)),
+            cursor_animation_controller,
+            layout,
+            highlighted: 0,
+            disabled: [None; MAX_DISABLED_ENTRIES],
+            wraparound: false,
+            cancellable: false,
+            visible: false,
+        };
+
+        menu.set_entries(entries);
+        menu
+    }
+
+    /// Moving past the last entry goes back round to the first, and vice versa.
+    pub fn with_wraparound(mut self) -> Self {
+        self.wraparound = true;
+        self
+    }
+
+    /// B at the top level gives a `MenuEvent::Cancelled`, rather than being ignored.
+    pub fn with_cancel(mut self) -> Self {
+        self.cancellable = true;
+        self
+    }
+
+    /// Handle this frame's input, returning what was picked, if anything.
+    pub fn update(&mut self) -> Option<MenuEvent<T>> {
+        self.cursor_animation_controller.tick();
+
+        let (back_key, forward_key) = match self.layout {
+            MenuLayout::Horizontal { .. } => (GbaKey::LEFT, GbaKey::RIGHT),
+            MenuLayout::Vertical { .. } => (GbaKey::UP, GbaKey::DOWN),
+        };
+
+        if self.gba.key_was_pressed(back_key) {
+            self.move_highlight(false);
+        } else if self.gba.key_was_pressed(forward_key) {
+            self.move_highlight(true);
+        }
+
+        if self.gba.key_was_pressed(GbaKey::A) || self.gba.key_was_pressed(GbaKey::START) {
+            self.confirm()
+        } else if self.gba.key_was_pressed(GbaKey::B) {
+            self.back()
+        } else {
+            None
+        }
+    }
+
+    /// The value of the highlighted entry, or `None` if it opens a sub-menu.
+    pub fn highlighted(&self) -> Option<T> {
+        self.entries[self.highlighted].value()
+    }
+
+    /// Highlight the entry with this value, if it is in the entries being shown.
+    pub fn select(&mut self, value: T) {
+        if let Some(index) = self
+            .entries
+            .iter()
+            .position(|entry| entry.value() == Some(value))
+        {
+            self.highlighted = index;
+            self.update_cursor();
+        }
+    }
+
+    /// Go back to the top level with a new set of entries.
+    pub fn set_entries(&mut self, entries: &'static [MenuEntry<T>]) {
+        self.parents = [None; MAX_MENU_DEPTH];
+        self.open(entries, 0);
+    }
+
+    /// Disabled entries are greyed out, and the cursor skips over them.
+    pub fn set_enabled(&mut self, value: T, enabled: bool) {
+        let existing = self.disabled.iter().position(|v| *v == Some(value));
+
+        match (existing, enabled) {
+            (Some(index), true) => self.disabled[index] = None,
+            (None, false) => {
+                let free = self
+                    .disabled
+                    .iter_mut()
+                    .find(|v| v.is_none())
+                    .expect("Too many disabled menu entries.");
+                *free = Some(value);
+            }
+            _ => return,
+        }
+
+        if !self.is_enabled(self.highlighted) {
+            self.highlighted = self.first_enabled_entry();
+        }
+
+        self.update_text();
+    }
+
+    pub fn show(&mut self) {
+        self.visible = true;
+        self.update_visibility();
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.update_visibility();
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Leave the entries on screen, but stop pointing at one.
+    pub fn hide_cursor(&mut self) {
+        self.cursor_animation_controller.set_hidden();
+    }
+
+    /// Redraw the entries, e.g. after the language has changed.
+    pub fn refresh(&mut self) {
+        self.update_text();
+    }
+
+    fn open(&mut self, entries: &'static [MenuEntry<T>], highlighted: usize) {
+        assert!(entries.len() <= N, "Too many menu entries.");
+
+        self.entries = entries;
+        self.highlighted = highlighted;
+
+        if !self.is_enabled(self.highlighted) {
+            self.highlighted = self.first_enabled_entry();
+        }
+
+        self.update_text();
+        self.update_visibility();
+    }
+
+    fn confirm(&mut self) -> Option<MenuEvent<T>> {
+        let entry = self.entries[self.highlighted];
+
+        if !self.is_enabled(self.highlighted) {
+            return None;
+        }
+
+        if let Some(sound) = entry.sound {
+            sound.play();
+        }
+
+        match entry.action {
+            MenuAction::Select(value) => Some(MenuEvent::Selected(value)),
+            MenuAction::Open(entries) => {
+                let parent = self
+                    .parents
+                    .iter_mut()
+                    .find(|parent| parent.is_none())
+                    .expect("Menus are nested too deeply.");
+
+                *parent = Some(ParentMenu {
+                    entries: self.entries,
+                    highlighted: self.highlighted,
+                });
+
+                self.open(entries, 0);
+                None
+            }
+        }
+    }
+
+    fn back(&mut self) -> Option<MenuEvent<T>> {
+        // Return to the menu this one was opened from, with the same entry highlighted.
+        if let Some(parent) = self
+            .parents
+            .iter_mut()
+            .rev()
+            .find(|parent| parent.is_some())
+        {
+            let ParentMenu {
+                entries,
+                highlighted,
+            } = parent.take().unwrap();
+
+            SoundEvent::Cancel.play();
+            self.open(entries, highlighted);
+
+            return None;
+        }
+
+        if self.cancellable {
+            SoundEvent::Cancel.play();
+            Some(MenuEvent::Cancelled)
+        } else {
+            None
+        }
+    }
+
+    /// Move to the next enabled entry in either direction, staying put if there isn't one.
+    fn move_highlight(&mut self, forward: bool) {
+        let len = self.entries.len();
+        let mut index = self.highlighted;
+
+        for _ in 1..len {
+            index = match (forward, self.wraparound) {
+                (true, _) if index + 1 < len => index + 1,
+                (true, true) => 0,
+                (false, _) if index > 0 => index - 1,
+                (false, true) => len - 1,
+                _ => return,
+            };
+
+            if self.is_enabled(index) {
+                play_menu_move_noise();
+                self.highlighted = index;
+                self.update_cursor();
+                return;
+            }
+        }
+    }
+
+    fn is_enabled(&self, index: usize) -> bool {
+        match self.entries[index].value() {
+            Some(value) => !self.disabled.contains(&Some(value)),
+            None => true,
+        }
+    }
+
+    fn first_enabled_entry(&self) -> usize {
+        (0..self.entries.len())
+            .find(|index| self.is_enabled(*index))
+            .unwrap_or(0)
+    }
+
+    /// Draw each entry's label and lay them out.
+    fn update_text(&mut self) {
+        let len = self.entries.len();
+        let enabled: [bool; N] = core::array::from_fn(|i| i < len && self.is_enabled(i));
+        let mut widths = [0; N];
+
+        for (i, (text, entry)) in self.texts.iter_mut().zip(self.entries).enumerate() {
+            text.set_dimmed(!enabled[i]);
+            text.set_text(entry.label.get());
+            widths[i] = CURSOR_X_OFFSET + text.width();
+        }
+
+        match self.layout {
+            MenuLayout::Horizontal { x, y, width } => {
+                let starts = text::spread_evenly(&widths[..len], x, width);
+
+                for (text, start) in self.texts.iter_mut().zip(starts) {
+                    text.set_position(start + CURSOR_X_OFFSET, y);
+                }
+            }
+            MenuLayout::Vertical { x, y, spacing } => {
+                for (i, text) in self.texts.iter_mut().take(len).enumerate() {
+                    let i: u16 = i.try_into().unwrap();
+                    text.set_position(x + CURSOR_X_OFFSET, y + i * spacing);
+                }
+            }
+        }
+
+        self.update_cursor();
+    }
+
+    fn update_visibility(&mut self) {
+        let len = self.entries.len();
+
+        for (i, text) in self.texts.iter_mut().enumerate() {
+            if self.visible && i < len {
+                text.set_visible();
+            } else {
+                text.set_hidden();
+            }
+        }
+
+        if self.visible {
+            self.cursor_animation_controller.set_visible();
+        } else {
+            self.cursor_animation_controller.set_hidden();
+        }
+    }
+
+    /// Point the cursor at the highlighted entry, centred against its line of text.
+    fn update_cursor(&mut self) {
+        let (text_x, text_y) = self.texts[self.highlighted].position();
+        let cursor_height: u16 = MENU_CURSOR_FRAME_0_SPRITE.height().try_into().unwrap();
+
+        let cursor_obj = self.cursor_animation_controller.get_obj_attr_entry();
+        let cursor_oa = cursor_obj.get_obj_attr_data();
+        cursor_oa.set_x(text_x - CURSOR_X_OFFSET);
+        cursor_oa.set_y(text_y + FONT.line_height() / 2 - cursor_height / 2);
+    }
+}